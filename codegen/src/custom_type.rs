@@ -0,0 +1,186 @@
+//! Code generation for `#[derive(RhaiType)]`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    spanned::Spanned, Attribute, Data, DeriveInput, Error, Fields, Index, Lit, Meta, NestedMeta,
+};
+
+/// Name of the attribute used to configure a derived custom type.
+pub const TYPE_ATTR: &str = "rhai";
+
+/// Options specified via `#[rhai(...)]` attributes on a field.
+#[derive(Debug, Default)]
+struct FieldOptions {
+    get: bool,
+    set: bool,
+    rename: Option<String>,
+    skip: bool,
+}
+
+/// Parse all `#[rhai(...)]` attributes, calling `f` for each option.
+fn parse_attrs(
+    attrs: &[Attribute],
+    mut f: impl FnMut(&NestedMeta) -> syn::Result<bool>,
+) -> syn::Result<()> {
+    for attr in attrs.iter().filter(|a| a.path.is_ident(TYPE_ATTR)) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(Error::new_spanned(meta, "expecting `#[rhai(...)]`")),
+        };
+
+        for item in &list.nested {
+            if !f(item)? {
+                return Err(Error::new_spanned(item, "unknown option"));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the string value of a `key = "value"` option.
+fn parse_name(item: &NestedMeta, key: &str) -> syn::Result<Option<String>> {
+    match item {
+        NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident(key) => match &nv.lit {
+            Lit::Str(s) if !s.value().trim().is_empty() => Ok(Some(s.value())),
+            lit => Err(Error::new_spanned(lit, "expecting a name")),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// Is this option a flag with the specified name?
+fn is_flag(item: &NestedMeta, flag: &str) -> bool {
+    match item {
+        NestedMeta::Meta(Meta::Path(path)) => path.is_ident(flag),
+        _ => false,
+    }
+}
+
+impl FieldOptions {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Self::default();
+
+        parse_attrs(attrs, |item| {
+            if is_flag(item, "get") {
+                options.get = true;
+            } else if is_flag(item, "set") {
+                options.set = true;
+            } else if is_flag(item, "skip") {
+                options.skip = true;
+            } else if let Some(name) = parse_name(item, "rename")? {
+                options.rename = Some(name);
+            } else {
+                return Ok(false);
+            }
+            Ok(true)
+        })?;
+
+        // Without `get` or `set`, a field has both a getter and a setter.
+        if !options.get && !options.set {
+            options.get = true;
+            options.set = true;
+        }
+
+        Ok(options)
+    }
+}
+
+/// Generate the implementation of `RhaiType` for a struct.
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`RhaiType` cannot be derived for generic types",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`RhaiType` can only be derived for structs",
+            ))
+        }
+    };
+
+    let ty = &input.ident;
+    let mut type_name = ty.to_string();
+
+    parse_attrs(&input.attrs, |item| {
+        Ok(match parse_name(item, "name")? {
+            Some(name) => {
+                type_name = name;
+                true
+            }
+            None => false,
+        })
+    })?;
+
+    let mut registrations = Vec::new();
+
+    let fields: Vec<_> = match fields {
+        Fields::Named(fields) => fields.named.iter().collect(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+        Fields::Unit => Vec::new(),
+    };
+
+    for (index, field) in fields.into_iter().enumerate() {
+        let options = FieldOptions::parse(&field.attrs)?;
+
+        if options.skip {
+            continue;
+        }
+
+        let member = match &field.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let index = Index::from(index);
+                quote! { #index }
+            }
+        };
+
+        let name = match (options.rename, &field.ident) {
+            (Some(name), _) => name,
+            (None, Some(ident)) => ident.to_string(),
+            (None, None) => {
+                return Err(Error::new(
+                    field.span(),
+                    "fields of tuple structs must be renamed via `#[rhai(rename = \"...\")]` or skipped",
+                ))
+            }
+        };
+        let field_ty = &field.ty;
+
+        if options.get {
+            registrations.push(quote! {
+                engine.register_get(#name, |obj: &mut #ty| obj.#member.clone());
+            });
+        }
+        if options.set {
+            registrations.push(quote! {
+                engine.register_set(#name, |obj: &mut #ty, value: #field_ty| obj.#member = value);
+            });
+        }
+    }
+
+    Ok(quote! {
+        impl rhai::plugins::RhaiType for #ty {
+            fn register_rhai_type(engine: &mut rhai::Engine) {
+                #[allow(unused_imports)]
+                use rhai::plugins::__private::*;
+
+                engine.register_type_with_name::<#ty>(#type_name);
+
+                #(#registrations)*
+
+                let probe = &&&Probe::<#ty>::new();
+                probe.register_to_string(engine);
+                probe.register_debug(engine);
+                probe.register_eq(engine);
+            }
+        }
+    })
+}
@@ -0,0 +1,420 @@
+//! Parsing and code generation for functions exported from a plugin module.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    spanned::Spanned, Attribute, Error, FnArg, Ident, ItemFn, Lit, Meta, NestedMeta, Pat,
+    ReturnType, Type,
+};
+
+/// Maximum number of parameters an exported function can take.
+pub const MAX_PARAMS: usize = 4;
+
+/// Name of the attribute used to configure an exported function.
+pub const FN_ATTR: &str = "rhai_fn";
+
+/// How an exported function is registered into the module.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FnKind {
+    /// A normal function, registered under one or more names.
+    Normal(Vec<String>),
+    /// A property getter.
+    Getter(String),
+    /// A property setter.
+    Setter(String),
+    /// An index getter.
+    IndexGetter,
+    /// An index setter.
+    IndexSetter,
+}
+
+/// How a parameter is passed to the exported function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PassBy {
+    /// Passed by value.
+    Value,
+    /// Passed by shared reference to a value owned by the wrapper.
+    Ref,
+    /// Passed by mutable reference (only allowed for the first parameter).
+    Mut,
+}
+
+/// A parameter of an exported function.
+struct Param {
+    /// Name of the parameter, used for metadata.
+    name: String,
+    /// Type of the value registered with Rhai.
+    ty: Type,
+    /// How the value is passed to the function.
+    pass_by: PassBy,
+}
+
+/// A function exported from a plugin module.
+pub struct ExportedFn {
+    /// Name of the Rust function.
+    name: Ident,
+    /// How the function is registered.
+    kind: FnKind,
+    /// Parameters of the function.
+    params: Vec<Param>,
+    /// Does the function return a `Result`?
+    fallible: bool,
+    /// Documentation of the function, taken from its doc comments.
+    doc: String,
+}
+
+/// Options specified via `#[rhai_fn(...)]` attributes.
+#[derive(Debug, Default)]
+struct FnOptions {
+    names: Vec<String>,
+    getter: Option<String>,
+    setter: Option<String>,
+    index_get: bool,
+    index_set: bool,
+    skip: bool,
+}
+
+impl FnOptions {
+    /// Parse and remove all `#[rhai_fn(...)]` attributes.
+    fn extract(attrs: &mut Vec<Attribute>) -> syn::Result<(Self, Span)> {
+        let mut options = Self::default();
+        let mut span = Span::call_site();
+
+        for attr in attrs.iter().filter(|a| a.path.is_ident(FN_ATTR)) {
+            span = attr.span();
+
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                meta => return Err(Error::new_spanned(meta, "expecting `#[rhai_fn(...)]`")),
+            };
+
+            for item in list.nested {
+                match item {
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => {
+                        options.skip = true
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("index_get") => {
+                        options.index_get = true
+                    }
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("index_set") => {
+                        options.index_set = true
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) => {
+                        let value = match &nv.lit {
+                            Lit::Str(s) if !s.value().trim().is_empty() => s.value(),
+                            lit => return Err(Error::new_spanned(lit, "expecting a name")),
+                        };
+
+                        if nv.path.is_ident("name") {
+                            options.names.push(value);
+                        } else if nv.path.is_ident("get") {
+                            options.getter = Some(value);
+                        } else if nv.path.is_ident("set") {
+                            options.setter = Some(value);
+                        } else {
+                            return Err(Error::new_spanned(nv.path, "unknown option"));
+                        }
+                    }
+                    item => return Err(Error::new_spanned(item, "unknown option")),
+                }
+            }
+        }
+
+        attrs.retain(|a| !a.path.is_ident(FN_ATTR));
+
+        Ok((options, span))
+    }
+
+    /// Map the options into the kind of function to register.
+    fn into_kind(self, name: &Ident, span: Span) -> syn::Result<FnKind> {
+        let specials = self.getter.is_some() as usize
+            + self.setter.is_some() as usize
+            + self.index_get as usize
+            + self.index_set as usize;
+
+        if specials > 1 || (specials > 0 && !self.names.is_empty()) {
+            return Err(Error::new(
+                span,
+                "conflicting options: `name`, `get`, `set`, `index_get` and `index_set` cannot be combined",
+            ));
+        }
+
+        Ok(if let Some(prop) = self.getter {
+            FnKind::Getter(prop)
+        } else if let Some(prop) = self.setter {
+            FnKind::Setter(prop)
+        } else if self.index_get {
+            FnKind::IndexGetter
+        } else if self.index_set {
+            FnKind::IndexSetter
+        } else if self.names.is_empty() {
+            FnKind::Normal(vec![name.to_string()])
+        } else {
+            FnKind::Normal(self.names)
+        })
+    }
+}
+
+/// Collect the text of all doc comments.
+fn extract_doc(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|a| a.path.is_ident("doc"))
+        .filter_map(|a| match a.parse_meta() {
+            Ok(Meta::NameValue(nv)) => match nv.lit {
+                Lit::Str(s) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Is this type a path ending with one of the given names?
+fn is_path_to(ty: &Type, names: &[&str]) -> bool {
+    match ty {
+        Type::Path(p) if p.qself.is_none() => p
+            .path
+            .segments
+            .last()
+            .map(|s| names.iter().any(|n| s.ident == n))
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+impl ExportedFn {
+    /// Parse a public function inside an exported module.
+    ///
+    /// Returns `None` if the function is marked with `#[rhai_fn(skip)]`.
+    pub fn parse(item: &mut ItemFn) -> syn::Result<Option<Self>> {
+        let (options, span) = FnOptions::extract(&mut item.attrs)?;
+
+        if options.skip {
+            return Ok(None);
+        }
+
+        let sig = &item.sig;
+
+        if let Some(token) = &sig.asyncness {
+            return Err(Error::new_spanned(
+                token,
+                "async functions cannot be exported",
+            ));
+        }
+        if let Some(token) = &sig.unsafety {
+            return Err(Error::new_spanned(
+                token,
+                "unsafe functions cannot be exported",
+            ));
+        }
+        if let Some(abi) = &sig.abi {
+            return Err(Error::new_spanned(
+                abi,
+                "extern functions cannot be exported",
+            ));
+        }
+        if let Some(variadic) = &sig.variadic {
+            return Err(Error::new_spanned(
+                variadic,
+                "variadic functions cannot be exported",
+            ));
+        }
+        if !sig.generics.params.is_empty() {
+            return Err(Error::new_spanned(
+                &sig.generics,
+                "generic functions cannot be exported",
+            ));
+        }
+
+        let mut params = Vec::with_capacity(sig.inputs.len());
+
+        for (index, arg) in sig.inputs.iter().enumerate() {
+            let arg = match arg {
+                FnArg::Typed(arg) => arg,
+                FnArg::Receiver(arg) => {
+                    return Err(Error::new_spanned(
+                        arg,
+                        "`self` parameters are not supported",
+                    ))
+                }
+            };
+
+            let name = match &*arg.pat {
+                Pat::Ident(pat) => pat.ident.to_string(),
+                _ => "_".to_string(),
+            };
+
+            let (ty, pass_by) = match &*arg.ty {
+                Type::Reference(r) if r.mutability.is_some() => {
+                    if index > 0 {
+                        return Err(Error::new_spanned(
+                            &arg.ty,
+                            "only the first parameter can be passed by `&mut`",
+                        ));
+                    }
+                    match &*r.elem {
+                        ty if is_path_to(ty, &["str"]) => {
+                            return Err(Error::new_spanned(
+                                &arg.ty,
+                                "use `&mut ImmutableString` instead of `&mut str`",
+                            ))
+                        }
+                        Type::Path(_) => ((*r.elem).clone(), PassBy::Mut),
+                        _ => return Err(Error::new_spanned(&arg.ty, "unsupported parameter type")),
+                    }
+                }
+                Type::Reference(r) if is_path_to(&r.elem, &["str"]) => {
+                    (syn::parse_quote!(rhai::ImmutableString), PassBy::Ref)
+                }
+                Type::Reference(r) => match &*r.elem {
+                    Type::Path(_) => ((*r.elem).clone(), PassBy::Ref),
+                    _ => return Err(Error::new_spanned(&arg.ty, "unsupported parameter type")),
+                },
+                Type::ImplTrait(_) | Type::TraitObject(_) | Type::Infer(_) | Type::Never(_) => {
+                    return Err(Error::new_spanned(&arg.ty, "unsupported parameter type"))
+                }
+                ty => (ty.clone(), PassBy::Value),
+            };
+
+            params.push(Param { name, ty, pass_by });
+        }
+
+        let fallible = match &sig.output {
+            ReturnType::Default => false,
+            ReturnType::Type(_, ty) => match &**ty {
+                Type::Reference(_) => {
+                    return Err(Error::new_spanned(ty, "functions cannot return references"))
+                }
+                Type::ImplTrait(_) | Type::TraitObject(_) | Type::Infer(_) => {
+                    return Err(Error::new_spanned(ty, "unsupported return type"))
+                }
+                ty => is_path_to(ty, &["Result", "FuncReturn"]),
+            },
+        };
+
+        let name = sig.ident.clone();
+        let kind = options.into_kind(&name, span)?;
+
+        let first_is_mut = params
+            .first()
+            .map(|p| p.pass_by == PassBy::Mut)
+            .unwrap_or(false);
+
+        let required = match &kind {
+            FnKind::Normal(_) => None,
+            FnKind::Getter(_) => Some((1, "a property getter")),
+            FnKind::Setter(_) => Some((2, "a property setter")),
+            FnKind::IndexGetter => Some((2, "an index getter")),
+            FnKind::IndexSetter => Some((3, "an index setter")),
+        };
+
+        match required {
+            Some((num, desc)) if params.len() != num || !first_is_mut => {
+                return Err(Error::new_spanned(
+                    &sig.inputs,
+                    format!(
+                        "{} must take exactly {} parameter(s), the first one being `&mut`",
+                        desc, num
+                    ),
+                ))
+            }
+            None if params.len() > MAX_PARAMS => {
+                return Err(Error::new_spanned(
+                    &sig.inputs,
+                    format!(
+                        "functions with more than {} parameters cannot be exported",
+                        MAX_PARAMS
+                    ),
+                ))
+            }
+            _ => (),
+        }
+
+        Ok(Some(Self {
+            name,
+            kind,
+            params,
+            fallible,
+            doc: extract_doc(&item.attrs),
+        }))
+    }
+
+    /// Generate the code registering this function into a module named `m`.
+    pub fn generate(&self) -> TokenStream {
+        let fn_name = &self.name;
+
+        let closure_params = self.params.iter().enumerate().map(|(i, p)| {
+            let arg = format_ident!("arg{}", i);
+            let ty = &p.ty;
+            match p.pass_by {
+                PassBy::Mut => quote! { #arg: &mut #ty },
+                PassBy::Value | PassBy::Ref => quote! { #arg: #ty },
+            }
+        });
+        let call_args = self.params.iter().enumerate().map(|(i, p)| {
+            let arg = format_ident!("arg{}", i);
+            match p.pass_by {
+                PassBy::Ref => quote! { &#arg },
+                PassBy::Value | PassBy::Mut => quote! { #arg },
+            }
+        });
+
+        let call = quote! { #fn_name(#(#call_args),*) };
+        let body = if self.fallible {
+            call
+        } else {
+            quote! { Ok(#call) }
+        };
+        let closure = quote! { |#(#closure_params),*| #body };
+
+        let is_method = self
+            .params
+            .first()
+            .map(|p| p.pass_by == PassBy::Mut)
+            .unwrap_or(false);
+        let set_fn = if is_method {
+            format_ident!("set_fn_{}_mut", self.params.len())
+        } else {
+            format_ident!("set_fn_{}", self.params.len())
+        };
+
+        let registrations: Vec<_> = match &self.kind {
+            FnKind::Normal(names) => names
+                .iter()
+                .map(|name| quote! { m.#set_fn(#name, #closure) })
+                .collect(),
+            FnKind::Getter(prop) => vec![quote! { m.set_getter_fn(#prop, #closure) }],
+            FnKind::Setter(prop) => vec![quote! { m.set_setter_fn(#prop, #closure) }],
+            FnKind::IndexGetter => vec![quote! { m.set_indexer_get_fn(#closure) }],
+            FnKind::IndexSetter => vec![quote! { m.set_indexer_set_fn(#closure) }],
+        };
+
+        let param_names = self.params.iter().map(|p| &p.name);
+        let doc = if self.doc.is_empty() {
+            quote! {}
+        } else {
+            let doc = &self.doc;
+            quote! { m.set_fn_doc(hash, #doc); }
+        };
+
+        let mut output = TokenStream::new();
+
+        for register in registrations {
+            let param_names = param_names.clone();
+
+            output.extend(quote! {
+                {
+                    let hash = #register;
+                    m.set_fn_param_names(hash, &[#(#param_names),*]);
+                    #doc
+                }
+            });
+        }
+
+        output
+    }
+}
@@ -0,0 +1,212 @@
+//! # Rhai - Procedural Macros
+//!
+//! This crate contains procedural macros that turn a Rust module into a Rhai [`Module`],
+//! without the need to register each function by hand.
+//!
+//! It is not intended to be used directly.  Turn on the `plugins` feature of Rhai instead,
+//! which re-exports all macros in this crate.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use rhai::{Engine, EvalAltResult, Module, INT};
+//! use rhai::plugins::*;
+//!
+//! #[export_module]
+//! mod my_api {
+//!     use rhai::INT;
+//!
+//!     /// The answer to everything.
+//!     pub const ANSWER: INT = 42;
+//!
+//!     /// Add two numbers.
+//!     pub fn add(x: INT, y: INT) -> INT {
+//!         x + y
+//!     }
+//!
+//!     /// Increment a number in place.
+//!     #[rhai_fn(name = "inc", name = "++")]
+//!     pub fn increment(x: &mut INT) {
+//!         *x += 1;
+//!     }
+//! }
+//!
+//! let mut engine = Engine::new();
+//! engine.load_package(exported_module!(my_api).into());
+//!
+//! assert_eq!(engine.eval::<INT>("let x = add(ANSWER, 1); inc(x); x")?, 44);
+//! ```
+//!
+//! [`Module`]: https://docs.rs/rhai/latest/rhai/struct.Module.html
+
+extern crate proc_macro;
+
+mod custom_type;
+mod function;
+mod module;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    parse::Parse, parse::ParseStream, parse_macro_input, DeriveInput, Expr, ItemMod, Path, Token,
+};
+
+/// Export all public functions, constants and sub-modules of a Rust module to Rhai.
+///
+/// Two functions are added to the module:
+///
+/// * `rhai_module_generate() -> Module` creates a new [`Module`] containing all exported items,
+/// * `rhai_generate_into(&mut Module)` registers all exported items into an existing [`Module`].
+///
+/// Use [`exported_module!`] and [`combine_with_exported_module!`] to call them.
+///
+/// # Exported Items
+///
+/// * Public functions are registered under their own names.  A first parameter of type `&mut T`
+///   makes the function a method, updating the value in place.  Parameters of type `&str` take
+///   strings, and other `&T` parameters take values of type `T`.
+///
+/// * Functions returning `Result` or `FuncReturn` must return `Result<T, Box<EvalAltResult>>`
+///   and are registered as fallible functions.
+///
+/// * Public constants are added as module variables.
+///
+/// * Public inline sub-modules are exported recursively as sub-modules.
+///
+/// * Doc comments on functions are kept as function metadata, together with parameter names.
+///
+/// # Function Options
+///
+/// Use the `#[rhai_fn(...)]` attribute to change how a public function is exported:
+///
+/// | Option               | Description                                                    |
+/// | -------------------- | -------------------------------------------------------------- |
+/// | `name = "..."`       | register under a different name; can be repeated for overloads |
+/// | `get = "prop"`       | register as a getter for the property `prop`                    |
+/// | `set = "prop"`       | register as a setter for the property `prop`                    |
+/// | `index_get`          | register as an index getter                                     |
+/// | `index_set`          | register as an index setter                                     |
+/// | `skip`               | do not export this function                                     |
+///
+/// # Errors
+///
+/// Functions that cannot be exported raise compile-time errors.  These include generic, `async`,
+/// `unsafe` and `extern` functions, functions taking `self` or more than four parameters,
+/// `&mut` parameters other than the first, and functions returning references.
+///
+/// [`Module`]: https://docs.rs/rhai/latest/rhai/struct.Module.html
+#[proc_macro_attribute]
+pub fn export_module(args: TokenStream, input: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "`#[export_module]` takes no arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let item = parse_macro_input!(input as ItemMod);
+
+    match module::transform(item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Create a [`Module`] from a Rust module marked with [`#[export_module]`](macro@export_module).
+///
+/// ```rust,ignore
+/// let module = exported_module!(my_api);
+/// ```
+///
+/// [`Module`]: https://docs.rs/rhai/latest/rhai/struct.Module.html
+#[proc_macro]
+pub fn exported_module(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as Path);
+    let generate_fn = syn::Ident::new(module::GENERATE_FN, proc_macro2::Span::call_site());
+
+    (quote! { #path::#generate_fn() }).into()
+}
+
+/// Arguments to `combine_with_exported_module!`.
+struct CombineArgs {
+    lib: Expr,
+    path: Path,
+}
+
+impl Parse for CombineArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let lib = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let path = input.parse()?;
+        Ok(Self { lib, path })
+    }
+}
+
+/// Register all items of a Rust module marked with [`#[export_module]`](macro@export_module)
+/// into an existing `&mut Module`.
+///
+/// This is typically used inside `def_package!` to build a package.
+///
+/// ```rust,ignore
+/// def_package!(rhai:MyPackage:"My own package", lib, {
+///     combine_with_exported_module!(lib, my_api);
+/// });
+/// ```
+#[proc_macro]
+pub fn combine_with_exported_module(input: TokenStream) -> TokenStream {
+    let CombineArgs { lib, path } = parse_macro_input!(input as CombineArgs);
+    let generate_into_fn =
+        syn::Ident::new(module::GENERATE_INTO_FN, proc_macro2::Span::call_site());
+
+    (quote! { #path::#generate_into_fn(#lib) }).into()
+}
+
+/// Derive `RhaiType` for a struct, registering it as a custom type.
+///
+/// The generated `RhaiType::register_rhai_type` function registers:
+///
+/// * the type under its own name (or the name given via `#[rhai(name = "...")]` on the struct),
+/// * a getter and a setter for each field,
+/// * `to_string` and `print` via `Display` (or `Debug` if the type does not implement `Display`),
+/// * `debug` via `Debug`,
+/// * `==` and `!=` via `PartialEq`.
+///
+/// Functions based on `Display`, `Debug` and `PartialEq` are only registered when the type
+/// implements these traits.
+///
+/// # Field Options
+///
+/// | Option             | Description                                          |
+/// | ------------------ | ---------------------------------------------------- |
+/// | `get`              | register only a getter (the field is read-only)      |
+/// | `set`              | register only a setter (the field is write-only)     |
+/// | `rename = "..."`   | use a different property name                        |
+/// | `skip`             | do not register the field                            |
+///
+/// Fields of tuple structs must be renamed or skipped.  Enums, unions and generic types
+/// are not supported.
+///
+/// ```rust,ignore
+/// #[derive(Debug, Clone, RhaiType)]
+/// #[rhai(name = "Point")]
+/// struct MyPoint {
+///     x: INT,
+///     #[rhai(get, rename = "why")]
+///     y: INT,
+///     #[rhai(skip)]
+///     cache: Vec<u8>,
+/// }
+///
+/// engine.register_rhai_type::<MyPoint>();
+/// ```
+#[proc_macro_derive(RhaiType, attributes(rhai))]
+pub fn derive_rhai_type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match custom_type::derive(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
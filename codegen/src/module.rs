@@ -0,0 +1,102 @@
+//! Parsing and code generation for plugin modules.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Error, Ident, Item, ItemMod, Visibility};
+
+use crate::function::{ExportedFn, FN_ATTR};
+
+/// Name of the generated function returning the module.
+pub const GENERATE_FN: &str = "rhai_module_generate";
+
+/// Name of the generated function registering everything into an existing module.
+pub const GENERATE_INTO_FN: &str = "rhai_generate_into";
+
+/// Is this item public?
+fn is_public(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
+
+/// Transform an exported module, appending the generated registration functions to it.
+pub fn transform(mut item: ItemMod) -> syn::Result<TokenStream> {
+    let content = match &mut item.content {
+        Some((_, content)) => content,
+        None => {
+            return Err(Error::new_spanned(
+                &item,
+                "only inline modules (i.e. `mod name { ... }`) can be exported",
+            ))
+        }
+    };
+
+    let mut fns = Vec::new();
+    let mut consts: Vec<Ident> = Vec::new();
+    let mut sub_modules: Vec<Ident> = Vec::new();
+    let mut errors: Option<Error> = None;
+
+    for sub_item in content.iter_mut() {
+        let result = match sub_item {
+            Item::Fn(f) if is_public(&f.vis) => {
+                ExportedFn::parse(f).map(|f| fns.extend(f)).map(|_| None)
+            }
+            Item::Fn(f) => match f.attrs.iter().find(|a| a.path.is_ident(FN_ATTR)) {
+                Some(attr) => Err(Error::new_spanned(
+                    attr,
+                    "`#[rhai_fn]` can only be used on public functions",
+                )),
+                None => Ok(None),
+            },
+            Item::Const(c) if is_public(&c.vis) => {
+                consts.push(c.ident.clone());
+                Ok(None)
+            }
+            Item::Mod(m) if is_public(&m.vis) && m.content.is_some() => {
+                sub_modules.push(m.ident.clone());
+                transform(m.clone()).map(Some)
+            }
+            _ => Ok(None),
+        };
+
+        match result {
+            Ok(Some(tokens)) => *sub_item = Item::Verbatim(tokens),
+            Ok(None) => (),
+            Err(err) => match &mut errors {
+                Some(errors) => errors.combine(err),
+                None => errors = Some(err),
+            },
+        }
+    }
+
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let generate_fn = Ident::new(GENERATE_FN, proc_macro2::Span::call_site());
+    let generate_into_fn = Ident::new(GENERATE_INTO_FN, proc_macro2::Span::call_site());
+
+    let const_names = consts.iter().map(|c| c.to_string());
+    let sub_module_names = sub_modules.iter().map(|m| m.to_string());
+    let fns = fns.iter().map(ExportedFn::generate);
+
+    let generated: Item = syn::parse_quote! {
+        #[allow(dead_code)]
+        pub fn #generate_fn() -> rhai::Module {
+            let mut m = rhai::Module::new();
+            #generate_into_fn(&mut m);
+            m
+        }
+    };
+    content.push(generated);
+
+    let generated: Item = syn::parse_quote! {
+        #[allow(dead_code)]
+        pub fn #generate_into_fn(m: &mut rhai::Module) {
+            #( m.set_var(#const_names, #consts); )*
+            #( #fns )*
+            #( m.set_sub_module(#sub_module_names, #sub_modules::#generate_fn()); )*
+        }
+    };
+    content.push(generated);
+
+    Ok(quote! { #item })
+}
@@ -0,0 +1,479 @@
+//! Module containing a light-weight symbol analysis of Rhai scripts.
+//!
+//! Scripts are scanned with the Rhai tokenizer rather than the full parser, so that symbols
+//! can still be located in scripts that contain syntax errors while they are being edited.
+
+use rhai::{get_next_token, InputStream, Position, Token, TokenizeState};
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A location in a script, with 1-based line number and character position.
+///
+/// Character positions count `char`s, as the tokenizer does, not the UTF-16 code units of LSP.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    /// Create a new `Location`.
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+
+    /// Convert a tokenizer `Position` into a `Location`.
+    fn from_position(pos: Position) -> Self {
+        Self::new(pos.line().unwrap_or(1), pos.position().unwrap_or(1))
+    }
+
+    /// The location at the very end of any script.
+    fn end() -> Self {
+        Self::new(usize::MAX, usize::MAX)
+    }
+}
+
+/// Type of a symbol defined in a script.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SymbolKind {
+    /// Script-defined function.
+    Function,
+    /// Function parameter.
+    Parameter,
+    /// Variable defined via `let`.
+    Variable,
+    /// Constant defined via `const`.
+    Constant,
+    /// Module alias defined via `import ... as`.
+    Module,
+}
+
+/// A symbol defined in a script.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Symbol {
+    /// Name of the symbol.
+    pub name: String,
+    /// Type of the symbol.
+    pub kind: SymbolKind,
+    /// Location of the symbol's name at its definition.
+    pub location: Location,
+    /// Region in which the symbol is visible: from the definition to the end of the enclosing block.
+    /// Script-defined functions are visible everywhere.
+    pub scope: (Location, Location),
+    /// Names of the parameters, for functions.
+    pub params: Vec<String>,
+    /// Path of the imported module, for module aliases.
+    pub import_path: Option<String>,
+    /// Is this a private function?
+    pub is_private: bool,
+    /// Is this variable exported from the script?
+    pub is_exported: bool,
+}
+
+/// A reference to a (possibly module-qualified) identifier in a script.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Reference {
+    /// Module qualifiers leading to the identifier, e.g. `["x", "y"]` for `x::y::foo`.
+    pub namespace: Vec<String>,
+    /// Name of the identifier.
+    pub name: String,
+    /// Location of the identifier.
+    pub location: Location,
+}
+
+/// Character input stream over a string slice.
+struct CharStream<'a>(Peekable<Chars<'a>>);
+
+impl InputStream for CharStream<'_> {
+    fn get_next(&mut self) -> Option<char> {
+        self.0.next()
+    }
+    fn peek_next(&mut self) -> Option<char> {
+        self.0.peek().cloned()
+    }
+}
+
+/// Split a script into tokens, skipping comments and erroneous tokens.
+fn tokenize(script: &str) -> Vec<(Token, Location)> {
+    let mut stream = CharStream(script.chars().peekable());
+    let mut state = TokenizeState {
+        max_string_size: 0,
+        non_unary: false,
+        comment_level: 0,
+        end_with_none: true,
+        include_comments: false,
+    };
+    let mut pos = Position::new(1, 0);
+    let mut tokens = Vec::new();
+
+    while let Some((token, token_pos)) = get_next_token(&mut stream, &mut state, &mut pos) {
+        match token {
+            Token::EOF => break,
+            Token::LexError(_) | Token::Comment(_) => (),
+            token => tokens.push((token, Location::from_position(token_pos))),
+        }
+    }
+
+    tokens
+}
+
+/// Analysis of the symbols defined and referenced in a script.
+#[derive(Debug, Clone, Default)]
+pub struct Analysis {
+    /// All symbols defined in the script.
+    pub symbols: Vec<Symbol>,
+    /// All identifiers referenced in the script, including those at the definitions.
+    pub references: Vec<Reference>,
+    /// Regions covered by function definitions, which cannot see variables defined outside.
+    fn_bodies: Vec<(Location, Location)>,
+}
+
+impl Analysis {
+    /// Analyze a script.
+    pub fn new(script: &str) -> Self {
+        let tokens = tokenize(script);
+        let mut analysis = Self::default();
+
+        // Stack of open blocks, each with the indices of the symbols defined within
+        let mut blocks: Vec<Vec<usize>> = vec![Vec::new()];
+        // Function whose body is about to start (location of `fn`, indices of parameters)
+        let mut pending_fn: Option<(Location, Vec<usize>)> = None;
+        // Open function bodies (block depth, start location)
+        let mut open_fns: Vec<(usize, Location)> = Vec::new();
+
+        let ident = |i: usize| match tokens.get(i) {
+            Some((Token::Identifier(name), loc)) => Some((name.clone(), *loc)),
+            _ => None,
+        };
+
+        let mut i = 0;
+
+        while i < tokens.len() {
+            let (token, loc) = &tokens[i];
+
+            match token {
+                Token::Fn => {
+                    let is_private = i > 0 && tokens[i - 1].0 == Token::Private;
+
+                    if let Some((name, name_loc)) = ident(i + 1) {
+                        let mut params = Vec::new();
+                        let mut param_symbols = Vec::new();
+                        let mut j = i + 2;
+
+                        if let Some((Token::LeftParen, _)) = tokens.get(j) {
+                            j += 1;
+                            while let Some((token, _)) = tokens.get(j) {
+                                match token {
+                                    Token::RightParen => break,
+                                    Token::Identifier(param) => {
+                                        params.push(param.clone());
+                                        param_symbols.push(analysis.symbols.len());
+                                        analysis.references.push(Reference {
+                                            namespace: Vec::new(),
+                                            name: param.clone(),
+                                            location: tokens[j].1,
+                                        });
+                                        analysis.symbols.push(Symbol {
+                                            name: param.clone(),
+                                            kind: SymbolKind::Parameter,
+                                            location: tokens[j].1,
+                                            scope: (tokens[j].1, Location::end()),
+                                            params: Vec::new(),
+                                            import_path: None,
+                                            is_private: false,
+                                            is_exported: false,
+                                        });
+                                    }
+                                    _ => (),
+                                }
+                                j += 1;
+                            }
+                        }
+
+                        analysis.references.push(Reference {
+                            namespace: Vec::new(),
+                            name: name.clone(),
+                            location: name_loc,
+                        });
+                        analysis.symbols.push(Symbol {
+                            name,
+                            kind: SymbolKind::Function,
+                            location: name_loc,
+                            scope: (Location::new(1, 1), Location::end()),
+                            params,
+                            import_path: None,
+                            is_private,
+                            is_exported: false,
+                        });
+
+                        pending_fn = Some((*loc, param_symbols));
+                        i = j;
+                    }
+                }
+
+                Token::Let | Token::Const => {
+                    if let Some((name, name_loc)) = ident(i + 1) {
+                        let kind = if *token == Token::Let {
+                            SymbolKind::Variable
+                        } else {
+                            SymbolKind::Constant
+                        };
+
+                        blocks.last_mut().unwrap().push(analysis.symbols.len());
+                        analysis.references.push(Reference {
+                            namespace: Vec::new(),
+                            name: name.clone(),
+                            location: name_loc,
+                        });
+                        analysis.symbols.push(Symbol {
+                            name,
+                            kind,
+                            location: name_loc,
+                            scope: (name_loc, Location::end()),
+                            params: Vec::new(),
+                            import_path: None,
+                            is_private: false,
+                            is_exported: false,
+                        });
+                        i += 1;
+                    }
+                }
+
+                Token::Import => {
+                    // import "path" as alias
+                    if let (Some((Token::StringConstant(path), _)), Some((Token::As, _))) =
+                        (tokens.get(i + 1), tokens.get(i + 2))
+                    {
+                        if let Some((name, name_loc)) = ident(i + 3) {
+                            blocks.last_mut().unwrap().push(analysis.symbols.len());
+                            analysis.references.push(Reference {
+                                namespace: Vec::new(),
+                                name: name.clone(),
+                                location: name_loc,
+                            });
+                            analysis.symbols.push(Symbol {
+                                name,
+                                kind: SymbolKind::Module,
+                                location: name_loc,
+                                scope: (name_loc, Location::end()),
+                                params: Vec::new(),
+                                import_path: Some(path.clone()),
+                                is_private: false,
+                                is_exported: false,
+                            });
+                            i += 3;
+                        }
+                    }
+                }
+
+                Token::Export => {
+                    // export x, y as z;
+                    let mut j = i + 1;
+
+                    while let Some((name, _)) = ident(j) {
+                        if let Some(symbol) = analysis
+                            .symbols
+                            .iter_mut()
+                            .rev()
+                            .find(|s| s.name == name && s.kind != SymbolKind::Function)
+                        {
+                            symbol.is_exported = true;
+                        }
+
+                        j += 1;
+
+                        if let Some((Token::As, _)) = tokens.get(j) {
+                            j += 2;
+                        }
+                        if let Some((Token::Comma, _)) = tokens.get(j) {
+                            j += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                }
+
+                Token::LeftBrace => {
+                    blocks.push(Vec::new());
+
+                    if let Some((start, params)) = pending_fn.take() {
+                        open_fns.push((blocks.len(), start));
+                        // Parameters are visible throughout the function body
+                        blocks.last_mut().unwrap().extend(params);
+                    }
+                }
+
+                Token::MapStart => blocks.push(Vec::new()),
+
+                Token::RightBrace => {
+                    if blocks.len() > 1 {
+                        for index in blocks.pop().unwrap() {
+                            analysis.symbols[index].scope.1 = *loc;
+                        }
+                    }
+
+                    if let Some(&(depth, start)) = open_fns.last() {
+                        if depth > blocks.len() {
+                            open_fns.pop();
+                            analysis.fn_bodies.push((start, *loc));
+                        }
+                    }
+                }
+
+                Token::Identifier(_) => {
+                    // Collect the full module-qualified path: x::y::name
+                    let mut namespace = Vec::new();
+                    let mut j = i;
+
+                    while let (Some((name, location)), Some((Token::DoubleColon, _))) =
+                        (ident(j), tokens.get(j + 1))
+                    {
+                        // Qualifiers are references to module aliases and sub-modules
+                        analysis.references.push(Reference {
+                            namespace: namespace.clone(),
+                            name: name.clone(),
+                            location,
+                        });
+                        namespace.push(name);
+                        j += 2;
+                    }
+
+                    if let Some((name, location)) = ident(j) {
+                        analysis.references.push(Reference {
+                            namespace,
+                            name,
+                            location,
+                        });
+                    }
+
+                    i = j;
+                }
+
+                _ => (),
+            }
+
+            i += 1;
+        }
+
+        // Unclosed function bodies extend to the end of the script
+        for (_, start) in open_fns {
+            analysis.fn_bodies.push((start, Location::end()));
+        }
+
+        analysis
+    }
+
+    /// Get the function body containing a location, if any.
+    fn fn_body_at(&self, location: Location) -> Option<(Location, Location)> {
+        self.fn_bodies
+            .iter()
+            .filter(|(start, end)| *start <= location && location <= *end)
+            .max_by_key(|(start, _)| *start)
+            .cloned()
+    }
+
+    /// Get the reference under a location, if any.
+    pub fn reference_at(&self, location: Location) -> Option<&Reference> {
+        self.references.iter().find(|r| {
+            r.location.line == location.line
+                && r.location.column <= location.column
+                && location.column <= r.location.column + r.name.chars().count()
+        })
+    }
+
+    /// Get all symbols visible at a location.
+    ///
+    /// Later definitions shadow earlier ones of the same name, so symbols are returned
+    /// in reverse order of definition.
+    pub fn visible_symbols(&self, location: Location) -> impl Iterator<Item = &Symbol> {
+        let fn_body = self.fn_body_at(location);
+
+        self.symbols.iter().rev().filter(move |s| match s.kind {
+            SymbolKind::Function => true,
+            _ if s.scope.0 > location || location > s.scope.1 => false,
+            // Functions cannot access variables defined outside of their bodies
+            _ => match fn_body {
+                Some((start, end)) => start <= s.location && s.location <= end,
+                None => true,
+            },
+        })
+    }
+
+    /// Find the definition of the identifier under a location, if any.
+    pub fn definition_at(&self, location: Location) -> Option<&Symbol> {
+        let reference = self.reference_at(location)?;
+
+        if !reference.namespace.is_empty() {
+            return None;
+        }
+
+        // Is the location at the definition itself?
+        if let Some(symbol) = self
+            .symbols
+            .iter()
+            .find(|s| s.location == reference.location)
+        {
+            return Some(symbol);
+        }
+
+        self.visible_symbols(reference.location)
+            .find(|s| s.name == reference.name && s.location <= reference.location)
+            .or_else(|| {
+                // Functions can be called before they are defined
+                self.symbols
+                    .iter()
+                    .find(|s| s.kind == SymbolKind::Function && s.name == reference.name)
+            })
+    }
+
+    /// Get the module alias visible at a location.
+    pub fn module_alias(&self, name: &str, location: Location) -> Option<&Symbol> {
+        self.visible_symbols(location)
+            .find(|s| s.kind == SymbolKind::Module && s.name == name)
+    }
+
+    /// Get the public functions and exported variables of the script, as a module.
+    pub fn exports(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.iter().filter(|s| match s.kind {
+            SymbolKind::Function => !s.is_private,
+            SymbolKind::Variable | SymbolKind::Constant => s.is_exported,
+            _ => false,
+        })
+    }
+}
+
+/// Extract the module path before a `::` that immediately precedes a location on its line, if any.
+///
+/// For example, for `x::y::fo` with the location at the end, this returns `["x", "y"]`.
+pub fn namespace_before(line: &str, column: usize) -> Option<Vec<String>> {
+    let chars: Vec<char> = line.chars().take(column.saturating_sub(1)).collect();
+    let is_ident_char = |c: char| c.is_alphanumeric() || c == '_';
+
+    // Skip the partial identifier being typed
+    let mut end = chars.len();
+    while end > 0 && is_ident_char(chars[end - 1]) {
+        end -= 1;
+    }
+
+    let mut namespace = Vec::new();
+
+    while end >= 2 && chars[end - 1] == ':' && chars[end - 2] == ':' {
+        let stop = end - 2;
+        let mut start = stop;
+        while start > 0 && is_ident_char(chars[start - 1]) {
+            start -= 1;
+        }
+        if start == stop {
+            break;
+        }
+        namespace.insert(0, chars[start..stop].iter().collect());
+        end = start;
+    }
+
+    if namespace.is_empty() {
+        None
+    } else {
+        Some(namespace)
+    }
+}
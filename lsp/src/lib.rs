@@ -0,0 +1,15 @@
+//! # rhai-lsp - Language Server Protocol implementation for Rhai scripts
+//!
+//! Provides diagnostics, go-to-definition, hover and completion for Rhai scripts
+//! to any editor supporting the [Language Server Protocol](https://microsoft.github.io/language-server-protocol/).
+//!
+//! Signatures of functions registered by the host application are read from a metadata file
+//! in the format generated by `Engine::gen_fn_metadata_to_json`.
+
+pub mod analysis;
+pub mod metadata;
+pub mod rpc;
+pub mod server;
+
+pub use metadata::Metadata;
+pub use server::Server;
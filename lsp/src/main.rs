@@ -0,0 +1,80 @@
+use rhai_lsp::rpc::{self, Message};
+use rhai_lsp::{server::load_metadata, Metadata, Server};
+
+use std::env;
+use std::io::{self, BufReader};
+use std::path::Path;
+use std::process::exit;
+
+fn print_usage() {
+    eprintln!("Usage: rhai-lsp [--metadata <file>]");
+    eprintln!();
+    eprintln!("Starts a Rhai language server speaking the Language Server Protocol over stdio.");
+    eprintln!();
+    eprintln!(
+        "    --metadata <file>   JSON file with the signatures of host-registered functions,"
+    );
+    eprintln!("                        as generated by `Engine::gen_fn_metadata_to_json`");
+}
+
+fn main() {
+    let mut metadata = Metadata::new();
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--metadata" => match args.next() {
+                Some(path) => match load_metadata(Path::new(&path)) {
+                    Ok(m) => metadata = m,
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        exit(1);
+                    }
+                },
+                None => {
+                    print_usage();
+                    exit(1);
+                }
+            },
+            "-h" | "--help" => {
+                print_usage();
+                return;
+            }
+            _ => {
+                eprintln!("Unknown argument: {}", arg);
+                print_usage();
+                exit(1);
+            }
+        }
+    }
+
+    let mut server = Server::new(metadata);
+    let mut input = BufReader::new(io::stdin());
+    let mut output = io::stdout();
+
+    loop {
+        let replies = match rpc::read_message(&mut input) {
+            Ok(Some(Message::Valid(message))) => server.handle(&message),
+            Ok(Some(Message::Invalid(reason))) => {
+                eprintln!("Invalid message: {}", reason);
+                vec![rpc::parse_error(&reason)]
+            }
+            Ok(None) => break,
+            Err(err) => {
+                eprintln!("Error reading message: {}", err);
+                exit(1);
+            }
+        };
+
+        for reply in replies {
+            if let Err(err) = rpc::write_message(&mut output, &reply) {
+                eprintln!("Error writing message: {}", err);
+                exit(1);
+            }
+        }
+
+        if let Some(code) = server.exit_code() {
+            exit(code);
+        }
+    }
+}
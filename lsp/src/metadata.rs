@@ -0,0 +1,187 @@
+//! Module for loading the metadata of host-registered functions.
+//!
+//! The metadata file is a JSON document in the format generated by
+//! `Engine::gen_fn_metadata_to_json`, i.e. an array of function objects:
+//!
+//! ```json
+//! [
+//!     {
+//!         "namespace": [],
+//!         "name": "add",
+//!         "params": [ { "name": "x", "type": "i64" }, { "name": "y", "type": "i64" } ],
+//!         "return_type": "i64",
+//!         "is_script": false,
+//!         "doc": "Add two numbers together."
+//!     }
+//! ]
+//! ```
+//!
+//! Alternatively, it can be an object mapping the paths of modules available for `import`
+//! to arrays of function objects, with the key `""` holding the global functions:
+//!
+//! ```json
+//! { "": [ ... ], "my_module": [ ... ] }
+//! ```
+
+use serde_json::Value;
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Signature of a host-registered function.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FnInfo {
+    /// Path of sub-modules leading to the function.
+    pub namespace: Vec<String>,
+    /// Name of the function.
+    pub name: String,
+    /// Parameters of the function: name (if known) and type.
+    pub params: Vec<(Option<String>, String)>,
+    /// Type of the return value.
+    pub return_type: String,
+    /// Documentation of the function, if any.
+    pub doc: Option<String>,
+}
+
+impl fmt::Display for FnInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fn {}(", self.name)?;
+
+        for (i, (name, typ)) in self.params.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}: {}", name.as_deref().unwrap_or("_"), typ)?;
+        }
+
+        write!(f, ") -> {}", self.return_type)
+    }
+}
+
+impl FnInfo {
+    /// Parse a function object.
+    fn from_json(value: &Value) -> Option<Self> {
+        let str_list = |v: Option<&Value>| -> Vec<String> {
+            v.and_then(Value::as_array)
+                .map(|a| {
+                    a.iter()
+                        .filter_map(Value::as_str)
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let params = value
+            .get("params")
+            .and_then(Value::as_array)
+            .map(|params| {
+                params
+                    .iter()
+                    .map(|p| {
+                        (
+                            p.get("name").and_then(Value::as_str).map(String::from),
+                            p.get("type")
+                                .and_then(Value::as_str)
+                                .unwrap_or("Dynamic")
+                                .to_string(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            namespace: str_list(value.get("namespace")),
+            name: value.get("name")?.as_str()?.to_string(),
+            params,
+            return_type: value
+                .get("return_type")
+                .and_then(Value::as_str)
+                .unwrap_or("Dynamic")
+                .to_string(),
+            doc: value.get("doc").and_then(Value::as_str).map(String::from),
+        })
+    }
+}
+
+/// Collection of host-registered functions.
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    /// Global functions, keyed by name.
+    functions: HashMap<String, Vec<FnInfo>>,
+    /// Functions in modules available for `import`, keyed by module path.
+    modules: HashMap<String, Vec<FnInfo>>,
+}
+
+impl Metadata {
+    /// Create an empty `Metadata`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Parse metadata from a JSON document.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(json).map_err(|err| err.to_string())?;
+        let mut metadata = Self::new();
+
+        let parse_list = |list: &Value| -> Result<Vec<FnInfo>, String> {
+            list.as_array()
+                .ok_or_else(|| "expecting an array of functions".to_string())?
+                .iter()
+                .map(|f| FnInfo::from_json(f).ok_or_else(|| format!("invalid function: {}", f)))
+                .collect()
+        };
+
+        match &value {
+            Value::Array(_) => metadata.add_functions(parse_list(&value)?),
+            Value::Object(map) => {
+                for (path, list) in map {
+                    let list = parse_list(list)?;
+
+                    if path.is_empty() {
+                        metadata.add_functions(list);
+                    } else {
+                        metadata
+                            .modules
+                            .entry(path.clone())
+                            .or_default()
+                            .extend(list);
+                    }
+                }
+            }
+            _ => return Err("expecting an array or an object".into()),
+        }
+
+        Ok(metadata)
+    }
+
+    /// Add global functions.
+    ///
+    /// Functions under a namespace are taken as belonging to a module with the same path.
+    fn add_functions(&mut self, list: Vec<FnInfo>) {
+        for mut f in list {
+            if f.namespace.is_empty() {
+                self.functions.entry(f.name.clone()).or_default().push(f);
+            } else {
+                let path = f.namespace.remove(0);
+                self.modules.entry(path).or_default().push(f);
+            }
+        }
+    }
+
+    /// Get all overloads of a global function.
+    pub fn get_fn(&self, name: &str) -> &[FnInfo] {
+        self.functions.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Iterate all global functions.
+    pub fn iter_fn(&self) -> impl Iterator<Item = &FnInfo> {
+        self.functions.values().flatten()
+    }
+
+    /// Iterate all functions in a module (and its sub-modules) available for `import`.
+    pub fn iter_module_fn<'a>(&'a self, path: &str) -> impl Iterator<Item = &'a FnInfo> {
+        self.modules.get(path).into_iter().flatten()
+    }
+}
@@ -0,0 +1,104 @@
+//! Module implementing the base protocol of the Language Server Protocol:
+//! JSON-RPC messages framed by HTTP-style headers.
+
+use serde_json::{json, Value};
+
+use std::io::{self, BufRead, Read, Write};
+
+/// Maximum length of the content of a message, in bytes.
+///
+/// Larger messages are skipped without reading them into memory.
+pub const MAX_CONTENT_LENGTH: usize = 64 * 1024 * 1024;
+
+/// JSON-RPC error code for a message that is not valid JSON.
+const PARSE_ERROR: i64 = -32700;
+
+/// A message read from an input stream.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    /// A message with valid JSON content.
+    Valid(Value),
+    /// A message that cannot be handled, with the reason.
+    Invalid(String),
+}
+
+/// Read a message from an input stream.
+///
+/// Returns `Ok(None)` at the end of the stream.
+/// A message that is not valid JSON, or that is too large, is returned as `Message::Invalid`
+/// so that the next message can still be read.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Message>> {
+    let mut content_length = None;
+
+    loop {
+        let mut line = String::new();
+
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let content_length = match content_length {
+        Some(len) => len,
+        None => {
+            return Ok(Some(Message::Invalid(
+                "missing Content-Length header".into(),
+            )))
+        }
+    };
+
+    if content_length > MAX_CONTENT_LENGTH {
+        // Skip the content to get to the next message
+        let mut content = Read::take(input, content_length as u64);
+        let skipped = io::copy(&mut content, &mut io::sink())?;
+
+        if skipped < content_length as u64 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        return Ok(Some(Message::Invalid(format!(
+            "message too large: {} bytes",
+            content_length
+        ))));
+    }
+
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+
+    Ok(Some(match serde_json::from_slice(&content) {
+        Ok(message) => Message::Valid(message),
+        Err(err) => Message::Invalid(err.to_string()),
+    }))
+}
+
+/// Create the JSON-RPC response to a message that cannot be handled.
+pub fn parse_error(reason: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": { "code": PARSE_ERROR, "message": format!("parse error: {}", reason) },
+    })
+}
+
+/// Write a message to an output stream.
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    output.flush()
+}
@@ -0,0 +1,526 @@
+//! Module implementing the language server: handling of LSP requests and notifications.
+
+use crate::analysis::{namespace_before, Analysis, Location, Symbol, SymbolKind};
+use crate::metadata::Metadata;
+
+use rhai::{Engine, ParseError, Position};
+use serde_json::{json, Value};
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File extension of Rhai scripts, used to resolve `import` paths.
+const SCRIPT_EXTENSION: &str = "rhai";
+
+/// Keywords offered as completions.
+const KEYWORDS: &[&str] = &[
+    "let", "const", "if", "else", "while", "loop", "for", "in", "continue", "break", "return",
+    "throw", "fn", "private", "import", "export", "as", "true", "false", "this",
+];
+
+/// LSP completion item kinds.
+mod completion_kind {
+    pub const FUNCTION: u32 = 3;
+    pub const VARIABLE: u32 = 6;
+    pub const MODULE: u32 = 9;
+    pub const KEYWORD: u32 = 14;
+    pub const CONSTANT: u32 = 21;
+}
+
+/// LSP error code for an unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// An open script document.
+struct Document {
+    /// Text of the script.
+    text: String,
+    /// Symbol analysis of the script.
+    analysis: Analysis,
+}
+
+impl Document {
+    fn new(text: String) -> Self {
+        let analysis = Analysis::new(&text);
+        Self { text, analysis }
+    }
+}
+
+/// A Rhai language server.
+pub struct Server {
+    /// Scripting engine used to check scripts for errors.
+    engine: Engine,
+    /// Metadata of host-registered functions.
+    metadata: Metadata,
+    /// Open documents, keyed by URI.
+    documents: HashMap<String, Document>,
+    /// Has a `shutdown` request been received?
+    shutdown: bool,
+    /// Exit code, set when an `exit` notification has been received.
+    exit_code: Option<i32>,
+}
+
+impl Server {
+    /// Create a new `Server` with metadata of host-registered functions.
+    pub fn new(metadata: Metadata) -> Self {
+        Self {
+            engine: Engine::new(),
+            metadata,
+            documents: HashMap::new(),
+            shutdown: false,
+            exit_code: None,
+        }
+    }
+
+    /// Get the exit code, if the server should exit.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    /// Handle an incoming message, returning the outgoing messages.
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let id = match message.get("id") {
+            Some(id) => id.clone(),
+            // Notification - no response
+            None => return self.handle_notification(method, &params),
+        };
+
+        let result = match method {
+            "initialize" => self.initialize(&params),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/definition" => Ok(self.definition(&params)),
+            "textDocument/hover" => Ok(self.hover(&params)),
+            "textDocument/completion" => Ok(self.completion(&params)),
+            _ => Err(json!({
+                "code": METHOD_NOT_FOUND,
+                "message": format!("unknown method: {}", method),
+            })),
+        };
+
+        vec![match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+        }]
+    }
+
+    /// Handle a notification.
+    fn handle_notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params
+            .pointer("/textDocument/uri")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string();
+
+        match method {
+            "exit" => {
+                self.exit_code = Some(if self.shutdown { 0 } else { 1 });
+                Vec::new()
+            }
+            "textDocument/didOpen" => {
+                let text = params
+                    .pointer("/textDocument/text")
+                    .and_then(Value::as_str)
+                    .unwrap_or("");
+                self.documents
+                    .insert(uri.clone(), Document::new(text.into()));
+                vec![self.diagnostics(&uri)]
+            }
+            "textDocument/didChange" => {
+                // Full document synchronization: the last change holds the whole text
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Value::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Value::as_str);
+
+                match text {
+                    Some(text) => {
+                        self.documents
+                            .insert(uri.clone(), Document::new(text.into()));
+                        vec![self.diagnostics(&uri)]
+                    }
+                    None => Vec::new(),
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                vec![publish_diagnostics(&uri, Vec::new())]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Handle the `initialize` request.
+    ///
+    /// The metadata file can also be specified via the `metadata` initialization option.
+    fn initialize(&mut self, params: &Value) -> Result<Value, Value> {
+        if let Some(path) = params
+            .pointer("/initializationOptions/metadata")
+            .and_then(Value::as_str)
+        {
+            self.metadata = load_metadata(Path::new(path))
+                .map_err(|err| json!({ "code": -32602, "message": err }))?;
+        }
+
+        Ok(json!({
+            "capabilities": {
+                "textDocumentSync": 1,
+                "definitionProvider": true,
+                "hoverProvider": true,
+                "completionProvider": { "triggerCharacters": [":"] },
+            },
+            "serverInfo": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        }))
+    }
+
+    /// Check a document for syntax errors, returning a `publishDiagnostics` notification.
+    fn diagnostics(&self, uri: &str) -> Value {
+        let diagnostics = match self.documents.get(uri) {
+            Some(doc) => match self.engine.compile(&doc.text) {
+                Ok(_) => Vec::new(),
+                Err(ParseError(err, pos)) => vec![json!({
+                    "range": position_range(&doc.text, pos),
+                    "severity": 1,
+                    "source": "rhai",
+                    "message": err.to_string(),
+                })],
+            },
+            None => Vec::new(),
+        };
+
+        publish_diagnostics(uri, diagnostics)
+    }
+
+    /// Get the document and location referred to by a text document position request.
+    fn document_at<'a>(&'a self, params: &'a Value) -> Option<(&'a str, &'a Document, Location)> {
+        let uri = params.pointer("/textDocument/uri")?.as_str()?;
+        let doc = self.documents.get(uri)?;
+        let line = params.pointer("/position/line")?.as_u64()? as usize;
+        let character = params.pointer("/position/character")?.as_u64()? as usize;
+        let text = doc.text.lines().nth(line).unwrap_or("");
+        Some((uri, doc, Location::new(line + 1, utf16_to_column(text, character))))
+    }
+
+    /// Resolve an `import` path against the importing document into a script file path.
+    fn resolve_import(&self, uri: &str, path: &str) -> Option<PathBuf> {
+        let dir = uri_to_path(uri)?.parent()?.to_path_buf();
+        let mut file = dir.join(path);
+        file.set_extension(SCRIPT_EXTENSION);
+
+        if file.is_file() {
+            Some(file)
+        } else {
+            None
+        }
+    }
+
+    /// Analyze the script imported under a module alias, if it is a script file.
+    ///
+    /// Returns the file path, the text of the script and its analysis.
+    fn analyze_import(&self, uri: &str, alias: &Symbol) -> Option<(PathBuf, String, Analysis)> {
+        let file = self.resolve_import(uri, alias.import_path.as_ref()?)?;
+        let text = fs::read_to_string(&file).ok()?;
+        let analysis = Analysis::new(&text);
+        Some((file, text, analysis))
+    }
+
+    /// Handle the `textDocument/definition` request.
+    fn definition(&self, params: &Value) -> Value {
+        let (uri, doc, location) = match self.document_at(params) {
+            Some(x) => x,
+            None => return Value::Null,
+        };
+
+        if let Some(symbol) = doc.analysis.definition_at(location) {
+            return symbol_location(uri, &doc.text, symbol);
+        }
+
+        // Qualified reference to a function in an imported script
+        let reference = match doc.analysis.reference_at(location) {
+            Some(r) if r.namespace.len() == 1 => r,
+            _ => return Value::Null,
+        };
+
+        doc.analysis
+            .module_alias(&reference.namespace[0], reference.location)
+            .and_then(|alias| self.analyze_import(uri, alias))
+            .and_then(|(file, text, analysis)| {
+                analysis
+                    .exports()
+                    .find(|s| s.name == reference.name)
+                    .map(|s| symbol_location(&path_to_uri(&file), &text, s))
+            })
+            .unwrap_or(Value::Null)
+    }
+
+    /// Handle the `textDocument/hover` request.
+    fn hover(&self, params: &Value) -> Value {
+        let (uri, doc, location) = match self.document_at(params) {
+            Some(x) => x,
+            None => return Value::Null,
+        };
+
+        let reference = match doc.analysis.reference_at(location) {
+            Some(r) => r,
+            None => return Value::Null,
+        };
+
+        let mut lines = Vec::new();
+
+        if reference.namespace.is_empty() {
+            if let Some(symbol) = doc.analysis.definition_at(location) {
+                lines.push(format!("```rhai\n{}\n```", describe_symbol(symbol)));
+            } else {
+                for f in self.metadata.get_fn(&reference.name) {
+                    lines.push(format!("```rhai\n{}\n```", f));
+                    lines.extend(f.doc.clone());
+                }
+            }
+        } else if let Some(alias) = doc
+            .analysis
+            .module_alias(&reference.namespace[0], reference.location)
+        {
+            let path = alias.import_path.as_deref().unwrap_or("");
+
+            for f in self.metadata.iter_module_fn(path) {
+                if f.name == reference.name && f.namespace[..] == reference.namespace[1..] {
+                    lines.push(format!("```rhai\n{}\n```", f));
+                    lines.extend(f.doc.clone());
+                }
+            }
+
+            if let Some((_, _, analysis)) = self.analyze_import(uri, alias) {
+                for symbol in analysis.exports().filter(|s| s.name == reference.name) {
+                    lines.push(format!("```rhai\n{}\n```", describe_symbol(symbol)));
+                }
+            }
+        }
+
+        if lines.is_empty() {
+            return Value::Null;
+        }
+
+        json!({
+            "contents": { "kind": "markdown", "value": lines.join("\n\n") },
+            "range": name_range(&doc.text, reference.location, &reference.name),
+        })
+    }
+
+    /// Handle the `textDocument/completion` request.
+    fn completion(&self, params: &Value) -> Value {
+        let (uri, doc, location) = match self.document_at(params) {
+            Some(x) => x,
+            None => return json!([]),
+        };
+
+        let line = doc.text.lines().nth(location.line - 1).unwrap_or("");
+        let mut items = Vec::new();
+        let mut seen = HashSet::new();
+
+        let mut add = |label: &str, kind: u32, detail: String| {
+            if seen.insert(label.to_string()) {
+                items.push(json!({ "label": label, "kind": kind, "detail": detail }));
+            }
+        };
+
+        if let Some(namespace) = namespace_before(line, location.column) {
+            // Members of an imported module
+            if let Some(alias) = doc.analysis.module_alias(&namespace[0], location) {
+                let path = alias.import_path.as_deref().unwrap_or("");
+
+                for f in self.metadata.iter_module_fn(path) {
+                    if f.namespace[..] == namespace[1..] {
+                        add(&f.name, completion_kind::FUNCTION, f.to_string());
+                    }
+                }
+
+                if namespace.len() == 1 {
+                    if let Some((_, _, analysis)) = self.analyze_import(uri, alias) {
+                        for symbol in analysis.exports() {
+                            add(
+                                &symbol.name,
+                                symbol_completion_kind(symbol.kind),
+                                describe_symbol(symbol),
+                            );
+                        }
+                    }
+                }
+            }
+        } else {
+            for symbol in doc.analysis.visible_symbols(location) {
+                add(
+                    &symbol.name,
+                    symbol_completion_kind(symbol.kind),
+                    describe_symbol(symbol),
+                );
+            }
+            for f in self.metadata.iter_fn() {
+                add(&f.name, completion_kind::FUNCTION, f.to_string());
+            }
+            for &keyword in KEYWORDS {
+                add(keyword, completion_kind::KEYWORD, "keyword".into());
+            }
+        }
+
+        Value::Array(items)
+    }
+}
+
+/// Load metadata of host-registered functions from a file.
+pub fn load_metadata(path: &Path) -> Result<Metadata, String> {
+    let json = fs::read_to_string(path)
+        .map_err(|err| format!("cannot read metadata file '{}': {}", path.display(), err))?;
+
+    Metadata::from_json(&json)
+        .map_err(|err| format!("invalid metadata file '{}': {}", path.display(), err))
+}
+
+/// Create a `publishDiagnostics` notification.
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+/// Convert a zero-based LSP character offset on a line, counted in UTF-16 code units,
+/// into a 1-based character position.
+fn utf16_to_column(line: &str, character: usize) -> usize {
+    let mut units = 0;
+
+    for (index, ch) in line.chars().enumerate() {
+        if units >= character {
+            return index + 1;
+        }
+        units += ch.len_utf16();
+    }
+
+    // Past the end of the line
+    line.chars().count() + 1 + character.saturating_sub(units)
+}
+
+/// Convert a 1-based character position on a line into a zero-based LSP character offset,
+/// counted in UTF-16 code units.
+fn column_to_utf16(line: &str, column: usize) -> usize {
+    let chars = column.saturating_sub(1);
+    let units: usize = line.chars().take(chars).map(char::len_utf16).sum();
+
+    // Past the end of the line
+    units + chars.saturating_sub(line.chars().count())
+}
+
+/// Convert a 1-based line and character position in a script into a zero-based LSP position.
+fn lsp_position(text: &str, line: usize, column: usize) -> Value {
+    let line = line.saturating_sub(1);
+    let character = column_to_utf16(text.lines().nth(line).unwrap_or(""), column);
+
+    json!({ "line": line, "character": character })
+}
+
+/// Get the LSP range covering a single character at a tokenizer `Position` in a script.
+fn position_range(text: &str, pos: Position) -> Value {
+    let line = pos.line().unwrap_or(1);
+    let column = pos.position().unwrap_or(1);
+
+    json!({
+        "start": lsp_position(text, line, column),
+        "end": lsp_position(text, line, column + 1),
+    })
+}
+
+/// Get the LSP range covering a name at a location in a script.
+fn name_range(text: &str, location: Location, name: &str) -> Value {
+    json!({
+        "start": lsp_position(text, location.line, location.column),
+        "end": lsp_position(text, location.line, location.column + name.chars().count()),
+    })
+}
+
+/// Get the LSP location of a symbol's definition in a script.
+fn symbol_location(uri: &str, text: &str, symbol: &Symbol) -> Value {
+    json!({ "uri": uri, "range": name_range(text, symbol.location, &symbol.name) })
+}
+
+/// Map the type of a symbol to an LSP completion item kind.
+fn symbol_completion_kind(kind: SymbolKind) -> u32 {
+    match kind {
+        SymbolKind::Function => completion_kind::FUNCTION,
+        SymbolKind::Parameter | SymbolKind::Variable => completion_kind::VARIABLE,
+        SymbolKind::Constant => completion_kind::CONSTANT,
+        SymbolKind::Module => completion_kind::MODULE,
+    }
+}
+
+/// Describe a symbol in Rhai syntax.
+fn describe_symbol(symbol: &Symbol) -> String {
+    match symbol.kind {
+        SymbolKind::Function => format!(
+            "{}fn {}({})",
+            if symbol.is_private { "private " } else { "" },
+            symbol.name,
+            symbol.params.join(", ")
+        ),
+        SymbolKind::Parameter => format!("{} (parameter)", symbol.name),
+        SymbolKind::Variable => format!("let {}", symbol.name),
+        SymbolKind::Constant => format!("const {}", symbol.name),
+        SymbolKind::Module => format!(
+            "import \"{}\" as {}",
+            symbol.import_path.as_deref().unwrap_or(""),
+            symbol.name
+        ),
+    }
+}
+
+/// Convert a `file://` URI into a file path.
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit()
+        {
+            decoded.push(u8::from_str_radix(&path[i + 1..i + 3], 16).unwrap());
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+/// Convert a file path into a `file://` URI.
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+
+    for ch in path.to_string_lossy().chars() {
+        match ch {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '/' | '-' | '_' | '.' | '~' => uri.push(ch),
+            ch => {
+                let mut buf = [0; 4];
+                for byte in ch.encode_utf8(&mut buf).bytes() {
+                    uri.push_str(&format!("%{:02X}", byte));
+                }
+            }
+        }
+    }
+
+    uri
+}
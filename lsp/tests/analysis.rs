@@ -0,0 +1,89 @@
+use rhai_lsp::analysis::{namespace_before, Analysis, Location, SymbolKind};
+
+const SCRIPT: &str = r#"import "utils" as u;
+let x = 40;
+fn add(a, b) {
+    let c = a + b;
+    c
+}
+if x > 0 {
+    let y = add(x, 2);
+}
+const z = u::calc(x);
+export x;
+"#;
+
+#[test]
+fn test_analysis_definition() {
+    let analysis = Analysis::new(SCRIPT);
+
+    // `x` in `add(x, 2)`
+    let symbol = analysis.definition_at(Location::new(8, 17)).unwrap();
+    assert_eq!(symbol.kind, SymbolKind::Variable);
+    assert_eq!(symbol.location, Location::new(2, 5));
+    assert!(symbol.is_exported);
+
+    // `add` in `add(x, 2)`
+    let symbol = analysis.definition_at(Location::new(8, 13)).unwrap();
+    assert_eq!(symbol.kind, SymbolKind::Function);
+    assert_eq!(symbol.params, ["a", "b"]);
+
+    // `a` in `a + b`
+    let symbol = analysis.definition_at(Location::new(4, 13)).unwrap();
+    assert_eq!(symbol.kind, SymbolKind::Parameter);
+    assert_eq!(symbol.location, Location::new(3, 8));
+
+    // Qualified names have no local definition
+    assert!(analysis.definition_at(Location::new(10, 14)).is_none());
+    assert_eq!(
+        analysis
+            .reference_at(Location::new(10, 14))
+            .unwrap()
+            .namespace,
+        ["u"]
+    );
+}
+
+#[test]
+fn test_analysis_scopes() {
+    let analysis = Analysis::new(SCRIPT);
+
+    let visible = |line, column| -> Vec<String> {
+        analysis
+            .visible_symbols(Location::new(line, column))
+            .map(|s| s.name.clone())
+            .collect()
+    };
+
+    // Inside a function body, outer variables are not visible
+    let names = visible(5, 5);
+    assert!(names.contains(&"c".to_string()));
+    assert!(names.contains(&"a".to_string()));
+    assert!(!names.contains(&"x".to_string()));
+
+    // Variables defined in a block are not visible after it
+    let names = visible(10, 1);
+    assert!(names.contains(&"x".to_string()));
+    assert!(names.contains(&"u".to_string()));
+    assert!(!names.contains(&"y".to_string()));
+    assert!(!names.contains(&"c".to_string()));
+
+    let alias = analysis.module_alias("u", Location::new(10, 1)).unwrap();
+    assert_eq!(alias.import_path.as_deref(), Some("utils"));
+
+    let exports: Vec<_> = analysis.exports().map(|s| s.name.as_str()).collect();
+    assert_eq!(exports, ["x", "add"]);
+}
+
+#[test]
+fn test_analysis_namespace_before() {
+    assert_eq!(
+        namespace_before("let a = u::ca", 14),
+        Some(vec!["u".to_string()])
+    );
+    assert_eq!(
+        namespace_before("x::y::", 7),
+        Some(vec!["x".to_string(), "y".to_string()])
+    );
+    assert_eq!(namespace_before("let a = ca", 11), None);
+}
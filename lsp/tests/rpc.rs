@@ -0,0 +1,69 @@
+use rhai_lsp::rpc::{parse_error, read_message, write_message, Message, MAX_CONTENT_LENGTH};
+use serde_json::json;
+
+use std::io::{BufReader, Cursor, Read};
+
+fn frame(content: &str) -> String {
+    format!("Content-Length: {}\r\n\r\n{}", content.len(), content)
+}
+
+#[test]
+fn test_rpc_round_trip() {
+    let message = json!({ "jsonrpc": "2.0", "id": 1, "method": "shutdown" });
+    let mut output = Vec::new();
+    write_message(&mut output, &message).unwrap();
+
+    let mut input = Cursor::new(output);
+    assert_eq!(
+        read_message(&mut input).unwrap(),
+        Some(Message::Valid(message))
+    );
+    assert_eq!(read_message(&mut input).unwrap(), None);
+}
+
+#[test]
+fn test_rpc_invalid_messages() {
+    let valid = r#"{"jsonrpc":"2.0","method":"exit"}"#;
+    let text = format!("{}{}{}", frame("{not json"), frame(valid), frame(valid));
+    let mut input = Cursor::new(text);
+
+    // A bad message does not prevent reading the next one
+    assert!(matches!(
+        read_message(&mut input).unwrap(),
+        Some(Message::Invalid(_))
+    ));
+    assert!(matches!(
+        read_message(&mut input).unwrap(),
+        Some(Message::Valid(_))
+    ));
+
+    let error = parse_error("bad");
+    assert_eq!(error["error"]["code"], -32700);
+    assert!(error["id"].is_null());
+}
+
+#[test]
+fn test_rpc_content_too_large() {
+    let valid = frame(r#"{"jsonrpc":"2.0","method":"exit"}"#);
+    let length = MAX_CONTENT_LENGTH + 1;
+
+    // The oversized content is skipped, not allocated
+    let header = format!("Content-Length: {}\r\n\r\n", length);
+    let input = Cursor::new(header)
+        .chain(std::io::repeat(b' ').take(length as u64))
+        .chain(Cursor::new(valid));
+    let mut input = BufReader::new(input);
+
+    assert!(matches!(
+        read_message(&mut input).unwrap(),
+        Some(Message::Invalid(_))
+    ));
+    assert!(matches!(
+        read_message(&mut input).unwrap(),
+        Some(Message::Valid(_))
+    ));
+
+    // A huge length without the content is an error, not an abort
+    let mut input = Cursor::new(format!("Content-Length: {}\r\n\r\n{{}}", usize::MAX));
+    assert!(read_message(&mut input).is_err());
+}
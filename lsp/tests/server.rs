@@ -0,0 +1,179 @@
+use rhai_lsp::server::path_to_uri;
+use rhai_lsp::{Metadata, Server};
+use serde_json::{json, Value};
+
+use std::fs;
+
+const METADATA: &str = r#"{
+    "": [
+        {
+            "namespace": [], "name": "greet",
+            "params": [ { "name": "name", "type": "string" } ],
+            "return_type": "string", "is_script": false, "doc": "Say hello."
+        }
+    ],
+    "host": [
+        {
+            "namespace": [], "name": "now",
+            "params": [], "return_type": "timestamp", "is_script": false, "doc": null
+        }
+    ]
+}"#;
+
+fn open(server: &mut Server, uri: &str, text: &str) -> Vec<Value> {
+    server.handle(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": { "textDocument": { "uri": uri, "languageId": "rhai", "version": 1, "text": text } },
+    }))
+}
+
+fn request(server: &mut Server, method: &str, uri: &str, line: u64, character: u64) -> Value {
+    let mut replies = server.handle(&json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": {
+            "textDocument": { "uri": uri },
+            "position": { "line": line, "character": character },
+        },
+    }));
+    assert_eq!(replies.len(), 1);
+    replies.remove(0)["result"].take()
+}
+
+fn labels(result: &Value) -> Vec<&str> {
+    result
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["label"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_server_lifecycle() {
+    let mut server = Server::new(Metadata::new());
+
+    let reply =
+        server.handle(&json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": {} }));
+    assert_eq!(reply[0]["result"]["capabilities"]["hoverProvider"], true);
+
+    let reply = server.handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": "foo" }));
+    assert_eq!(reply[0]["error"]["code"], -32601);
+
+    server.handle(&json!({ "jsonrpc": "2.0", "id": 2, "method": "shutdown" }));
+    assert_eq!(server.exit_code(), None);
+    server.handle(&json!({ "jsonrpc": "2.0", "method": "exit" }));
+    assert_eq!(server.exit_code(), Some(0));
+}
+
+#[test]
+fn test_server_diagnostics() {
+    let mut server = Server::new(Metadata::new());
+
+    let notifications = open(&mut server, "file:///test.rhai", "let x = 42;\nlet = 1;");
+    let diagnostics = notifications[0]["params"]["diagnostics"]
+        .as_array()
+        .unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["range"]["start"]["line"], 1);
+
+    let notifications = open(&mut server, "file:///test.rhai", "let x = 42;");
+    let diagnostics = notifications[0]["params"]["diagnostics"]
+        .as_array()
+        .unwrap();
+    assert!(diagnostics.is_empty());
+}
+
+#[test]
+fn test_server_metadata() {
+    let mut server = Server::new(Metadata::from_json(METADATA).unwrap());
+    let uri = "file:///test.rhai";
+
+    open(
+        &mut server,
+        uri,
+        "import \"host\" as h;\nlet x = greet(\"world\");\nh::",
+    );
+
+    let hover = request(&mut server, "textDocument/hover", uri, 1, 9);
+    let text = hover["contents"]["value"].as_str().unwrap();
+    assert!(text.contains("fn greet(name: string) -> string"));
+    assert!(text.contains("Say hello."));
+
+    let completion = request(&mut server, "textDocument/completion", uri, 2, 3);
+    assert_eq!(labels(&completion), ["now"]);
+
+    let completion = request(&mut server, "textDocument/completion", uri, 1, 22);
+    let labels = labels(&completion);
+    assert!(labels.contains(&"x"));
+    assert!(labels.contains(&"h"));
+    assert!(labels.contains(&"greet"));
+    assert!(labels.contains(&"let"));
+}
+
+#[test]
+fn test_server_import_script() {
+    let dir = std::env::temp_dir().join(format!("rhai-lsp-test-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("utils.rhai"),
+        "fn calc(x) { x * 2 }\nprivate fn hidden() { 0 }\nlet answer = 42;\nexport answer;\n",
+    )
+    .unwrap();
+
+    let mut server = Server::new(Metadata::new());
+    let uri = path_to_uri(&dir.join("main.rhai"));
+
+    open(
+        &mut server,
+        &uri,
+        "import \"utils\" as u;\nu::calc(u::answer);\nu::",
+    );
+
+    let completion = request(&mut server, "textDocument/completion", &uri, 2, 3);
+    let mut labels = labels(&completion);
+    labels.sort();
+    assert_eq!(labels, ["answer", "calc"]);
+
+    let definition = request(&mut server, "textDocument/definition", &uri, 1, 4);
+    assert_eq!(definition["uri"], path_to_uri(&dir.join("utils.rhai")));
+    assert_eq!(
+        definition["range"]["start"],
+        json!({ "line": 0, "character": 3 })
+    );
+
+    // Go to the import alias
+    let definition = request(&mut server, "textDocument/definition", &uri, 1, 0);
+    assert_eq!(
+        definition["range"]["start"],
+        json!({ "line": 0, "character": 18 })
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_server_utf16_positions() {
+    let mut server = Server::new(Metadata::new());
+    let uri = "file:///test.rhai";
+
+    // '😀' is one character, but two UTF-16 code units
+    open(&mut server, uri, "let s = \"😀\"; let x = 1; x + s");
+
+    let definition = request(&mut server, "textDocument/definition", uri, 0, 26);
+    assert_eq!(
+        definition["range"],
+        json!({ "start": { "line": 0, "character": 18 }, "end": { "line": 0, "character": 19 } })
+    );
+
+    let notifications = open(&mut server, uri, "\"😀\"; let = 1;");
+    let diagnostics = notifications[0]["params"]["diagnostics"]
+        .as_array()
+        .unwrap();
+    assert_eq!(
+        diagnostics[0]["range"]["start"],
+        json!({ "line": 0, "character": 10 })
+    );
+}
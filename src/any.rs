@@ -1,8 +1,11 @@
 //! Helper module which defines the `Any` trait to to allow dynamic value handling.
 
+use crate::datetime::{DateTime, Duration};
 use crate::fn_native::{FnPtr, SendSync};
+use crate::json::{write_json, JsonLimits};
 use crate::parser::{ImmutableString, INT};
 use crate::r#unsafe::{unsafe_cast_box, unsafe_try_cast};
+use crate::result::EvalAltResult;
 
 #[cfg(not(feature = "no_float"))]
 use crate::parser::FLOAT;
@@ -13,6 +16,9 @@ use crate::engine::Array;
 #[cfg(not(feature = "no_object"))]
 use crate::engine::Map;
 
+#[cfg(not(feature = "no_module"))]
+use crate::{fn_native::Shared, module::Module};
+
 use crate::stdlib::{
     any::{type_name, Any, TypeId},
     boxed::Box,
@@ -198,6 +204,8 @@ impl Dynamic {
 
             #[cfg(not(feature = "no_std"))]
             Union::Variant(value) if value.is::<Instant>() => "timestamp",
+            #[cfg(not(feature = "no_module"))]
+            Union::Variant(value) if value.is::<Shared<Module>>() => "module",
             Union::Variant(value) => (***value).type_name(),
         }
     }
@@ -214,11 +222,27 @@ pub(crate) fn map_std_type_name(name: &str) -> &str {
         "string"
     } else if name == type_name::<FnPtr>() {
         "Fn"
+    } else if name == type_name::<Dynamic>() {
+        "Dynamic"
     } else {
         #[cfg(not(feature = "no_std"))]
         if name == type_name::<Instant>() {
             return "timestamp";
         }
+        if name == type_name::<DateTime>() {
+            return "DateTime";
+        }
+        if name == type_name::<Duration>() {
+            return "Duration";
+        }
+        #[cfg(feature = "regex")]
+        if name == type_name::<regex::Regex>() {
+            return "Regex";
+        }
+        #[cfg(not(feature = "no_module"))]
+        if name == type_name::<Shared<Module>>() {
+            return "module";
+        }
         #[cfg(not(feature = "no_index"))]
         if name == type_name::<Array>() {
             return "array";
@@ -232,6 +256,41 @@ pub(crate) fn map_std_type_name(name: &str) -> &str {
     }
 }
 
+/// Get the Rust type name of a standard type from its `TypeId`.
+///
+/// Types that are not recognized map to `Dynamic`.
+pub(crate) fn std_type_name_of(id: TypeId) -> &'static str {
+    macro_rules! check_types {
+        ($($t:ty),*) => {
+            $(
+                if id == TypeId::of::<$t>() {
+                    return type_name::<$t>();
+                }
+            )*
+        };
+    }
+
+    check_types!((), bool, char, INT, ImmutableString, FnPtr);
+
+    #[cfg(not(feature = "only_i32"))]
+    #[cfg(not(feature = "only_i64"))]
+    check_types!(i8, u8, i16, u16, i32, u32, i64, u64, i128, u128);
+
+    #[cfg(not(feature = "no_float"))]
+    check_types!(FLOAT);
+
+    #[cfg(not(feature = "no_index"))]
+    check_types!(Array);
+
+    #[cfg(not(feature = "no_object"))]
+    check_types!(Map);
+
+    #[cfg(not(feature = "no_std"))]
+    check_types!(Instant);
+
+    type_name::<Dynamic>()
+}
+
 impl fmt::Display for Dynamic {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.0 {
@@ -403,6 +462,11 @@ impl Dynamic {
             }
         }
 
+        boxed = match unsafe_cast_box::<_, FnPtr>(boxed) {
+            Ok(fn_ptr) => return (*fn_ptr).into(),
+            Err(val) => val,
+        };
+
         boxed = match unsafe_cast_box::<_, Dynamic>(boxed) {
             Ok(d) => return *d,
             Err(val) => val,
@@ -740,6 +804,32 @@ impl Dynamic {
             _ => Err(self.type_name()),
         }
     }
+
+    /// Encode the `Dynamic` into JSON text.
+    ///
+    /// `()` is encoded as `null`, and the properties of object maps are written in sorted order.
+    /// Floating-point numbers always contain a decimal point or an exponent;
+    /// `NaN` and infinities are encoded as `null`.
+    ///
+    /// Function pointers and custom types cannot be encoded and return an error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Dynamic;
+    ///
+    /// let value = Dynamic::from("hello\n".to_string());
+    /// assert_eq!(value.to_json()?, r#""hello\n""#);
+    ///
+    /// let value = Dynamic::from(());
+    /// assert_eq!(value.to_json()?, "null");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_json(&self) -> Result<String, Box<EvalAltResult>> {
+        write_json(self, JsonLimits::new())
+    }
 }
 
 impl From<()> for Dynamic {
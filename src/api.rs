@@ -1,14 +1,22 @@
 //! Module that defines the extern API of `Engine`.
 
 use crate::any::{Dynamic, Variant};
+#[cfg(not(feature = "no_std"))]
+use crate::coverage::{Coverage, CoverageCollector};
+#[cfg(not(feature = "no_std"))]
+use crate::datetime::DateTime;
 use crate::engine::{make_getter, make_setter, Engine, Imports, State, FN_IDX_GET, FN_IDX_SET};
 use crate::error::ParseError;
 use crate::fn_args::FuncArgs;
-use crate::fn_native::{IteratorFn, SendSync};
+use crate::fn_metadata::{sort_fn_metadata, FnMetadata};
+use crate::fn_native::{IteratorFn, NativeCallContext, SendSync, Shared};
 use crate::fn_register::RegisterFn;
+use crate::json::{read_json, JsonLimits};
 use crate::module::{FuncReturn, Module};
 use crate::optimize::{optimize_into_ast, OptimizationLevel};
 use crate::parser::AST;
+#[cfg(not(feature = "no_std"))]
+use crate::profiler::{Profile, Profiler};
 use crate::result::EvalAltResult;
 use crate::scope::Scope;
 use crate::token::{lex, Position};
@@ -20,11 +28,16 @@ use crate::engine::Map;
 #[cfg(not(feature = "no_function"))]
 use crate::engine::get_script_function_by_signature;
 
+#[cfg(feature = "plugins")]
+#[cfg(not(feature = "no_object"))]
+use crate::plugin::RhaiType;
+
 use crate::stdlib::{
     any::{type_name, TypeId},
     boxed::Box,
     mem,
     string::{String, ToString},
+    vec::Vec,
 };
 
 #[cfg(not(feature = "no_std"))]
@@ -39,6 +52,10 @@ impl Engine {
     ///
     /// This function is very low level.  It takes a list of `TypeId`'s indicating the actual types of the parameters.
     ///
+    /// The function receives a [`NativeCallContext`] which provides access to the calling `Engine`,
+    /// the current set of script-defined functions, the imported modules and the position of the call.
+    /// It can be used to call function pointers and other functions.
+    ///
     /// Arguments are simply passed in as a mutable array of `&mut Dynamic`,
     /// The arguments are guaranteed to be of the correct types matching the `TypeId`'s.
     ///
@@ -53,7 +70,7 @@ impl Engine {
         &mut self,
         name: &str,
         arg_types: &[TypeId],
-        func: impl Fn(&Engine, &Module, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
+        func: impl Fn(&NativeCallContext, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
     ) -> &mut Self {
         self.global_module.set_raw_fn(name, arg_types, func);
         self
@@ -68,7 +85,7 @@ impl Engine {
     pub fn register_raw_fn_0<T: Variant + Clone>(
         &mut self,
         name: &str,
-        func: impl Fn(&Engine, &Module, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
+        func: impl Fn(&NativeCallContext, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
     ) -> &mut Self {
         self.global_module.set_raw_fn(name, &[], func);
         self
@@ -93,7 +110,7 @@ impl Engine {
     pub fn register_raw_fn_1<A: Variant + Clone, T: Variant + Clone>(
         &mut self,
         name: &str,
-        func: impl Fn(&Engine, &Module, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
+        func: impl Fn(&NativeCallContext, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
     ) -> &mut Self {
         self.global_module
             .set_raw_fn(name, &[TypeId::of::<A>()], func);
@@ -119,7 +136,7 @@ impl Engine {
     pub fn register_raw_fn_2<A: Variant + Clone, B: Variant + Clone, T: Variant + Clone>(
         &mut self,
         name: &str,
-        func: impl Fn(&Engine, &Module, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
+        func: impl Fn(&NativeCallContext, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
     ) -> &mut Self {
         self.global_module
             .set_raw_fn(name, &[TypeId::of::<A>(), TypeId::of::<B>()], func);
@@ -150,7 +167,7 @@ impl Engine {
     >(
         &mut self,
         name: &str,
-        func: impl Fn(&Engine, &Module, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
+        func: impl Fn(&NativeCallContext, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
     ) -> &mut Self {
         self.global_module.set_raw_fn(
             name,
@@ -185,7 +202,7 @@ impl Engine {
     >(
         &mut self,
         name: &str,
-        func: impl Fn(&Engine, &Module, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
+        func: impl Fn(&NativeCallContext, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
     ) -> &mut Self {
         self.global_module.set_raw_fn(
             name,
@@ -297,6 +314,38 @@ impl Engine {
         self
     }
 
+    /// Register a custom type implementing `RhaiType` (usually via `#[derive(RhaiType)]`),
+    /// together with its getters, setters and other functions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::plugins::RhaiType;
+    ///
+    /// #[derive(Clone, RhaiType)]
+    /// struct TestStruct {
+    ///     field: i64
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, RegisterFn};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_rhai_type::<TestStruct>()
+    ///       .register_fn("new_ts", || TestStruct { field: 1 });
+    ///
+    /// assert_eq!(engine.eval::<i64>("let x = new_ts(); x.field = 42; x.field")?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "plugins")]
+    #[cfg(not(feature = "no_object"))]
+    pub fn register_rhai_type<T: RhaiType>(&mut self) -> &mut Self {
+        T::register_rhai_type(self);
+        self
+    }
+
     /// Register an iterator adapter for a type with the `Engine`.
     /// This is an advanced feature.
     pub fn register_iterator<T: Variant + Clone>(&mut self, f: IteratorFn) -> &mut Self {
@@ -602,6 +651,132 @@ impl Engine {
             .register_indexer_set(setter)
     }
 
+    /// Describe all functions registered with the `Engine` under a particular name and
+    /// number of parameters, by setting their parameter names and documentation.
+    ///
+    /// The description is used when generating function metadata via `gen_fn_metadata`.
+    /// An empty `doc` string clears the documentation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, RegisterFn};
+    ///
+    /// let mut engine = Engine::new_raw();
+    ///
+    /// engine.register_fn("add", |x: i64, y: i64| x + y);
+    /// engine.describe_fn("add", &["x", "y"], "Add two numbers together.");
+    ///
+    /// let metadata = engine.gen_fn_metadata(false);
+    ///
+    /// assert_eq!(metadata[0].to_string(), "add(x: i64, y: i64) -> i64");
+    /// assert_eq!(metadata[0].doc.as_deref(), Some("Add two numbers together."));
+    /// ```
+    pub fn describe_fn(&mut self, name: &str, param_names: &[&str], doc: &str) -> &mut Self {
+        self.global_module.describe_fn(name, param_names, doc);
+        self
+    }
+
+    /// Generate metadata for all public functions registered with the `Engine`,
+    /// optionally including those in loaded packages.
+    ///
+    /// Type names are mapped to their pretty-print names, including those
+    /// registered via `register_type_with_name`.
+    /// The result is sorted by namespace, function name and number of parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, RegisterFn};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_fn("greet", |name: &str| format!("Hello, {}!", name));
+    ///
+    /// let metadata = engine.gen_fn_metadata(false);
+    ///
+    /// assert_eq!(metadata.len(), 1);
+    /// assert_eq!(metadata[0].to_string(), "greet(_: string) -> string");
+    ///
+    /// // Functions in packages are included as well
+    /// assert!(engine.gen_fn_metadata(true).iter().any(|f| f.name == "len"));
+    /// ```
+    pub fn gen_fn_metadata(&self, include_packages: bool) -> Vec<FnMetadata> {
+        let mut list = Vec::new();
+
+        self.collect_fn_metadata(&self.global_module, &mut Vec::new(), &mut list);
+
+        if include_packages {
+            self.packages
+                .iter()
+                .for_each(|p| self.collect_fn_metadata(p, &mut Vec::new(), &mut list));
+        }
+
+        sort_fn_metadata(&mut list);
+        list
+    }
+
+    /// Generate metadata for all public functions in a `Module`, including its sub-modules,
+    /// using the type names registered with the `Engine`.
+    ///
+    /// The result is sorted by namespace, function name and number of parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, Module};
+    ///
+    /// let mut sub_module = Module::new();
+    /// sub_module.set_fn_1("inc", |x: i64| Ok(x + 1));
+    ///
+    /// let mut module = Module::new();
+    /// module.set_sub_module("calc", sub_module);
+    ///
+    /// let engine = Engine::new();
+    /// let metadata = engine.gen_module_fn_metadata(&module);
+    ///
+    /// assert_eq!(metadata[0].to_string(), "calc::inc(_: i64) -> i64");
+    /// ```
+    pub fn gen_module_fn_metadata(&self, module: &Module) -> Vec<FnMetadata> {
+        let mut list = Vec::new();
+        self.collect_fn_metadata(module, &mut Vec::new(), &mut list);
+        sort_fn_metadata(&mut list);
+        list
+    }
+
+    /// Generate metadata for all public functions registered with the `Engine`,
+    /// optionally including those in loaded packages, encoded as a JSON array.
+    ///
+    /// See `FnMetadata::to_json` for the format of each function.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, RegisterFn};
+    ///
+    /// let mut engine = Engine::new_raw();
+    ///
+    /// engine.register_fn("answer", || 42_i64);
+    ///
+    /// assert_eq!(
+    ///     engine.gen_fn_metadata_to_json(false),
+    ///     r#"[{"namespace":[],"name":"answer","params":[],"return_type":"i64","is_script":false,"doc":null}]"#
+    /// );
+    /// ```
+    pub fn gen_fn_metadata_to_json(&self, include_packages: bool) -> String {
+        let mut json = String::from("[");
+
+        for (i, metadata) in self.gen_fn_metadata(include_packages).iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            json.push_str(&metadata.to_json());
+        }
+
+        json.push(']');
+        json
+    }
+
     /// Compile a string into an `AST`, which can be used later for evaluation.
     ///
     /// # Example
@@ -759,6 +934,8 @@ impl Engine {
 
     /// Compile a script file into an `AST`, which can be used later for evaluation.
     ///
+    /// The path of the script file is set as the source of the `AST`.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -821,7 +998,13 @@ impl Engine {
         scope: &Scope,
         path: PathBuf,
     ) -> Result<AST, Box<EvalAltResult>> {
-        Self::read_file(path).and_then(|contents| Ok(self.compile_with_scope(scope, &contents)?))
+        let source = path.to_string_lossy().to_string();
+
+        Self::read_file(path).and_then(|contents| {
+            let mut ast = self.compile_with_scope(scope, &contents)?;
+            ast.set_source(source);
+            Ok(ast)
+        })
     }
 
     /// Parse a JSON string into a map.
@@ -865,6 +1048,43 @@ impl Engine {
         self.eval_ast_with_scope(&mut scope, &ast)
     }
 
+    /// Parse JSON text into a `Dynamic` value.
+    ///
+    /// Unlike [`parse_json`](Engine::parse_json), any JSON value is accepted at the top level,
+    /// `null` always maps to `()`, and the text is never evaluated as a script.
+    ///
+    /// Numbers without a decimal point or an exponent are parsed as integers if they fit,
+    /// otherwise as floating-point numbers.
+    ///
+    /// The maximum expression depth and the string, array and map size limits of the `Engine`
+    /// apply to the parsed value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_index"))]
+    /// # {
+    /// use rhai::{Array, Engine};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let value = engine.parse_json_value(r#"[1, "hello", true, null]"#)?;
+    /// let array = value.cast::<Array>();
+    ///
+    /// assert_eq!(array.len(), 4);
+    /// assert_eq!(array[0].clone().cast::<i64>(), 1);
+    /// assert_eq!(array[1].clone().cast::<String>(), "hello");
+    /// assert_eq!(array[2].clone().cast::<bool>(), true);
+    /// assert_eq!(array[3].clone().cast::<()>(), ());
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse_json_value(&self, json: &str) -> Result<Dynamic, Box<EvalAltResult>> {
+        read_json(json, JsonLimits::from_engine(self))
+    }
+
     /// Compile a string containing an expression into an `AST`,
     /// which can be used later for evaluation.
     ///
@@ -959,7 +1179,8 @@ impl Engine {
     #[cfg(not(feature = "no_std"))]
     #[cfg(not(target_arch = "wasm32"))]
     pub fn eval_file<T: Variant + Clone>(&self, path: PathBuf) -> Result<T, Box<EvalAltResult>> {
-        Self::read_file(path).and_then(|contents| self.eval::<T>(&contents))
+        self.compile_file(path)
+            .and_then(|ast| self.eval_ast::<T>(&ast))
     }
 
     /// Evaluate a script file with own scope.
@@ -988,7 +1209,8 @@ impl Engine {
         scope: &mut Scope,
         path: PathBuf,
     ) -> Result<T, Box<EvalAltResult>> {
-        Self::read_file(path).and_then(|contents| self.eval_with_scope::<T>(scope, &contents))
+        self.compile_file_with_scope(scope, path)
+            .and_then(|ast| self.eval_ast_with_scope::<T>(scope, &ast))
     }
 
     /// Evaluate a string.
@@ -1175,6 +1397,13 @@ impl Engine {
         ast: &'a AST,
     ) -> Result<(Dynamic, u64), Box<EvalAltResult>> {
         let mut state = State::new();
+        state.source = ast.source_raw().cloned();
+        if ast.lib().iter_fn().next().is_some() {
+            state.lib = Some(ast.shared_lib().clone());
+        }
+
+        #[cfg(not(feature = "no_std"))]
+        self.register_coverage(ast);
 
         ast.statements()
             .iter()
@@ -1193,7 +1422,8 @@ impl Engine {
     #[cfg(not(feature = "no_std"))]
     #[cfg(not(target_arch = "wasm32"))]
     pub fn consume_file(&self, path: PathBuf) -> Result<(), Box<EvalAltResult>> {
-        Self::read_file(path).and_then(|contents| self.consume(&contents))
+        self.compile_file(path)
+            .and_then(|ast| self.consume_ast(&ast))
     }
 
     /// Evaluate a file with own scope, but throw away the result and only return error (if any).
@@ -1205,7 +1435,8 @@ impl Engine {
         scope: &mut Scope,
         path: PathBuf,
     ) -> Result<(), Box<EvalAltResult>> {
-        Self::read_file(path).and_then(|contents| self.consume_with_scope(scope, &contents))
+        self.compile_file_with_scope(scope, path)
+            .and_then(|ast| self.consume_ast_with_scope(scope, &ast))
     }
 
     /// Evaluate a string, but throw away the result and only return error (if any).
@@ -1241,8 +1472,15 @@ impl Engine {
         ast: &AST,
     ) -> Result<(), Box<EvalAltResult>> {
         let mut state = State::new();
+        state.source = ast.source_raw().cloned();
+        if ast.lib().iter_fn().next().is_some() {
+            state.lib = Some(ast.shared_lib().clone());
+        }
         let mut mods = Default::default();
 
+        #[cfg(not(feature = "no_std"))]
+        self.register_coverage(ast);
+
         ast.statements()
             .iter()
             .try_fold(().into(), |_, stmt| {
@@ -1302,7 +1540,14 @@ impl Engine {
         args: A,
     ) -> Result<T, Box<EvalAltResult>> {
         let mut arg_values = args.into_vec();
-        let result = self.call_fn_dynamic_raw(scope, ast, name, &mut None, arg_values.as_mut())?;
+        let result = self.call_fn_dynamic_raw(
+            scope,
+            ast,
+            Some(ast.shared_lib()),
+            name,
+            &mut None,
+            arg_values.as_mut(),
+        )?;
 
         let typ = self.map_type_name(result.type_name());
 
@@ -1373,7 +1618,7 @@ impl Engine {
         mut this_ptr: Option<&mut Dynamic>,
         mut arg_values: impl AsMut<[Dynamic]>,
     ) -> FuncReturn<Dynamic> {
-        self.call_fn_dynamic_raw(scope, lib, name, &mut this_ptr, arg_values.as_mut())
+        self.call_fn_dynamic_raw(scope, lib, None, name, &mut this_ptr, arg_values.as_mut())
     }
 
     /// Call a script function defined in an `AST` with multiple `Dynamic` arguments.
@@ -1389,6 +1634,7 @@ impl Engine {
         &self,
         scope: &mut Scope,
         lib: impl AsRef<Module>,
+        shared_lib: Option<&Shared<Module>>,
         name: &str,
         this_ptr: &mut Option<&mut Dynamic>,
         arg_values: &mut [Dynamic],
@@ -1404,9 +1650,15 @@ impl Engine {
             })?;
 
         let mut state = State::new();
+        state.lib = shared_lib.cloned();
         let mut mods = Imports::new();
         let args = args.as_mut();
 
+        #[cfg(not(feature = "no_std"))]
+        if let Some(coverage) = &self.coverage {
+            coverage.register_lib(lib);
+        }
+
         self.call_script_fn(
             scope, &mut mods, &mut state, lib, this_ptr, name, fn_def, args, 0,
         )
@@ -1434,15 +1686,17 @@ impl Engine {
         let lib = ast
             .lib()
             .iter_fn()
-            .filter(|(_, _, _, f)| f.is_script())
-            .map(|(_, _, _, f)| f.get_fn_def().clone())
+            .filter(|(_, _, _, f, _)| f.is_script())
+            .map(|(_, _, _, f, _)| f.get_fn_def().clone())
             .collect();
 
         #[cfg(feature = "no_function")]
         let lib = Default::default();
 
         let stmt = mem::take(ast.statements_mut());
-        optimize_into_ast(self, scope, stmt, lib, optimization_level)
+        let mut optimized = optimize_into_ast(self, scope, stmt, lib, optimization_level);
+        optimized.set_source_raw(ast.source_raw().cloned());
+        optimized
     }
 
     /// Register a callback for script evaluation progress.
@@ -1487,6 +1741,136 @@ impl Engine {
         self
     }
 
+    /// Set the clock that time functions read the current time from, instead of the system clock.
+    ///
+    /// The clock returns the current date-time, and is used by `now`, `timestamp` and `elapsed`
+    /// in scripts, as well as by the profiler. Supply a fixed or stepped clock to make scripts
+    /// that use time reproducible.
+    ///
+    /// Timestamps (which are `Instant`'s) advance as the clock moves from its reading when
+    /// `set_clock` is called.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # use std::sync::atomic::{AtomicI64, Ordering};
+    /// # use std::sync::Arc;
+    /// use rhai::{DateTime, Engine};
+    ///
+    /// let seconds = Arc::new(AtomicI64::new(1_600_000_000));
+    /// let clock = seconds.clone();
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.set_clock(move || {
+    ///     DateTime::from_unix_timestamp(clock.load(Ordering::SeqCst) as _).unwrap()
+    /// });
+    ///
+    /// assert_eq!(engine.eval::<String>("now().to_string()")?, "2020-09-13T12:26:40Z");
+    ///
+    /// let ts = engine.eval::<rhai::Dynamic>("timestamp()")?;
+    /// seconds.fetch_add(5, Ordering::SeqCst);
+    ///
+    /// let mut scope = rhai::Scope::new();
+    /// scope.push("ts", ts);
+    ///
+    /// assert_eq!(engine.eval_with_scope::<i64>(&mut scope, "ts.elapsed.to_int()")?, 5);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_std"))]
+    pub fn set_clock(&mut self, clock: impl Fn() -> DateTime + SendSync + 'static) -> &mut Self {
+        self.clock.set(Box::new(clock));
+        self
+    }
+
+    /// Take the profiling statistics collected so far, resetting them.
+    ///
+    /// Statistics are accumulated across all evaluations (e.g. `eval_ast`, `call_fn`)
+    /// since profiling is turned on via `set_profiling` or the last call to `take_profile`.
+    ///
+    /// Returns `None` if profiling is not turned on.
+    ///
+    /// Not available under the `no_std` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.set_profiling(true);
+    ///
+    /// let ast = engine.compile(r"
+    ///     fn double(x) { x * 2 }
+    ///     let sum = 0;
+    ///     for n in range(0, 10) { sum += double(n); }
+    ///     sum
+    /// ")?;
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 90);
+    ///
+    /// let profile = engine.take_profile().unwrap();
+    /// let stats = profile.get_fn("double").unwrap();
+    ///
+    /// assert!(stats.is_script);
+    /// assert_eq!(stats.calls, 10);
+    /// assert!(stats.inclusive_operations > 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_std"))]
+    pub fn take_profile(&self) -> Option<Profile> {
+        self.profiler.as_ref().map(Profiler::take)
+    }
+
+    /// Take the line and branch coverage collected so far, resetting it.
+    ///
+    /// Coverage is accumulated across all evaluations (e.g. `eval_ast`, `call_fn`)
+    /// since coverage is turned on via `set_coverage` or the last call to `take_coverage`.
+    ///
+    /// Returns `None` if coverage is not turned on.
+    ///
+    /// Not available under the `no_std` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.set_coverage(true);
+    ///
+    /// let mut ast = engine.compile(r"
+    ///     let x = 42;
+    ///     if x < 0 {
+    ///         x = -x;
+    ///     }
+    ///     x
+    /// ")?;
+    ///
+    /// ast.set_source("abs.rhai");
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 42);
+    ///
+    /// let coverage = engine.take_coverage().unwrap();
+    /// let abs = coverage.get("abs.rhai").unwrap();
+    ///
+    /// assert_eq!(abs.lines[&2], 1);   // 'let x = 42;' is evaluated once
+    /// assert_eq!(abs.lines[&4], 0);   // 'x = -x;' is never evaluated
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_std"))]
+    pub fn take_coverage(&self) -> Option<Coverage> {
+        self.coverage.as_ref().map(CoverageCollector::take)
+    }
+
     /// Override default action of `print` (print to stdout using `println!`)
     ///
     /// # Example
@@ -0,0 +1,82 @@
+//! Module implementing the clock that time functions read from.
+#![cfg(not(feature = "no_std"))]
+
+use crate::datetime::DateTime;
+
+use crate::stdlib::{boxed::Box, convert::TryFrom, fmt, time::Duration};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::stdlib::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+
+/// A function returning the current date-time.
+#[cfg(not(feature = "sync"))]
+pub(crate) type ClockFn = dyn Fn() -> DateTime + 'static;
+/// A function returning the current date-time.
+#[cfg(feature = "sync")]
+pub(crate) type ClockFn = dyn Fn() -> DateTime + Send + Sync + 'static;
+
+/// Source of the current time for an `Engine`.
+///
+/// By default, the system clock is used.
+#[derive(Default)]
+pub(crate) struct Clock {
+    /// Custom clock, plus the readings of the system's monotonic clock and of the custom clock
+    /// when it was set.
+    custom: Option<(Box<ClockFn>, Instant, DateTime)>,
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(if self.custom.is_some() {
+            "Clock(custom)"
+        } else {
+            "Clock(system)"
+        })
+    }
+}
+
+impl Clock {
+    /// Use a custom clock.
+    pub fn set(&mut self, clock: Box<ClockFn>) {
+        let start = clock();
+        self.custom = Some((clock, Instant::now(), start));
+    }
+    /// The current date-time, or `None` if there is no custom clock and the system clock
+    /// is not available.
+    pub fn now(&self) -> Option<DateTime> {
+        match &self.custom {
+            Some((clock, _, _)) => Some(clock()),
+            #[cfg(not(target_arch = "wasm32"))]
+            None => Some(DateTime::now()),
+            #[cfg(target_arch = "wasm32")]
+            None => None,
+        }
+    }
+    /// The current reading of the monotonic clock.
+    ///
+    /// With a custom clock, this is the `Instant` when the clock was set, advanced by the time
+    /// the custom clock has moved since. A custom clock that moves backwards before the time
+    /// it was set at reads as the `Instant` when it was set.
+    pub fn instant(&self) -> Instant {
+        match &self.custom {
+            Some((clock, start, start_time)) => {
+                let nanos = clock().duration_since(*start_time).as_nanos();
+
+                if nanos <= 0 {
+                    return *start;
+                }
+
+                let secs = u64::try_from(nanos / 1_000_000_000).unwrap_or(u64::MAX);
+                let nanos = (nanos % 1_000_000_000) as u32;
+
+                start
+                    .checked_add(Duration::new(secs, nanos))
+                    .unwrap_or(*start)
+            }
+            None => Instant::now(),
+        }
+    }
+}
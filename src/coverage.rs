@@ -0,0 +1,334 @@
+//! Module implementing line and branch coverage of scripts.
+#![cfg(not(feature = "no_std"))]
+
+use crate::engine::Engine;
+use crate::parser::{Expr, ImmutableString, Stmt, AST};
+use crate::token::Position;
+
+#[cfg(not(feature = "no_function"))]
+use crate::module::Module;
+
+use crate::stdlib::{
+    collections::BTreeMap,
+    fmt::Write,
+    mem,
+    string::{String, ToString},
+};
+
+#[cfg(not(feature = "sync"))]
+use crate::stdlib::cell::RefCell;
+
+#[cfg(feature = "sync")]
+use crate::stdlib::sync::RwLock;
+
+/// Index of the branch taken when the condition of an `if` statement is `true`,
+/// or when the right-hand side of an `&&` or `||` operator is evaluated.
+pub const BRANCH_TAKEN: usize = 0;
+
+/// Index of the branch taken when the condition of an `if` statement is `false`,
+/// or when an `&&` or `||` operator short-circuits.
+pub const BRANCH_NOT_TAKEN: usize = 1;
+
+/// Line and branch coverage of a single source.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SourceCoverage {
+    /// Number of statements evaluated, keyed by line number.
+    ///
+    /// Lines containing statements that have never been evaluated are included with a count of zero.
+    pub lines: BTreeMap<usize, u64>,
+    /// Number of times each branch is taken, keyed by the `Position` of the branching construct
+    /// (the opening `{` of the block of an `if` statement, or an `&&` or `||` operator)
+    /// and the index of the branch (`BRANCH_TAKEN` or `BRANCH_NOT_TAKEN`).
+    pub branches: BTreeMap<(Position, usize), u64>,
+}
+
+impl SourceCoverage {
+    /// Number of lines with at least one statement evaluated.
+    pub fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|&&n| n > 0).count()
+    }
+
+    /// Number of branches taken at least once.
+    pub fn branches_hit(&self) -> usize {
+        self.branches.values().filter(|&&n| n > 0).count()
+    }
+
+    /// Merge another `SourceCoverage` into this one, adding up the counts.
+    fn merge(&mut self, other: &Self) {
+        other
+            .lines
+            .iter()
+            .for_each(|(&line, &n)| *self.lines.entry(line).or_default() += n);
+        other
+            .branches
+            .iter()
+            .for_each(|(&key, &n)| *self.branches.entry(key).or_default() += n);
+    }
+}
+
+/// Line and branch coverage collected by an `Engine` with coverage turned on.
+///
+/// Statistics are keyed by the source of the `AST` (see `AST::set_source`).
+/// `AST`'s without a source are recorded under an empty name.
+///
+/// Not available under the `no_std` feature.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Coverage {
+    sources: BTreeMap<String, SourceCoverage>,
+}
+
+impl Coverage {
+    /// Get the coverage of a source.
+    pub fn get(&self, source: &str) -> Option<&SourceCoverage> {
+        self.sources.get(source)
+    }
+
+    /// Get an iterator over the coverage of all sources, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SourceCoverage)> {
+        self.sources.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Merge another `Coverage` into this one, adding up the counts.
+    pub fn merge(&mut self, other: &Self) -> &mut Self {
+        other.sources.iter().for_each(|(source, cov)| {
+            self.sources.entry(source.clone()).or_default().merge(cov);
+        });
+        self
+    }
+
+    /// Export the coverage as an lcov tracefile, which can be fed into tools such as `genhtml`.
+    ///
+    /// Only sources with a name are exported.
+    /// The block number of a branch is the character position of the branching construct.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.set_coverage(true);
+    ///
+    /// let mut ast = engine.compile("let x = 42;\nif x > 0 { x } else { -x }")?;
+    /// ast.set_source("abs.rhai");
+    ///
+    /// engine.eval_ast::<i64>(&ast)?;
+    ///
+    /// let lcov = engine.take_coverage().unwrap().to_lcov();
+    ///
+    /// assert!(lcov.starts_with("TN:\nSF:abs.rhai\n"));
+    /// assert!(lcov.contains("BRDA:2,10,0,1\nBRDA:2,10,1,0\n"));
+    /// assert!(lcov.contains("DA:1,1\n"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_lcov(&self) -> String {
+        let mut output = String::new();
+
+        for (source, cov) in self.sources.iter().filter(|(s, _)| !s.is_empty()) {
+            writeln!(output, "TN:").unwrap();
+            writeln!(output, "SF:{}", source).unwrap();
+
+            for (&(pos, branch), &n) in &cov.branches {
+                let line = pos.line().unwrap_or(0);
+                let block = pos.position().unwrap_or(0);
+
+                // A branching construct that never runs has no branch taken at all
+                let executed = [BRANCH_TAKEN, BRANCH_NOT_TAKEN]
+                    .iter()
+                    .any(|&b| cov.branches.get(&(pos, b)).copied().unwrap_or(0) > 0);
+
+                if executed {
+                    writeln!(output, "BRDA:{},{},{},{}", line, block, branch, n).unwrap();
+                } else {
+                    writeln!(output, "BRDA:{},{},{},-", line, block, branch).unwrap();
+                }
+            }
+
+            writeln!(output, "BRF:{}", cov.branches.len()).unwrap();
+            writeln!(output, "BRH:{}", cov.branches_hit()).unwrap();
+
+            for (line, n) in &cov.lines {
+                writeln!(output, "DA:{},{}", line, n).unwrap();
+            }
+
+            writeln!(output, "LF:{}", cov.lines.len()).unwrap();
+            writeln!(output, "LH:{}", cov.lines_hit()).unwrap();
+            writeln!(output, "end_of_record").unwrap();
+        }
+
+        output
+    }
+}
+
+/// Coverage collector attached to an `Engine`.
+#[derive(Debug, Default)]
+pub(crate) struct CoverageCollector {
+    #[cfg(not(feature = "sync"))]
+    data: RefCell<Coverage>,
+    #[cfg(feature = "sync")]
+    data: RwLock<Coverage>,
+}
+
+impl CoverageCollector {
+    /// Run a closure on the coverage of a source.
+    fn with_source<T>(
+        &self,
+        source: Option<&ImmutableString>,
+        f: impl FnOnce(&mut SourceCoverage) -> T,
+    ) -> T {
+        #[cfg(not(feature = "sync"))]
+        let mut data = self.data.borrow_mut();
+        #[cfg(feature = "sync")]
+        let mut data = self.data.write().unwrap();
+
+        let source = source.map(|s| s.as_str()).unwrap_or("");
+
+        // Avoid allocating a new key if the source is already known
+        if !data.sources.contains_key(source) {
+            data.sources.insert(source.to_string(), Default::default());
+        }
+
+        f(data.sources.get_mut(source).unwrap())
+    }
+
+    /// Register all statements and branches in a list of statements,
+    /// so that those never evaluated are reported.
+    pub fn register_stmts(&self, source: Option<&ImmutableString>, stmts: &[Stmt]) {
+        self.with_source(source, |cov| {
+            stmts.iter().for_each(|s| register_stmt(cov, s))
+        });
+    }
+
+    /// Register all statements and branches in the script-defined functions of a module.
+    #[cfg(not(feature = "no_function"))]
+    pub fn register_lib(&self, lib: &Module) {
+        lib.iter_script_fn().for_each(|f| {
+            self.with_source(f.source.as_ref(), |cov| register_stmt(cov, &f.body));
+        });
+    }
+
+    /// Record the evaluation of a statement.
+    pub fn hit_line(&self, source: Option<&ImmutableString>, pos: Position) {
+        if let Some(line) = pos.line() {
+            self.with_source(source, |cov| *cov.lines.entry(line).or_default() += 1);
+        }
+    }
+
+    /// Record a branch taken.
+    pub fn hit_branch(&self, source: Option<&ImmutableString>, pos: Position, branch: usize) {
+        if !pos.is_none() {
+            self.with_source(source, |cov| {
+                *cov.branches.entry((pos, branch)).or_default() += 1
+            });
+        }
+    }
+
+    /// Take the coverage collected so far.
+    pub fn take(&self) -> Coverage {
+        #[cfg(not(feature = "sync"))]
+        return mem::take(&mut self.data.borrow_mut());
+        #[cfg(feature = "sync")]
+        return mem::take(&mut self.data.write().unwrap());
+    }
+}
+
+/// Register a branching construct.
+fn register_branch(cov: &mut SourceCoverage, pos: Position) {
+    if !pos.is_none() {
+        cov.branches.entry((pos, BRANCH_TAKEN)).or_default();
+        cov.branches.entry((pos, BRANCH_NOT_TAKEN)).or_default();
+    }
+}
+
+/// Register a statement, together with all statements and branches nested within.
+fn register_stmt(cov: &mut SourceCoverage, stmt: &Stmt) {
+    if let Some(line) = stmt.position().line() {
+        cov.lines.entry(line).or_default();
+    }
+
+    match stmt {
+        Stmt::Noop(_) | Stmt::Continue(_) | Stmt::Break(_) | Stmt::Export(_) => (),
+        Stmt::IfThenElse(x) => {
+            register_branch(cov, x.1.position());
+            register_expr(cov, &x.0);
+            register_stmt(cov, &x.1);
+            if let Some(stmt) = &x.2 {
+                register_stmt(cov, stmt);
+            }
+        }
+        Stmt::While(x) => {
+            register_expr(cov, &x.0);
+            register_stmt(cov, &x.1);
+        }
+        Stmt::Loop(x) => register_stmt(cov, x),
+        Stmt::For(x) => {
+            register_expr(cov, &x.1);
+            register_stmt(cov, &x.2);
+        }
+        Stmt::Let(x) => {
+            if let Some(expr) = &x.1 {
+                register_expr(cov, expr);
+            }
+        }
+        Stmt::Const(x) => register_expr(cov, &x.1),
+        Stmt::Block(x) => x.0.iter().for_each(|stmt| register_stmt(cov, stmt)),
+        Stmt::Expr(x) => register_expr(cov, x),
+        Stmt::ReturnWithVal(x) => {
+            if let Some(expr) = &x.1 {
+                register_expr(cov, expr);
+            }
+        }
+        Stmt::Import(x) => register_expr(cov, &x.0),
+    }
+}
+
+/// Register all statements and branches nested within an expression.
+fn register_expr(cov: &mut SourceCoverage, expr: &Expr) {
+    match expr {
+        Expr::Stmt(x) => register_stmt(cov, &x.0),
+        Expr::Expr(x) => register_expr(cov, x),
+        Expr::FnCall(x) => x.3.iter().for_each(|expr| register_expr(cov, expr)),
+        Expr::Assignment(x) => {
+            register_expr(cov, &x.0);
+            register_expr(cov, &x.2);
+        }
+        Expr::Dot(x) | Expr::Index(x) | Expr::In(x) => {
+            register_expr(cov, &x.0);
+            register_expr(cov, &x.1);
+        }
+        Expr::And(x) | Expr::Or(x) => {
+            register_branch(cov, x.2);
+            register_expr(cov, &x.0);
+            register_expr(cov, &x.1);
+        }
+        Expr::Array(x) => x.0.iter().for_each(|expr| register_expr(cov, expr)),
+        Expr::Map(x) => x.0.iter().for_each(|(_, expr)| register_expr(cov, expr)),
+        Expr::Custom(x) => (x.0).0.iter().for_each(|expr| register_expr(cov, expr)),
+        _ => (),
+    }
+}
+
+impl Engine {
+    /// Register all statements and branches in an `AST`, if coverage is turned on.
+    pub(crate) fn register_coverage(&self, ast: &AST) {
+        if let Some(coverage) = &self.coverage {
+            coverage.register_stmts(ast.source_raw(), ast.as_ref());
+            #[cfg(not(feature = "no_function"))]
+            coverage.register_lib(ast.as_ref());
+        }
+    }
+
+    /// Record a branch taken in the coverage collector, if coverage is turned on.
+    pub(crate) fn cover_branch(
+        &self,
+        source: Option<&ImmutableString>,
+        pos: Position,
+        branch: usize,
+    ) {
+        if let Some(coverage) = &self.coverage {
+            coverage.hit_branch(source, pos, branch);
+        }
+    }
+}
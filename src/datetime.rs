@@ -0,0 +1,548 @@
+//! Module defining the calendar date-time and duration types.
+
+use crate::parser::INT;
+
+use crate::stdlib::{cmp::Ordering, fmt, format};
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(target_arch = "wasm32"))]
+use crate::stdlib::time::SystemTime;
+
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+const NANOS_PER_MINUTE: i128 = 60 * NANOS_PER_SEC;
+const NANOS_PER_DAY: i128 = 86_400 * NANOS_PER_SEC;
+
+/// Range of years supported by `DateTime`.
+const MIN_YEAR: INT = -9999;
+const MAX_YEAR: INT = 9999;
+
+/// Bound on nanoseconds since 1970-01-01, comfortably beyond the range of supported years.
+const MAX_NANOS: i128 = 12_000 * 366 * NANOS_PER_DAY;
+
+/// Maximum offset from UTC, in minutes.
+const MAX_OFFSET: i32 = 24 * 60 - 1;
+
+/// Number of days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: INT, month: INT, day: INT) -> INT {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Date in the proleptic Gregorian calendar of a number of days since 1970-01-01.
+fn civil_from_days(days: INT) -> (INT, INT, INT) {
+    let days = days + 719_468;
+    let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = mp + if mp < 10 { 3 } else { -9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn is_leap_year(year: INT) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: INT, month: INT) -> INT {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// A signed span of time, with nanosecond precision.
+///
+/// A day is always 86,400 seconds long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Duration {
+    nanos: i128,
+}
+
+impl Duration {
+    /// Create a `Duration` from a number of nanoseconds.
+    pub fn from_nanos(nanos: i128) -> Self {
+        Self { nanos }
+    }
+    /// Create a `Duration` from a number of milliseconds.
+    pub fn from_millis(millis: INT) -> Self {
+        Self::from_nanos(millis as i128 * 1_000_000)
+    }
+    /// Create a `Duration` from a number of seconds.
+    pub fn from_secs(secs: INT) -> Self {
+        Self::from_nanos(secs as i128 * NANOS_PER_SEC)
+    }
+    /// Total number of nanoseconds in this `Duration`.
+    pub fn as_nanos(&self) -> i128 {
+        self.nanos
+    }
+    /// Total number of whole milliseconds in this `Duration`, rounded towards zero.
+    pub fn whole_millis(&self) -> i128 {
+        self.nanos / 1_000_000
+    }
+    /// Total number of whole seconds in this `Duration`, rounded towards zero.
+    pub fn whole_secs(&self) -> i128 {
+        self.nanos / NANOS_PER_SEC
+    }
+    /// Add two `Duration`'s, returning `None` on overflow.
+    pub fn checked_add(&self, other: Self) -> Option<Self> {
+        self.nanos.checked_add(other.nanos).map(Self::from_nanos)
+    }
+    /// Subtract a `Duration`, returning `None` on overflow.
+    pub fn checked_sub(&self, other: Self) -> Option<Self> {
+        self.nanos.checked_sub(other.nanos).map(Self::from_nanos)
+    }
+    /// Multiply this `Duration` by a factor, returning `None` on overflow.
+    pub fn checked_mul(&self, factor: INT) -> Option<Self> {
+        self.nanos.checked_mul(factor as i128).map(Self::from_nanos)
+    }
+    /// Negate this `Duration`, returning `None` on overflow.
+    pub fn checked_neg(&self) -> Option<Self> {
+        self.nanos.checked_neg().map(Self::from_nanos)
+    }
+}
+
+/// Write a number of seconds and nanoseconds, omitting trailing zeros of the fraction.
+fn write_seconds(f: &mut fmt::Formatter, secs: u128, nanos: u128) -> fmt::Result {
+    if nanos == 0 {
+        write!(f, "{:02}", secs)
+    } else {
+        let fraction = format!("{:09}", nanos);
+        write!(f, "{:02}.{}", secs, fraction.trim_end_matches('0'))
+    }
+}
+
+/// Format as an ISO-8601 duration, e.g. `P1DT2H3M4.5S`.
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.nanos < 0 {
+            f.write_str("-")?;
+        }
+
+        let nanos = self.nanos.unsigned_abs();
+        let secs = nanos / NANOS_PER_SEC as u128;
+        let (days, hours, minutes, secs) =
+            (secs / 86_400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+        let nanos = nanos % NANOS_PER_SEC as u128;
+
+        f.write_str("P")?;
+        if days > 0 {
+            write!(f, "{}D", days)?;
+        }
+        if hours == 0 && minutes == 0 && secs == 0 && nanos == 0 {
+            return if days == 0 {
+                f.write_str("T0S")
+            } else {
+                Ok(())
+            };
+        }
+        f.write_str("T")?;
+        if hours > 0 {
+            write!(f, "{}H", hours)?;
+        }
+        if minutes > 0 {
+            write!(f, "{}M", minutes)?;
+        }
+        if secs > 0 || nanos > 0 {
+            if nanos == 0 {
+                write!(f, "{}", secs)?;
+            } else {
+                let fraction = format!("{:09}", nanos);
+                write!(f, "{}.{}", secs, fraction.trim_end_matches('0'))?;
+            }
+            f.write_str("S")?;
+        }
+        Ok(())
+    }
+}
+
+/// A calendar date-time in the proleptic Gregorian calendar, with nanosecond precision
+/// and a fixed offset from UTC.
+///
+/// Years from -9999 to 9999 are supported. Leap seconds are not.
+///
+/// Two `DateTime`'s are equal if they refer to the same instant, regardless of their offsets.
+///
+/// # Example
+///
+/// ```
+/// use rhai::{DateTime, Duration};
+///
+/// let dt = DateTime::parse("2020-02-29T12:30:00+08:00").unwrap();
+///
+/// assert_eq!(dt.weekday(), 6);
+/// assert_eq!(dt.to_utc().to_string(), "2020-02-29T04:30:00Z");
+///
+/// let later = dt.checked_add(Duration::from_secs(3600)).unwrap();
+/// assert_eq!(later.to_string(), "2020-02-29T13:30:00+08:00");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DateTime {
+    /// Nanoseconds since 1970-01-01T00:00:00Z.
+    nanos: i128,
+    /// Offset from UTC, in minutes.
+    offset: i32,
+}
+
+impl PartialEq for DateTime {
+    fn eq(&self, other: &Self) -> bool {
+        self.nanos == other.nanos
+    }
+}
+impl Eq for DateTime {}
+impl PartialOrd for DateTime {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for DateTime {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.nanos.cmp(&other.nanos)
+    }
+}
+
+impl DateTime {
+    /// Create a `DateTime`, checking that its local date is within range.
+    fn new(nanos: i128, offset: i32) -> Option<Self> {
+        if offset.abs() > MAX_OFFSET || !(-MAX_NANOS..=MAX_NANOS).contains(&nanos) {
+            return None;
+        }
+        let value = Self { nanos, offset };
+        let year = value.local_date().0;
+
+        if (MIN_YEAR..=MAX_YEAR).contains(&year) {
+            Some(value)
+        } else {
+            None
+        }
+    }
+    /// Create a `DateTime` in UTC from a date, at midnight.
+    ///
+    /// Returns `None` if the date is not valid.
+    pub fn from_ymd(year: INT, month: INT, day: INT) -> Option<Self> {
+        Self::from_ymd_hms(year, month, day, 0, 0, 0)
+    }
+    /// Create a `DateTime` in UTC from a date and a time.
+    ///
+    /// Returns `None` if the date or the time is not valid.
+    pub fn from_ymd_hms(
+        year: INT,
+        month: INT,
+        day: INT,
+        hour: INT,
+        minute: INT,
+        second: INT,
+    ) -> Option<Self> {
+        Self::from_parts(year, month, day, hour, minute, second, 0, 0)
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn from_parts(
+        year: INT,
+        month: INT,
+        day: INT,
+        hour: INT,
+        minute: INT,
+        second: INT,
+        nanos: INT,
+        offset: i32,
+    ) -> Option<Self> {
+        if !(MIN_YEAR..=MAX_YEAR).contains(&year)
+            || !(1..=12).contains(&month)
+            || !(1..=days_in_month(year, month)).contains(&day)
+            || !(0..24).contains(&hour)
+            || !(0..60).contains(&minute)
+            || !(0..60).contains(&second)
+        {
+            return None;
+        }
+
+        let days = days_from_civil(year, month, day) as i128;
+        let secs = ((hour * 60 + minute) * 60 + second) as i128;
+        let local = days * NANOS_PER_DAY + secs * NANOS_PER_SEC + nanos as i128;
+
+        Self::new(local - offset as i128 * NANOS_PER_MINUTE, offset)
+    }
+    /// Create a `DateTime` in UTC from a number of seconds since 1970-01-01T00:00:00Z.
+    ///
+    /// Returns `None` if the result is out of range.
+    pub fn from_unix_timestamp(secs: INT) -> Option<Self> {
+        Self::from_unix_nanos(secs as i128 * NANOS_PER_SEC)
+    }
+    /// Create a `DateTime` in UTC from a number of nanoseconds since 1970-01-01T00:00:00Z.
+    ///
+    /// Returns `None` if the result is out of range.
+    pub fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        Self::new(nanos, 0)
+    }
+    /// The current date-time in UTC, according to the system clock.
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn now() -> Self {
+        let nanos = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(d) => d.as_nanos() as i128,
+            Err(err) => -(err.duration().as_nanos() as i128),
+        };
+        Self::from_unix_nanos(nanos).expect("system time should be within range")
+    }
+    /// Parse an ISO-8601 date or date-time, such as `2020-02-29`, `2020-02-29T12:30:00Z`
+    /// or `2020-02-29 12:30:00.250+08:00`.
+    ///
+    /// A date-time without an offset is taken to be in UTC.
+    /// Returns `None` if the text is not valid.
+    pub fn parse(text: &str) -> Option<Self> {
+        Parser(text.as_bytes()).parse_datetime()
+    }
+
+    /// Number of nanoseconds since 1970-01-01T00:00:00Z.
+    pub fn unix_nanos(&self) -> i128 {
+        self.nanos
+    }
+    /// Number of whole seconds since 1970-01-01T00:00:00Z, rounded down.
+    pub fn unix_timestamp(&self) -> i64 {
+        self.nanos.div_euclid(NANOS_PER_SEC) as i64
+    }
+    /// Offset from UTC, in minutes.
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+    /// The same instant with a different offset from UTC, in minutes.
+    ///
+    /// Returns `None` if the offset is not within ±23:59, or the local date would be out of range.
+    pub fn with_offset(&self, offset: i32) -> Option<Self> {
+        Self::new(self.nanos, offset)
+    }
+    /// The same instant in UTC.
+    pub fn to_utc(&self) -> Self {
+        Self {
+            nanos: self.nanos,
+            offset: 0,
+        }
+    }
+
+    /// Nanoseconds since 1970-01-01T00:00:00 in local time.
+    fn local_nanos(&self) -> i128 {
+        self.nanos + self.offset as i128 * NANOS_PER_MINUTE
+    }
+    fn local_date(&self) -> (INT, INT, INT) {
+        civil_from_days(self.local_nanos().div_euclid(NANOS_PER_DAY) as INT)
+    }
+    fn local_time(&self) -> i128 {
+        self.local_nanos().rem_euclid(NANOS_PER_DAY)
+    }
+
+    /// Year, in local time.
+    pub fn year(&self) -> INT {
+        self.local_date().0
+    }
+    /// Month (1-12), in local time.
+    pub fn month(&self) -> u32 {
+        self.local_date().1 as u32
+    }
+    /// Day of the month (1-31), in local time.
+    pub fn day(&self) -> u32 {
+        self.local_date().2 as u32
+    }
+    /// Hour (0-23), in local time.
+    pub fn hour(&self) -> u32 {
+        (self.local_time() / (60 * NANOS_PER_MINUTE)) as u32
+    }
+    /// Minute (0-59), in local time.
+    pub fn minute(&self) -> u32 {
+        (self.local_time() / NANOS_PER_MINUTE % 60) as u32
+    }
+    /// Second (0-59).
+    pub fn second(&self) -> u32 {
+        (self.local_time() / NANOS_PER_SEC % 60) as u32
+    }
+    /// Fraction of the second, in nanoseconds.
+    pub fn nanosecond(&self) -> u32 {
+        (self.local_time() % NANOS_PER_SEC) as u32
+    }
+    /// ISO-8601 day of the week, from 1 (Monday) to 7 (Sunday), in local time.
+    pub fn weekday(&self) -> u32 {
+        // 1970-01-01 is a Thursday
+        let days = self.local_nanos().div_euclid(NANOS_PER_DAY);
+        ((days + 3).rem_euclid(7) + 1) as u32
+    }
+    /// Day of the year (1-366), in local time.
+    pub fn day_of_year(&self) -> u32 {
+        let days = self.local_nanos().div_euclid(NANOS_PER_DAY) as INT;
+        (days - days_from_civil(self.year(), 1, 1) + 1) as u32
+    }
+
+    /// Add a `Duration`, returning `None` if the result is out of range.
+    pub fn checked_add(&self, duration: Duration) -> Option<Self> {
+        Self::new(self.nanos.checked_add(duration.as_nanos())?, self.offset)
+    }
+    /// Subtract a `Duration`, returning `None` if the result is out of range.
+    pub fn checked_sub(&self, duration: Duration) -> Option<Self> {
+        Self::new(self.nanos.checked_sub(duration.as_nanos())?, self.offset)
+    }
+    /// The `Duration` from an earlier `DateTime` to this one, negative if `earlier` is later.
+    pub fn duration_since(&self, earlier: Self) -> Duration {
+        Duration::from_nanos(self.nanos - earlier.nanos)
+    }
+    /// Add a number of calendar months, keeping the local time of day.
+    ///
+    /// The day is clamped to the last day of the resulting month, so adding one month to
+    /// January 31 results in the last day of February.
+    /// Returns `None` if the result is out of range.
+    pub fn checked_add_months(&self, months: INT) -> Option<Self> {
+        let (year, month, day) = self.local_date();
+        let total = (year * 12 + month - 1).checked_add(months)?;
+        let (year, month) = (total.div_euclid(12), total.rem_euclid(12) + 1);
+
+        if !(MIN_YEAR..=MAX_YEAR).contains(&year) {
+            return None;
+        }
+
+        let day = day.min(days_in_month(year, month));
+        let local = days_from_civil(year, month, day) as i128 * NANOS_PER_DAY + self.local_time();
+        Self::new(local - self.offset as i128 * NANOS_PER_MINUTE, self.offset)
+    }
+}
+
+/// Format as an ISO-8601 date-time, e.g. `2020-02-29T12:30:00Z` or `2020-02-29T12:30:00.25+08:00`.
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (year, month, day) = self.local_date();
+
+        if year < 0 {
+            write!(f, "-{:04}", -year)?;
+        } else {
+            write!(f, "{:04}", year)?;
+        }
+        write!(
+            f,
+            "-{:02}-{:02}T{:02}:{:02}:",
+            month,
+            day,
+            self.hour(),
+            self.minute()
+        )?;
+        write_seconds(f, self.second() as u128, self.nanosecond() as u128)?;
+
+        match self.offset {
+            0 => f.write_str("Z"),
+            offset => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.abs();
+                write!(f, "{}{:02}:{:02}", sign, offset / 60, offset % 60)
+            }
+        }
+    }
+}
+
+/// Parser for ISO-8601 date-times.
+struct Parser<'a>(&'a [u8]);
+
+impl Parser<'_> {
+    fn eat(&mut self, ch: u8) -> bool {
+        match self.0.split_first() {
+            Some((&c, rest)) if c == ch => {
+                self.0 = rest;
+                true
+            }
+            _ => false,
+        }
+    }
+    fn eat_any(&mut self, chars: &[u8]) -> Option<u8> {
+        match self.0.split_first() {
+            Some((&c, rest)) if chars.contains(&c) => {
+                self.0 = rest;
+                Some(c)
+            }
+            _ => None,
+        }
+    }
+    /// Read exactly `len` decimal digits.
+    fn digits(&mut self, len: usize) -> Option<INT> {
+        if self.0.len() < len || !self.0[..len].iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        let value = self.0[..len]
+            .iter()
+            .fold(0, |n, &d| n * 10 + (d - b'0') as INT);
+        self.0 = &self.0[len..];
+        Some(value)
+    }
+    /// Read a fraction of a second, returning the number of nanoseconds.
+    fn fraction(&mut self) -> Option<INT> {
+        let len = self.0.iter().take_while(|d| d.is_ascii_digit()).count();
+
+        if len == 0 || len > 9 {
+            return None;
+        }
+        let value = self.digits(len)?;
+        Some(value * (10 as INT).pow((9 - len) as u32))
+    }
+    /// Read an offset from UTC (`Z`, `±HH`, `±HHMM` or `±HH:MM`), in minutes.
+    fn offset(&mut self) -> Option<i32> {
+        if self.eat_any(b"Zz").is_some() {
+            return Some(0);
+        }
+        let sign = if self.eat_any(b"+-")? == b'-' { -1 } else { 1 };
+        let hours = self.digits(2)?;
+        let minutes = if self.0.is_empty() {
+            0
+        } else {
+            self.eat(b':');
+            self.digits(2)?
+        };
+
+        if hours > 23 || minutes > 59 {
+            return None;
+        }
+        Some(sign * (hours * 60 + minutes) as i32)
+    }
+
+    fn parse_datetime(&mut self) -> Option<DateTime> {
+        let negative = self.eat(b'-');
+        let year = self.digits(4)?;
+        let year = if negative { -year } else { year };
+
+        if !self.eat(b'-') {
+            return None;
+        }
+        let month = self.digits(2)?;
+        if !self.eat(b'-') {
+            return None;
+        }
+        let day = self.digits(2)?;
+
+        let (mut hour, mut minute, mut second, mut nanos, mut offset) = (0, 0, 0, 0, 0);
+
+        if self.eat_any(b"Tt ").is_some() {
+            hour = self.digits(2)?;
+            if !self.eat(b':') {
+                return None;
+            }
+            minute = self.digits(2)?;
+
+            if self.eat(b':') {
+                second = self.digits(2)?;
+
+                if self.eat_any(b".,").is_some() {
+                    nanos = self.fraction()?;
+                }
+            }
+            if !self.0.is_empty() {
+                offset = self.offset()?;
+            }
+        }
+
+        if !self.0.is_empty() {
+            return None;
+        }
+
+        DateTime::from_parts(year, month, day, hour, minute, second, nanos, offset)
+    }
+}
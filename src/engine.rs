@@ -2,12 +2,22 @@
 
 use crate::any::{map_std_type_name, Dynamic, Union, Variant};
 use crate::calc_fn_hash;
+#[cfg(not(feature = "no_std"))]
+use crate::clock::Clock;
+#[cfg(not(feature = "no_std"))]
+use crate::coverage::{CoverageCollector, BRANCH_NOT_TAKEN, BRANCH_TAKEN};
 use crate::fn_call::run_builtin_op_assignment;
-use crate::fn_native::{CallableFunction, Callback, FnPtr};
+use crate::fn_native::{
+    shared_make_mut, CallableFunction, Callback, FnPtr, NativeCallContext, Shared,
+};
 use crate::module::{resolvers, Module, ModuleRef, ModuleResolver};
 use crate::optimize::OptimizationLevel;
-use crate::packages::{Package, PackagesCollection, StandardPackage};
+use crate::packages::{Package, PackagesCollection, RandomState, StandardPackage};
+#[cfg(feature = "regex")]
+use crate::packages::RegexCache;
 use crate::parser::{Expr, FnAccess, ImmutableString, ReturnType, ScriptFnDef, Stmt};
+#[cfg(not(feature = "no_std"))]
+use crate::profiler::Profiler;
 use crate::r#unsafe::unsafe_cast_var_name_to_lifetime;
 use crate::result::EvalAltResult;
 use crate::scope::{EntryType as ScopeEntryType, Scope};
@@ -19,6 +29,7 @@ use crate::stdlib::{
     any::TypeId,
     borrow::Cow,
     boxed::Box,
+    cell::Cell,
     collections::{HashMap, HashSet},
     fmt, format,
     iter::{empty, once},
@@ -39,7 +50,41 @@ pub type Array = Vec<Dynamic>;
 pub type Map = HashMap<ImmutableString, Dynamic>;
 
 /// A stack of imported modules.
-pub type Imports<'a> = Vec<(Cow<'a, str>, Module)>;
+///
+/// Modules may be shared with the module resolver and other importers,
+/// so they are copied before being modified.
+pub type Imports<'a> = Vec<(Cow<'a, str>, ImportedModule)>;
+
+/// A module in a stack of imported modules.
+#[derive(Debug, Clone)]
+pub enum ImportedModule {
+    /// A resolved module.
+    Resolved(Shared<Module>),
+    /// A module to be resolved on first use.
+    /// Wrapped values are the path of the module, the source of the importing script (if any),
+    /// and the position of the path.
+    ///
+    /// Not available under the `no_module` feature.
+    #[cfg(not(feature = "no_module"))]
+    Lazy(ImmutableString, Option<ImmutableString>, Position),
+}
+
+impl ImportedModule {
+    /// Get the module, if it is resolved.
+    pub fn module(&self) -> Option<&Shared<Module>> {
+        match self {
+            Self::Resolved(module) => Some(module),
+            #[cfg(not(feature = "no_module"))]
+            Self::Lazy(_, _, _) => None,
+        }
+    }
+}
+
+impl From<Shared<Module>> for ImportedModule {
+    fn from(module: Shared<Module>) -> Self {
+        Self::Resolved(module)
+    }
+}
 
 #[cfg(not(feature = "unchecked"))]
 #[cfg(debug_assertions)]
@@ -72,6 +117,8 @@ pub const KEYWORD_PRINT: &str = "print";
 pub const KEYWORD_DEBUG: &str = "debug";
 pub const KEYWORD_TYPE_OF: &str = "type_of";
 pub const KEYWORD_EVAL: &str = "eval";
+#[cfg(not(feature = "no_module"))]
+pub const KEYWORD_IMPORT: &str = "import";
 pub const KEYWORD_FN_PTR: &str = "Fn";
 pub const KEYWORD_FN_PTR_CALL: &str = "call";
 pub const KEYWORD_FN_PTR_CURRY: &str = "curry";
@@ -195,7 +242,7 @@ impl<T: Into<Dynamic>> From<T> for Target<'_> {
 ///
 /// This type uses some unsafe code, mainly for avoiding cloning of local variable names via
 /// direct lifetime casting.
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct State {
     /// Normally, access to variables are parsed with a relative offset into the scope to avoid a lookup.
     /// In some situation, e.g. after running an `eval` statement, subsequent offsets become mis-aligned.
@@ -208,6 +255,11 @@ pub struct State {
     pub operations: u64,
     /// Number of modules loaded.
     pub modules: usize,
+    /// Name of the source (e.g. the path of the script file) being evaluated, if any.
+    pub source: Option<ImmutableString>,
+    /// Shared library of script-defined functions being evaluated, if any.
+    /// Function pointers created by the script carry a reference to it.
+    pub lib: Option<Shared<Module>>,
 }
 
 impl State {
@@ -266,6 +318,9 @@ pub struct Engine {
 
     /// A module resolution service.
     pub(crate) module_resolver: Option<Box<dyn ModuleResolver>>,
+    /// Resolve imported modules on first use.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) lazy_imports: bool,
 
     /// A hashmap mapping type names to pretty-print names.
     pub(crate) type_names: Option<HashMap<String, String>>,
@@ -283,6 +338,20 @@ pub struct Engine {
     pub(crate) debug: Callback<str, ()>,
     /// Callback closure for progress reporting.
     pub(crate) progress: Option<Callback<u64, bool>>,
+    /// Profiler collecting per-function and per-line statistics.
+    #[cfg(not(feature = "no_std"))]
+    pub(crate) profiler: Option<Profiler>,
+    /// Collector of line and branch coverage.
+    #[cfg(not(feature = "no_std"))]
+    pub(crate) coverage: Option<CoverageCollector>,
+    /// Source of the current time for time functions and the profiler.
+    #[cfg(not(feature = "no_std"))]
+    pub(crate) clock: Clock,
+    /// State of the random number generator used by the `RandomPackage`.
+    pub(crate) random: RandomState,
+    /// Cache of regular expressions compiled by the `RegexPackage`.
+    #[cfg(feature = "regex")]
+    pub(crate) regex_cache: RegexCache,
 
     /// Optimize the AST after compilation.
     pub(crate) optimization_level: OptimizationLevel,
@@ -330,6 +399,8 @@ impl Default for Engine {
             module_resolver: Some(Box::new(resolvers::FileModuleResolver::new())),
             #[cfg(any(feature = "no_module", feature = "no_std", target_arch = "wasm32",))]
             module_resolver: None,
+            #[cfg(not(feature = "no_module"))]
+            lazy_imports: false,
 
             type_names: None,
             disabled_symbols: None,
@@ -343,6 +414,17 @@ impl Default for Engine {
             // progress callback
             progress: None,
 
+            // profiling and coverage
+            #[cfg(not(feature = "no_std"))]
+            profiler: None,
+            #[cfg(not(feature = "no_std"))]
+            coverage: None,
+            #[cfg(not(feature = "no_std"))]
+            clock: Default::default(),
+            random: Default::default(),
+            #[cfg(feature = "regex")]
+            regex_cache: Default::default(),
+
             // optimization level
             #[cfg(feature = "no_optimize")]
             optimization_level: OptimizationLevel::None,
@@ -383,107 +465,6 @@ fn default_print(s: &str) {
     println!("{}", s);
 }
 
-/// Search for a module within an imports stack.
-/// Position in `EvalAltResult` is `None` and must be set afterwards.
-pub fn search_imports<'s>(
-    mods: &'s Imports,
-    state: &mut State,
-    modules: &Box<ModuleRef>,
-) -> Result<&'s Module, Box<EvalAltResult>> {
-    let (root, root_pos) = modules.get(0);
-
-    // Qualified - check if the root module is directly indexed
-    let index = if state.always_search {
-        None
-    } else {
-        modules.index()
-    };
-
-    Ok(if let Some(index) = index {
-        let offset = mods.len() - index.get();
-        &mods.get(offset).unwrap().1
-    } else {
-        mods.iter()
-            .rev()
-            .find(|(n, _)| n == root)
-            .map(|(_, m)| m)
-            .ok_or_else(|| {
-                Box::new(EvalAltResult::ErrorModuleNotFound(
-                    root.to_string(),
-                    *root_pos,
-                ))
-            })?
-    })
-}
-
-/// Search for a module within an imports stack.
-/// Position in `EvalAltResult` is `None` and must be set afterwards.
-pub fn search_imports_mut<'s>(
-    mods: &'s mut Imports,
-    state: &mut State,
-    modules: &Box<ModuleRef>,
-) -> Result<&'s mut Module, Box<EvalAltResult>> {
-    let (root, root_pos) = modules.get(0);
-
-    // Qualified - check if the root module is directly indexed
-    let index = if state.always_search {
-        None
-    } else {
-        modules.index()
-    };
-
-    Ok(if let Some(index) = index {
-        let offset = mods.len() - index.get();
-        &mut mods.get_mut(offset).unwrap().1
-    } else {
-        mods.iter_mut()
-            .rev()
-            .find(|(n, _)| n == root)
-            .map(|(_, m)| m)
-            .ok_or_else(|| {
-                Box::new(EvalAltResult::ErrorModuleNotFound(
-                    root.to_string(),
-                    *root_pos,
-                ))
-            })?
-    })
-}
-
-/// Search for a variable within the scope and imports
-pub fn search_namespace<'s, 'a>(
-    scope: &'s mut Scope,
-    mods: &'s mut Imports,
-    state: &mut State,
-    this_ptr: &'s mut Option<&mut Dynamic>,
-    expr: &'a Expr,
-) -> Result<(&'s mut Dynamic, &'a str, ScopeEntryType, Position), Box<EvalAltResult>> {
-    match expr {
-        Expr::Variable(v) => match v.as_ref() {
-            // Qualified variable
-            ((name, pos), Some(modules), hash_var, _) => {
-                let module = search_imports_mut(mods, state, modules)?;
-                let target = module
-                    .get_qualified_var_mut(*hash_var)
-                    .map_err(|err| match *err {
-                        EvalAltResult::ErrorVariableNotFound(_, _) => {
-                            Box::new(EvalAltResult::ErrorVariableNotFound(
-                                format!("{}{}", modules, name),
-                                *pos,
-                            ))
-                        }
-                        _ => err.new_position(*pos),
-                    })?;
-
-                // Module variables are constant
-                Ok((target, name, ScopeEntryType::Constant, *pos))
-            }
-            // Normal variable access
-            _ => search_scope_only(scope, state, this_ptr, expr),
-        },
-        _ => unreachable!(),
-    }
-}
-
 /// Search for a variable within the scope
 pub fn search_scope_only<'s, 'a>(
     scope: &'s mut Scope,
@@ -523,6 +504,146 @@ pub fn search_scope_only<'s, 'a>(
 }
 
 impl Engine {
+    /// Resolve a module via the module resolver.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) fn resolve_import(
+        &self,
+        source: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        if let Some(resolver) = &self.module_resolver {
+            let mut module = resolver.resolve_with_source(self, source, path, pos)?;
+            if !module.is_indexed() {
+                shared_make_mut(&mut module).index_all_sub_modules();
+            }
+            Ok(module)
+        } else {
+            Err(Box::new(EvalAltResult::ErrorModuleNotFound(
+                path.into(),
+                pos,
+            )))
+        }
+    }
+
+    /// Find a module within an imports stack, resolving it if it is imported lazily.
+    fn find_import(
+        &self,
+        mods: &mut Imports,
+        state: &State,
+        modules: &ModuleRef,
+    ) -> Result<Option<usize>, Box<EvalAltResult>> {
+        let (root, _) = modules.get(0);
+
+        // Qualified - check if the root module is directly indexed
+        let index = if state.always_search {
+            None
+        } else {
+            modules.index()
+        };
+
+        let index = if let Some(index) = index {
+            mods.len() - index.get()
+        } else {
+            match mods.iter().rposition(|(n, _)| n == root) {
+                Some(index) => index,
+                None => return Ok(None),
+            }
+        };
+
+        #[cfg(not(feature = "no_module"))]
+        if let ImportedModule::Lazy(path, source, pos) = &mods.get(index).unwrap().1 {
+            let module = self.resolve_import(source.as_ref().map(|s| s.as_str()), path, *pos)?;
+            mods.get_mut(index).unwrap().1 = module.into();
+        }
+
+        Ok(Some(index))
+    }
+
+    /// Search for a module within an imports stack, then for a module value within the scope.
+    /// Position in `EvalAltResult` is `None` and must be set afterwards.
+    pub(crate) fn search_imports(
+        &self,
+        scope: &Scope,
+        mods: &mut Imports,
+        state: &State,
+        modules: &ModuleRef,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        if let Some(index) = self.find_import(mods, state, modules)? {
+            return Ok(mods.get(index).unwrap().1.module().unwrap().clone());
+        }
+
+        let (root, root_pos) = modules.get(0);
+
+        scope
+            .get_value::<Shared<Module>>(root)
+            .ok_or_else(|| Box::new(EvalAltResult::ErrorModuleNotFound(root.into(), *root_pos)))
+    }
+
+    /// Search for a module within an imports stack, then for a module value within the scope.
+    /// Position in `EvalAltResult` is `None` and must be set afterwards.
+    pub(crate) fn search_imports_mut<'s>(
+        &self,
+        scope: &'s mut Scope,
+        mods: &'s mut Imports,
+        state: &State,
+        modules: &ModuleRef,
+    ) -> Result<&'s mut Module, Box<EvalAltResult>> {
+        if let Some(index) = self.find_import(mods, state, modules)? {
+            return match &mut mods.get_mut(index).unwrap().1 {
+                ImportedModule::Resolved(module) => Ok(shared_make_mut(module)),
+                #[cfg(not(feature = "no_module"))]
+                ImportedModule::Lazy(_, _, _) => unreachable!(),
+            };
+        }
+
+        let (root, root_pos) = modules.get(0);
+
+        match scope.get_index(root) {
+            Some((index, _)) => scope.get_mut(index).0.downcast_mut::<Shared<Module>>(),
+            None => None,
+        }
+        .map(shared_make_mut)
+        .ok_or_else(|| Box::new(EvalAltResult::ErrorModuleNotFound(root.into(), *root_pos)))
+    }
+
+    /// Search for a variable within the scope and imports
+    pub(crate) fn search_namespace<'s, 'a>(
+        &self,
+        scope: &'s mut Scope,
+        mods: &'s mut Imports,
+        state: &mut State,
+        this_ptr: &'s mut Option<&mut Dynamic>,
+        expr: &'a Expr,
+    ) -> Result<(&'s mut Dynamic, &'a str, ScopeEntryType, Position), Box<EvalAltResult>> {
+        match expr {
+            Expr::Variable(v) => match v.as_ref() {
+                // Qualified variable
+                ((name, pos), Some(modules), hash_var, _) => {
+                    let module = self.search_imports_mut(scope, mods, state, modules)?;
+                    let target =
+                        module
+                            .get_qualified_var_mut(*hash_var)
+                            .map_err(|err| match *err {
+                                EvalAltResult::ErrorVariableNotFound(_, _) => {
+                                    Box::new(EvalAltResult::ErrorVariableNotFound(
+                                        format!("{}{}", modules, name),
+                                        *pos,
+                                    ))
+                                }
+                                _ => err.new_position(*pos),
+                            })?;
+
+                    // Module variables are constant
+                    Ok((target, name, ScopeEntryType::Constant, *pos))
+                }
+                // Normal variable access
+                _ => search_scope_only(scope, state, this_ptr, expr),
+            },
+            _ => unreachable!(),
+        }
+    }
+
     /// Create a new `Engine`
     pub fn new() -> Self {
         Default::default()
@@ -537,6 +658,8 @@ impl Engine {
             packages: Default::default(),
             global_module: Default::default(),
             module_resolver: None,
+            #[cfg(not(feature = "no_module"))]
+            lazy_imports: false,
 
             type_names: None,
             disabled_symbols: None,
@@ -547,6 +670,16 @@ impl Engine {
             debug: Box::new(|_| {}),
             progress: None,
 
+            #[cfg(not(feature = "no_std"))]
+            profiler: None,
+            #[cfg(not(feature = "no_std"))]
+            coverage: None,
+            #[cfg(not(feature = "no_std"))]
+            clock: Default::default(),
+            random: Default::default(),
+            #[cfg(feature = "regex")]
+            regex_cache: Default::default(),
+
             #[cfg(feature = "no_optimize")]
             optimization_level: OptimizationLevel::None,
 
@@ -568,6 +701,7 @@ impl Engine {
     /// Position in `EvalAltResult` is `None` and must be set afterwards.
     fn eval_dot_index_chain_helper(
         &self,
+        mods: &Imports,
         state: &mut State,
         lib: &Module,
         this_ptr: &mut Option<&mut Dynamic>,
@@ -604,11 +738,11 @@ impl Engine {
                         let (idx, expr, pos) = x.as_ref();
                         let idx_pos = idx.position();
                         let obj_ptr = &mut self
-                            .get_indexed_mut(state, lib, target, idx_val, idx_pos, false, level)?;
+                            .get_indexed_mut(mods, state, lib, target, idx_val, idx_pos, false, level)?;
 
                         self.eval_dot_index_chain_helper(
-                            state, lib, this_ptr, obj_ptr, expr, idx_values, next_chain, level,
-                            new_val,
+                            mods, state, lib, this_ptr, obj_ptr, expr, idx_values, next_chain,
+                            level, new_val,
                         )
                         .map_err(|err| err.new_position(*pos))
                     }
@@ -617,15 +751,15 @@ impl Engine {
                         let mut new_val = new_val.unwrap();
                         let mut idx_val2 = idx_val.clone();
 
-                        match self.get_indexed_mut(state, lib, target, idx_val, pos, true, level) {
+                        match self.get_indexed_mut(mods, state, lib, target, idx_val, pos, true, level) {
                             // Indexed value is an owned value - the only possibility is an indexer
                             // Try to call an index setter
                             Ok(obj_ptr) if obj_ptr.is_value() => {
                                 let args = &mut [target.as_mut(), &mut idx_val2, &mut new_val];
 
                                 self.exec_fn_call(
-                                    state, lib, FN_IDX_SET, true, 0, args, is_ref, true, None,
-                                    level,
+                                    mods, state, lib, FN_IDX_SET, true, 0, args, is_ref, true,
+                                    None, pos, level,
                                 )
                                 .or_else(|err| match *err {
                                     // If there is no index setter, no need to set it back because the indexer is read-only
@@ -649,8 +783,8 @@ impl Engine {
                                     let args = &mut [target.as_mut(), &mut idx_val2, &mut new_val];
 
                                     self.exec_fn_call(
-                                        state, lib, FN_IDX_SET, true, 0, args, is_ref, true, None,
-                                        level,
+                                        mods, state, lib, FN_IDX_SET, true, 0, args, is_ref, true,
+                                        None, pos, level,
                                     )?;
                                 }
                                 // Error
@@ -661,7 +795,7 @@ impl Engine {
                     }
                     // xxx[rhs]
                     _ => self
-                        .get_indexed_mut(state, lib, target, idx_val, pos, false, level)
+                        .get_indexed_mut(mods, state, lib, target, idx_val, pos, false, level)
                         .map(|v| (v.clone_into_dynamic(), false)),
                 }
             }
@@ -671,7 +805,7 @@ impl Engine {
                 match rhs {
                     // xxx.fn_name(arg_expr_list)
                     Expr::FnCall(x) if x.1.is_none() => {
-                        self.make_method_call(state, lib, target, rhs, idx_val, level)
+                        self.make_method_call(mods, state, lib, target, rhs, idx_val, level)
                     }
                     // xxx.module::fn_name(...) - syntax error
                     Expr::FnCall(_) => unreachable!(),
@@ -680,7 +814,7 @@ impl Engine {
                         let ((prop, _, _), pos) = x.as_ref();
                         let index = prop.clone().into();
                         let mut val =
-                            self.get_indexed_mut(state, lib, target, index, *pos, true, level)?;
+                            self.get_indexed_mut(mods, state, lib, target, index, *pos, true, level)?;
 
                         val.set_value(new_val.unwrap())
                             .map_err(|err| err.new_position(rhs.position()))?;
@@ -691,7 +825,7 @@ impl Engine {
                         let ((prop, _, _), pos) = x.as_ref();
                         let index = prop.clone().into();
                         let val =
-                            self.get_indexed_mut(state, lib, target, index, *pos, false, level)?;
+                            self.get_indexed_mut(mods, state, lib, target, index, *pos, false, level)?;
 
                         Ok((val.clone_into_dynamic(), false))
                     }
@@ -700,7 +834,8 @@ impl Engine {
                         let ((_, _, setter), pos) = x.as_ref();
                         let mut args = [target.as_mut(), new_val.as_mut().unwrap()];
                         self.exec_fn_call(
-                            state, lib, setter, true, 0, &mut args, is_ref, true, None, level,
+                            mods, state, lib, setter, true, 0, &mut args, is_ref, true, None, *pos,
+                            level,
                         )
                         .map(|(v, _)| (v, true))
                         .map_err(|err| err.new_position(*pos))
@@ -710,7 +845,8 @@ impl Engine {
                         let ((_, getter, _), pos) = x.as_ref();
                         let mut args = [target.as_mut()];
                         self.exec_fn_call(
-                            state, lib, getter, true, 0, &mut args, is_ref, true, None, level,
+                            mods, state, lib, getter, true, 0, &mut args, is_ref, true, None, *pos,
+                            level,
                         )
                         .map(|(v, _)| (v, false))
                         .map_err(|err| err.new_position(*pos))
@@ -723,12 +859,12 @@ impl Engine {
                             Expr::Property(p) => {
                                 let ((prop, _, _), _) = p.as_ref();
                                 let index = prop.clone().into();
-                                self.get_indexed_mut(state, lib, target, index, *pos, false, level)?
+                                self.get_indexed_mut(mods, state, lib, target, index, *pos, false, level)?
                             }
                             // {xxx:map}.fn_name(arg_expr_list)[expr] | {xxx:map}.fn_name(arg_expr_list).expr
                             Expr::FnCall(x) if x.1.is_none() => {
                                 let (val, _) = self.make_method_call(
-                                    state, lib, target, sub_lhs, idx_val, level,
+                                    mods, state, lib, target, sub_lhs, idx_val, level,
                                 )?;
                                 val.into()
                             }
@@ -739,8 +875,8 @@ impl Engine {
                         };
 
                         self.eval_dot_index_chain_helper(
-                            state, lib, this_ptr, &mut val, expr, idx_values, next_chain, level,
-                            new_val,
+                            mods, state, lib, this_ptr, &mut val, expr, idx_values, next_chain,
+                            level, new_val,
                         )
                         .map_err(|err| err.new_position(*pos))
                     }
@@ -756,8 +892,8 @@ impl Engine {
                                 let args = &mut arg_values[..1];
                                 let (mut val, updated) = self
                                     .exec_fn_call(
-                                        state, lib, getter, true, 0, args, is_ref, true, None,
-                                        level,
+                                        mods, state, lib, getter, true, 0, args, is_ref, true,
+                                        None, *pos, level,
                                     )
                                     .map_err(|err| err.new_position(*pos))?;
 
@@ -766,8 +902,8 @@ impl Engine {
 
                                 let (result, may_be_changed) = self
                                     .eval_dot_index_chain_helper(
-                                        state, lib, this_ptr, target, expr, idx_values, next_chain,
-                                        level, new_val,
+                                        mods, state, lib, this_ptr, target, expr, idx_values,
+                                        next_chain, level, new_val,
                                     )
                                     .map_err(|err| err.new_position(*pos))?;
 
@@ -776,8 +912,8 @@ impl Engine {
                                     // Re-use args because the first &mut parameter will not be consumed
                                     arg_values[1] = val;
                                     self.exec_fn_call(
-                                        state, lib, setter, true, 0, arg_values, is_ref, true,
-                                        None, level,
+                                        mods, state, lib, setter, true, 0, arg_values, is_ref,
+                                        true, None, *pos, level,
                                     )
                                     .or_else(
                                         |err| match *err {
@@ -795,14 +931,14 @@ impl Engine {
                             // xxx.fn_name(arg_expr_list)[expr] | xxx.fn_name(arg_expr_list).expr
                             Expr::FnCall(x) if x.1.is_none() => {
                                 let (mut val, _) = self.make_method_call(
-                                    state, lib, target, sub_lhs, idx_val, level,
+                                    mods, state, lib, target, sub_lhs, idx_val, level,
                                 )?;
                                 let val = &mut val;
                                 let target = &mut val.into();
 
                                 self.eval_dot_index_chain_helper(
-                                    state, lib, this_ptr, target, expr, idx_values, next_chain,
-                                    level, new_val,
+                                    mods, state, lib, this_ptr, target, expr, idx_values,
+                                    next_chain, level, new_val,
                                 )
                                 .map_err(|err| err.new_position(*pos))
                             }
@@ -856,8 +992,19 @@ impl Engine {
                 self.inc_operations(state)
                     .map_err(|err| err.new_position(*var_pos))?;
 
-                let (target, _, typ, pos) =
-                    search_namespace(scope, mods, state, this_ptr, dot_lhs)?;
+                // A module variable borrows from the imported modules,
+                // so keep a copy of them for function calls along the chain
+                let caller_mods;
+                let (target, _, typ, pos, mods) = if x.1.is_some() {
+                    caller_mods = mods.clone();
+                    let (target, name, typ, pos) =
+                        self.search_namespace(scope, mods, state, this_ptr, dot_lhs)?;
+                    (target, name, typ, pos, &caller_mods)
+                } else {
+                    let (target, name, typ, pos) =
+                        search_scope_only(scope, state, this_ptr, dot_lhs)?;
+                    (target, name, typ, pos, &*mods)
+                };
 
                 // Constants cannot be modified
                 match typ {
@@ -872,7 +1019,8 @@ impl Engine {
 
                 let obj_ptr = &mut target.into();
                 self.eval_dot_index_chain_helper(
-                    state, lib, &mut None, obj_ptr, dot_rhs, idx_values, chain_type, level, new_val,
+                    mods, state, lib, &mut None, obj_ptr, dot_rhs, idx_values, chain_type, level,
+                    new_val,
                 )
                 .map(|(v, _)| v)
                 .map_err(|err| err.new_position(*op_pos))
@@ -888,7 +1036,8 @@ impl Engine {
                 let val = self.eval_expr(scope, mods, state, lib, this_ptr, expr, level)?;
                 let obj_ptr = &mut val.into();
                 self.eval_dot_index_chain_helper(
-                    state, lib, this_ptr, obj_ptr, dot_rhs, idx_values, chain_type, level, new_val,
+                    mods, state, lib, this_ptr, obj_ptr, dot_rhs, idx_values, chain_type, level,
+                    new_val,
                 )
                 .map(|(v, _)| v)
                 .map_err(|err| err.new_position(*op_pos))
@@ -973,6 +1122,7 @@ impl Engine {
     /// Position in `EvalAltResult` may be None and should be set afterwards.
     fn get_indexed_mut<'a>(
         &self,
+        mods: &Imports,
         state: &mut State,
         lib: &Module,
         target: &'a mut Target,
@@ -1055,7 +1205,8 @@ impl Engine {
                 let val_type_name = val.type_name();
                 let args = &mut [val, &mut idx];
                 self.exec_fn_call(
-                    state, lib, FN_IDX_GET, true, 0, args, is_ref, true, None, level,
+                    mods, state, lib, FN_IDX_GET, true, 0, args, is_ref, true, None, idx_pos,
+                    level,
                 )
                 .map(|(v, _)| v.into())
                 .map_err(|e| match *e {
@@ -1115,7 +1266,7 @@ impl Engine {
                     let (r, _) = self
                         .call_fn_raw(
                             &mut scope, mods, state, lib, op, hashes, args, false, false,
-                            def_value, level,
+                            def_value, rhs.position(), level,
                         )
                         .map_err(|err| err.new_position(rhs.position()))?;
                     if r.as_bool().unwrap_or(false) {
@@ -1166,7 +1317,9 @@ impl Engine {
             Expr::FloatConstant(x) => Ok(x.0.into()),
             Expr::StringConstant(x) => Ok(x.0.to_string().into()),
             Expr::CharConstant(x) => Ok(x.0.into()),
-            Expr::FnPointer(x) => Ok(FnPtr::new_unchecked(x.0.clone(), Default::default()).into()),
+            Expr::FnPointer(x) => {
+                Ok(FnPtr::new_unchecked(x.0.clone(), Default::default(), state.lib.clone()).into())
+            }
             Expr::Variable(x) if (x.0).0 == KEYWORD_THIS => {
                 if let Some(val) = this_ptr {
                     Ok(val.clone())
@@ -1174,8 +1327,20 @@ impl Engine {
                     Err(Box::new(EvalAltResult::ErrorUnboundedThis((x.0).1)))
                 }
             }
+            // Qualified variable - avoid copying a shared module
+            Expr::Variable(x) if x.1.is_some() => {
+                let ((name, pos), modules, hash_var, _) = x.as_ref();
+                let modules = modules.as_ref().unwrap();
+                let module = self.search_imports(scope, mods, state, modules)?;
+                module.get_qualified_var(*hash_var).cloned().ok_or_else(|| {
+                    Box::new(EvalAltResult::ErrorVariableNotFound(
+                        format!("{}{}", modules, name),
+                        *pos,
+                    ))
+                })
+            }
             Expr::Variable(_) => {
-                let (val, _, _, _) = search_namespace(scope, mods, state, this_ptr, expr)?;
+                let (val, _, _, _) = self.search_namespace(scope, mods, state, this_ptr, expr)?;
                 Ok(val.clone())
             }
             Expr::Property(_) => unreachable!(),
@@ -1188,8 +1353,18 @@ impl Engine {
                 let (lhs_expr, op, rhs_expr, op_pos) = x.as_ref();
                 let mut rhs_val =
                     self.eval_expr(scope, mods, state, lib, this_ptr, rhs_expr, level)?;
-                let (lhs_ptr, name, typ, pos) =
-                    search_namespace(scope, mods, state, this_ptr, lhs_expr)?;
+                let (lhs_ptr, name, typ, pos) = match lhs_expr {
+                    // Module variables are constant
+                    Expr::Variable(x) if x.1.is_some() => {
+                        let (_, name, _, pos) =
+                            self.search_namespace(scope, mods, state, this_ptr, lhs_expr)?;
+                        return Err(Box::new(EvalAltResult::ErrorAssignmentToConstant(
+                            name.to_string(),
+                            pos,
+                        )));
+                    }
+                    _ => search_scope_only(scope, state, this_ptr, lhs_expr)?,
+                };
                 self.inc_operations(state)
                     .map_err(|err| err.new_position(pos))?;
 
@@ -1219,7 +1394,13 @@ impl Engine {
                             .or_else(|| self.packages.get_fn(hash_fn))
                         {
                             // Overriding exact implementation
-                            func(self, lib, &mut [lhs_ptr, &mut rhs_val])?;
+                            let ops = Cell::new(state.operations);
+                            let context = NativeCallContext::new_with_all_fields(
+                                self, mods, lib, *op_pos, &ops, level,
+                            );
+                            let result = func(&context, &mut [lhs_ptr, &mut rhs_val]);
+                            state.operations = ops.get();
+                            result?;
                         } else if run_builtin_op_assignment(op, lhs_ptr, &rhs_val)?.is_none() {
                             // Not built in, map to `var = var op rhs`
                             let op = &op[..op.len() - 1]; // extract operator without =
@@ -1229,7 +1410,8 @@ impl Engine {
                             // Run function
                             let (value, _) = self
                                 .exec_fn_call(
-                                    state, lib, op, true, hash, args, false, false, None, level,
+                                    mods, state, lib, op, true, hash, args, false, false, None,
+                                    *op_pos, level,
                                 )
                                 .map_err(|err| err.new_position(*op_pos))?;
                             // Set value to LHS
@@ -1257,7 +1439,9 @@ impl Engine {
                         &mut self.eval_expr(scope, mods, state, lib, this_ptr, lhs_expr, level)?,
                         &mut rhs_val,
                     ];
-                    self.exec_fn_call(state, lib, op, true, hash, args, false, false, None, level)
+                    self.exec_fn_call(
+                        mods, state, lib, op, true, hash, args, false, false, None, *op_pos, level,
+                    )
                         .map(|(v, _)| v)
                         .map_err(|err| err.new_position(*op_pos))?
                 });
@@ -1329,7 +1513,7 @@ impl Engine {
                 let ((name, native, pos), _, hash, args_expr, def_val) = x.as_ref();
                 self.make_function_call(
                     scope, mods, state, lib, this_ptr, name, args_expr, *def_val, *hash, *native,
-                    level,
+                    *pos, level,
                 )
                 .map_err(|err| err.new_position(*pos))
             }
@@ -1339,7 +1523,7 @@ impl Engine {
                 let ((name, _, pos), modules, hash, args_expr, def_val) = x.as_ref();
                 self.make_qualified_function_call(
                     scope, mods, state, lib, this_ptr, modules, name, args_expr, *def_val, *hash,
-                    level,
+                    *pos, level,
                 )
                 .map_err(|err| err.new_position(*pos))
             }
@@ -1347,13 +1531,26 @@ impl Engine {
             Expr::In(x) => self.eval_in_expr(scope, mods, state, lib, this_ptr, &x.0, &x.1, level),
 
             Expr::And(x) => {
-                let (lhs, rhs, _) = x.as_ref();
-                Ok((self
+                let (lhs, rhs, _pos) = x.as_ref();
+                let lhs_val = self
                     .eval_expr(scope, mods, state, lib, this_ptr, lhs, level)?
                     .as_bool()
                     .map_err(|_| {
                         EvalAltResult::ErrorBooleanArgMismatch("AND".into(), lhs.position())
-                    })?
+                    })?;
+
+                #[cfg(not(feature = "no_std"))]
+                self.cover_branch(
+                    state.source.as_ref(),
+                    *_pos,
+                    if lhs_val {
+                        BRANCH_TAKEN
+                    } else {
+                        BRANCH_NOT_TAKEN
+                    },
+                );
+
+                Ok((lhs_val
                     && // Short-circuit using &&
                 self
                     .eval_expr(scope, mods, state, lib, this_ptr, rhs, level)?
@@ -1365,13 +1562,26 @@ impl Engine {
             }
 
             Expr::Or(x) => {
-                let (lhs, rhs, _) = x.as_ref();
-                Ok((self
+                let (lhs, rhs, _pos) = x.as_ref();
+                let lhs_val = self
                     .eval_expr(scope, mods, state, lib, this_ptr, lhs, level)?
                     .as_bool()
                     .map_err(|_| {
                         EvalAltResult::ErrorBooleanArgMismatch("OR".into(), lhs.position())
-                    })?
+                    })?;
+
+                #[cfg(not(feature = "no_std"))]
+                self.cover_branch(
+                    state.source.as_ref(),
+                    *_pos,
+                    if !lhs_val {
+                        BRANCH_TAKEN
+                    } else {
+                        BRANCH_NOT_TAKEN
+                    },
+                );
+
+                Ok((lhs_val
                     || // Short-circuit using ||
                 self
                     .eval_expr(scope, mods, state, lib, this_ptr, rhs, level)?
@@ -1416,6 +1626,43 @@ impl Engine {
         this_ptr: &mut Option<&mut Dynamic>,
         stmt: &Stmt,
         level: usize,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        #[cfg(not(feature = "no_std"))]
+        if let Some(coverage) = &self.coverage {
+            coverage.hit_line(state.source.as_ref(), stmt.position());
+        }
+
+        #[cfg(not(feature = "no_std"))]
+        let prev_line = self
+            .profiler
+            .as_ref()
+            .map(|profiler| profiler.enter_stmt(stmt.position()));
+
+        #[cfg(not(feature = "no_std"))]
+        let result = self.eval_stmt_raw(scope, mods, state, lib, this_ptr, stmt, level);
+
+        #[cfg(not(feature = "no_std"))]
+        if let (Some(profiler), Some(prev_line)) = (&self.profiler, prev_line) {
+            profiler.exit_stmt(prev_line);
+        }
+
+        #[cfg(not(feature = "no_std"))]
+        return result;
+
+        #[cfg(feature = "no_std")]
+        return self.eval_stmt_raw(scope, mods, state, lib, this_ptr, stmt, level);
+    }
+
+    /// Evaluate a statement, without recording it in the profiler.
+    fn eval_stmt_raw(
+        &self,
+        scope: &mut Scope,
+        mods: &mut Imports,
+        state: &mut State,
+        lib: &Module,
+        this_ptr: &mut Option<&mut Dynamic>,
+        stmt: &Stmt,
+        level: usize,
     ) -> Result<Dynamic, Box<EvalAltResult>> {
         self.inc_operations(state)
             .map_err(|err| err.new_position(stmt.position()))?;
@@ -1456,6 +1703,17 @@ impl Engine {
                     .as_bool()
                     .map_err(|_| Box::new(EvalAltResult::ErrorLogicGuard(expr.position())))
                     .and_then(|guard_val| {
+                        #[cfg(not(feature = "no_std"))]
+                        self.cover_branch(
+                            state.source.as_ref(),
+                            if_block.position(),
+                            if guard_val {
+                                BRANCH_TAKEN
+                            } else {
+                                BRANCH_NOT_TAKEN
+                            },
+                        );
+
                         if guard_val {
                             self.eval_stmt(scope, mods, state, lib, this_ptr, if_block, level)
                         } else if let Some(stmt) = else_block {
@@ -1646,19 +1904,19 @@ impl Engine {
                     .try_cast::<ImmutableString>()
                 {
                     #[cfg(not(feature = "no_module"))]
-                    if let Some(resolver) = &self.module_resolver {
-                        let mut module = resolver.resolve(self, &path, expr.position())?;
-                        module.index_all_sub_modules();
+                    {
+                        let module = if self.lazy_imports {
+                            // Resolve the module on first use
+                            ImportedModule::Lazy(path, state.source.clone(), expr.position())
+                        } else {
+                            let source = state.source.as_ref().map(|s| s.as_str());
+                            self.resolve_import(source, &path, expr.position())?.into()
+                        };
                         mods.push((name.clone().into(), module));
 
                         state.modules += 1;
 
                         Ok(Default::default())
-                    } else {
-                        Err(Box::new(EvalAltResult::ErrorModuleNotFound(
-                            path.to_string(),
-                            expr.position(),
-                        )))
                     }
 
                     #[cfg(feature = "no_module")]
@@ -1670,12 +1928,15 @@ impl Engine {
 
             // Export statement
             Stmt::Export(list) => {
-                for ((id, id_pos), rename) in list.iter() {
-                    // Mark scope variables as public
-                    if let Some(index) = scope.get_index(id).map(|(i, _)| i) {
+                for ((id, id_pos), modules, rename) in list.iter() {
+                    if let Some(modules) = modules {
+                        // Items re-exported from imported modules are collected when creating the module
+                        self.search_imports(scope, mods, state, modules)?;
+                    } else if let Some(index) = scope.get_index(id).map(|(i, _)| i) {
+                        // Mark scope variables as public
                         let alias = rename.as_ref().map(|(n, _)| n).unwrap_or_else(|| id);
                         scope.set_entry_alias(index, alias.clone());
-                    } else {
+                    } else if !mods.iter().any(|(name, _)| name == id) {
                         return Err(Box::new(EvalAltResult::ErrorVariableNotFound(
                             id.into(),
                             *id_pos,
@@ -1804,6 +2065,11 @@ impl Engine {
     pub(crate) fn inc_operations(&self, state: &mut State) -> Result<(), Box<EvalAltResult>> {
         state.operations += 1;
 
+        #[cfg(not(feature = "no_std"))]
+        if let Some(profiler) = &self.profiler {
+            profiler.inc_operations();
+        }
+
         #[cfg(not(feature = "unchecked"))]
         // Guard against too many operations
         if self.max_operations > 0 && state.operations > self.max_operations {
@@ -3,12 +3,15 @@
 use crate::any::Dynamic;
 use crate::calc_fn_hash;
 use crate::engine::{
-    search_imports, search_namespace, search_scope_only, Engine, Imports, State, Target, FN_GET,
-    FN_IDX_GET, FN_IDX_SET, FN_SET, KEYWORD_DEBUG, KEYWORD_EVAL, KEYWORD_FN_PTR,
-    KEYWORD_FN_PTR_CALL, KEYWORD_FN_PTR_CURRY, KEYWORD_PRINT, KEYWORD_TYPE_OF,
+    search_scope_only, Engine, Imports, State, Target, FN_GET, FN_IDX_GET, FN_IDX_SET, FN_SET,
+    KEYWORD_DEBUG, KEYWORD_EVAL, KEYWORD_FN_PTR, KEYWORD_FN_PTR_CALL, KEYWORD_FN_PTR_CURRY,
+    KEYWORD_PRINT, KEYWORD_TYPE_OF,
 };
+
+#[cfg(not(feature = "no_module"))]
+use crate::engine::KEYWORD_IMPORT;
 use crate::error::ParseErrorType;
-use crate::fn_native::{FnCallArgs, FnPtr};
+use crate::fn_native::{FnCallArgs, FnPtr, NativeCallContext, Shared};
 use crate::module::{Module, ModuleRef};
 use crate::optimize::OptimizationLevel;
 use crate::parser::{Expr, ImmutableString, ScriptFnDef, AST, INT};
@@ -27,6 +30,7 @@ use crate::engine::Map;
 use crate::stdlib::{
     any::{type_name, TypeId},
     boxed::Box,
+    cell::Cell,
     convert::TryFrom,
     format,
     iter::{empty, once},
@@ -95,6 +99,25 @@ fn restore_first_arg<'a>(old_this_ptr: Option<&'a mut Dynamic>, args: &mut FnCal
     }
 }
 
+/// Make a function call via a function pointer, searching script-defined functions in the library
+/// carried by the function pointer (if any) instead of the current library.
+fn with_fn_ptr_lib<T>(
+    state: &mut State,
+    lib: &Module,
+    fn_lib: Option<Shared<Module>>,
+    f: impl FnOnce(&mut State, &Module) -> T,
+) -> T {
+    match fn_lib {
+        Some(fn_lib) => {
+            let orig_lib = state.lib.replace(fn_lib.clone());
+            let result = f(state, &fn_lib);
+            state.lib = orig_lib;
+            result
+        }
+        None => f(state, lib),
+    }
+}
+
 impl Engine {
     /// Universal method for calling functions either registered with the `Engine` or written in Rhai.
     /// Position in `EvalAltResult` is `None` and must be set afterwards.
@@ -107,7 +130,7 @@ impl Engine {
     pub(crate) fn call_fn_raw(
         &self,
         scope: &mut Scope,
-        mods: &mut Imports,
+        mods: &Imports,
         state: &mut State,
         lib: &Module,
         fn_name: &str,
@@ -116,6 +139,7 @@ impl Engine {
         is_ref: bool,
         is_method: bool,
         def_val: Option<bool>,
+        pos: Position,
         level: usize,
     ) -> Result<(Dynamic, bool), Box<EvalAltResult>> {
         self.inc_operations(state)?;
@@ -162,6 +186,8 @@ impl Engine {
             if func.is_script() {
                 // Run scripted function
                 let fn_def = func.get_fn_def();
+                // Script functions do not see the modules imported by the caller
+                let mods = &mut Imports::new();
 
                 // Method call of script function - map first argument to `this`
                 return if is_method {
@@ -193,7 +219,18 @@ impl Engine {
             }
 
             // Run external function
-            let result = func.get_native_fn()(self, lib, args)?;
+            #[cfg(not(feature = "no_std"))]
+            self.profile_enter_fn(state, fn_name, false);
+
+            let ops = Cell::new(state.operations);
+            let context = NativeCallContext::new_with_all_fields(self, mods, lib, pos, &ops, level);
+            let result = func.get_native_fn()(&context, args);
+            state.operations = ops.get();
+
+            #[cfg(not(feature = "no_std"))]
+            self.profile_exit_fn(state);
+
+            let result = result?;
 
             // Restore the original reference
             restore_first_arg(old_this_ptr, args);
@@ -317,9 +354,18 @@ impl Engine {
         args: &mut FnCallArgs,
         level: usize,
     ) -> Result<Dynamic, Box<EvalAltResult>> {
+        #[cfg(not(feature = "no_std"))]
+        self.profile_enter_fn(state, fn_name, true);
+
         let orig_scope_level = state.scope_level;
         state.scope_level += 1;
 
+        // Evaluate the function body within its own source
+        let orig_source = match &fn_def.source {
+            Some(source) => state.source.replace(source.clone()),
+            None => state.source.clone(),
+        };
+
         let prev_scope_len = scope.len();
         let prev_mods_len = mods.len();
 
@@ -360,6 +406,10 @@ impl Engine {
         scope.rewind(prev_scope_len);
         mods.truncate(prev_mods_len);
         state.scope_level = orig_scope_level;
+        state.source = orig_source;
+
+        #[cfg(not(feature = "no_std"))]
+        self.profile_exit_fn(state);
 
         result
     }
@@ -389,6 +439,7 @@ impl Engine {
     /// **DO NOT** reuse the argument values unless for the first `&mut` argument - all others are silently replaced by `()`!
     pub(crate) fn exec_fn_call(
         &self,
+        mods: &Imports,
         state: &mut State,
         lib: &Module,
         fn_name: &str,
@@ -398,6 +449,7 @@ impl Engine {
         is_ref: bool,
         is_method: bool,
         def_val: Option<bool>,
+        pos: Position,
         level: usize,
     ) -> Result<(Dynamic, bool), Box<EvalAltResult>> {
         // Qualifiers (none) + function name + number of arguments + argument `TypeId`'s.
@@ -433,10 +485,9 @@ impl Engine {
             // Normal function call
             _ => {
                 let mut scope = Scope::new();
-                let mut mods = Imports::new();
                 self.call_fn_raw(
-                    &mut scope, &mut mods, state, lib, fn_name, hashes, args, is_ref, is_method,
-                    def_val, level,
+                    &mut scope, mods, state, lib, fn_name, hashes, args, is_ref, is_method,
+                    def_val, pos, level,
                 )
             }
         }
@@ -488,6 +539,7 @@ impl Engine {
     /// Call a dot method.
     pub(crate) fn make_method_call(
         &self,
+        mods: &Imports,
         state: &mut State,
         lib: &Module,
         target: &mut Target,
@@ -524,9 +576,12 @@ impl Engine {
             let args = arg_values.as_mut();
 
             // Map it to name(args) in function-call style
-            self.exec_fn_call(
-                state, lib, fn_name, *native, hash, args, false, false, *def_val, level,
-            )
+            with_fn_ptr_lib(state, lib, fn_ptr.shared_lib().cloned(), |state, lib| {
+                self.exec_fn_call(
+                    mods, state, lib, fn_name, *native, hash, args, false, false, *def_val, *pos,
+                    level,
+                )
+            })
         } else if fn_name == KEYWORD_FN_PTR_CALL && idx.len() > 0 && idx[0].is::<FnPtr>() {
             // FnPtr call on object
             let fn_ptr = idx.remove(0).cast::<FnPtr>();
@@ -543,9 +598,12 @@ impl Engine {
             let args = arg_values.as_mut();
 
             // Map it to name(args) in function-call style
-            self.exec_fn_call(
-                state, lib, &fn_name, *native, hash, args, is_ref, true, *def_val, level,
-            )
+            with_fn_ptr_lib(state, lib, fn_ptr.shared_lib().cloned(), |state, lib| {
+                self.exec_fn_call(
+                    mods, state, lib, &fn_name, *native, hash, args, is_ref, true, *def_val,
+                    *pos, level,
+                )
+            })
         } else if fn_name == KEYWORD_FN_PTR_CURRY && obj.is::<FnPtr>() {
             // Curry call
             let fn_ptr = obj.downcast_ref::<FnPtr>().unwrap();
@@ -558,6 +616,7 @@ impl Engine {
                         .cloned()
                         .chain(idx.into_iter())
                         .collect(),
+                    fn_ptr.shared_lib().cloned(),
                 )
                 .into(),
                 false,
@@ -565,6 +624,7 @@ impl Engine {
         } else {
             let redirected;
             let mut hash = *hash;
+            let mut fn_lib = None;
 
             // Check if it is a map method call in OOP style
             #[cfg(not(feature = "no_object"))]
@@ -574,6 +634,7 @@ impl Engine {
                         // Remap the function name
                         redirected = f.get_fn_name().clone();
                         fn_name = &redirected;
+                        fn_lib = f.shared_lib().cloned();
                         // Recalculate the hash based on the new function name
                         hash = calc_fn_hash(empty(), fn_name, idx.len(), empty());
                     }
@@ -584,9 +645,12 @@ impl Engine {
             let mut arg_values = once(obj).chain(idx.iter_mut()).collect::<StaticVec<_>>();
             let args = arg_values.as_mut();
 
-            self.exec_fn_call(
-                state, lib, fn_name, *native, hash, args, is_ref, true, *def_val, level,
-            )
+            with_fn_ptr_lib(state, lib, fn_lib, |state, lib| {
+                self.exec_fn_call(
+                    mods, state, lib, fn_name, *native, hash, args, is_ref, true, *def_val, *pos,
+                    level,
+                )
+            })
         }
         .map_err(|err| err.new_position(*pos))?;
 
@@ -613,6 +677,7 @@ impl Engine {
         def_val: Option<bool>,
         mut hash: u64,
         native: bool,
+        pos: Position,
         level: usize,
     ) -> Result<Dynamic, Box<EvalAltResult>> {
         // Handle Fn()
@@ -634,7 +699,7 @@ impl Engine {
                         ))
                     })
                     .and_then(|s| FnPtr::try_from(s))
-                    .map(Into::<Dynamic>::into);
+                    .map(|f| f.with_lib(state.lib.clone()).into());
             }
         }
 
@@ -651,7 +716,7 @@ impl Engine {
                 )));
             }
 
-            let (fn_name, fn_curry) = fn_ptr.cast::<FnPtr>().take_data();
+            let (fn_name, fn_curry, fn_lib) = fn_ptr.cast::<FnPtr>().take_data();
 
             let curry: StaticVec<_> = args_expr
                 .iter()
@@ -662,10 +727,40 @@ impl Engine {
             return Ok(FnPtr::new_unchecked(
                 fn_name,
                 fn_curry.into_iter().chain(curry.into_iter()).collect(),
+                fn_lib,
             )
             .into());
         }
 
+        // Handle import()
+        #[cfg(not(feature = "no_module"))]
+        if name == KEYWORD_IMPORT && args_expr.len() == 1 {
+            let hash_fn = calc_fn_hash(empty(), name, 1, once(TypeId::of::<ImmutableString>()));
+
+            if !self.has_override(lib, hash_fn, hash) {
+                // import - only in function call style
+                let expr = &args_expr[0];
+                let path = self
+                    .eval_expr(scope, mods, state, lib, this_ptr, expr, level)?
+                    .try_cast::<ImmutableString>()
+                    .ok_or_else(|| Box::new(EvalAltResult::ErrorImportExpr(expr.position())))?;
+
+                // Guard against too many modules
+                if state.modules >= self.max_modules {
+                    return Err(Box::new(EvalAltResult::ErrorTooManyModules(
+                        expr.position(),
+                    )));
+                }
+
+                let source = state.source.as_ref().map(|s| s.as_str());
+                let module = self.resolve_import(source, &path, expr.position())?;
+
+                state.modules += 1;
+
+                return Ok(Dynamic::from(module));
+            }
+        }
+
         // Handle eval()
         if name == KEYWORD_EVAL && args_expr.len() == 1 {
             let hash_fn = calc_fn_hash(empty(), name, 1, once(TypeId::of::<ImmutableString>()));
@@ -693,6 +788,7 @@ impl Engine {
         let redirected;
         let mut args_expr = args_expr.as_ref();
         let mut curry: StaticVec<_> = Default::default();
+        let mut fn_lib = None;
         let mut name = name;
 
         if name == KEYWORD_FN_PTR_CALL && args_expr.len() >= 1 && !self.has_override(lib, 0, hash) {
@@ -703,7 +799,9 @@ impl Engine {
                 let fn_ptr = fn_name.cast::<FnPtr>();
                 curry = fn_ptr.curry().iter().cloned().collect();
                 // Redirect function name
-                redirected = fn_ptr.take_data().0;
+                let (fn_name, _, lib) = fn_ptr.take_data();
+                redirected = fn_name;
+                fn_lib = lib;
                 name = &redirected;
                 // Skip the first argument
                 args_expr = &args_expr.as_ref()[1..];
@@ -727,18 +825,19 @@ impl Engine {
             // No arguments
             args = Default::default();
         } else {
-            // See if the first argument is a variable, if so, convert to method-call style
-            // in order to leverage potential &mut first argument and avoid cloning the value
+            // See if the first argument is a variable (not module-qualified).
+            // If so, convert to method-call style in order to leverage potential
+            // &mut first argument and avoid cloning the value
             match args_expr.get(0).unwrap() {
                 // func(x, ...) -> x.func(...)
-                lhs @ Expr::Variable(_) => {
+                lhs @ Expr::Variable(x) if x.1.is_none() => {
                     arg_values = args_expr
                         .iter()
                         .skip(1)
                         .map(|expr| self.eval_expr(scope, mods, state, lib, this_ptr, expr, level))
                         .collect::<Result<_, _>>()?;
 
-                    let (target, _, _, pos) = search_namespace(scope, mods, state, this_ptr, lhs)?;
+                    let (target, _, _, pos) = search_scope_only(scope, state, this_ptr, lhs)?;
 
                     self.inc_operations(state)
                         .map_err(|err| err.new_position(pos))?;
@@ -750,7 +849,7 @@ impl Engine {
 
                     is_ref = true;
                 }
-                // func(..., ...)
+                // func(..., ...) or func(mod::x, ...)
                 _ => {
                     arg_values = args_expr
                         .iter()
@@ -763,9 +862,11 @@ impl Engine {
         }
 
         let args = args.as_mut();
-        self.exec_fn_call(
-            state, lib, name, native, hash, args, is_ref, false, def_val, level,
-        )
+        with_fn_ptr_lib(state, lib, fn_lib, |state, lib| {
+            self.exec_fn_call(
+                mods, state, lib, name, native, hash, args, is_ref, false, def_val, pos, level,
+            )
+        })
         .map(|(v, _)| v)
     }
 
@@ -783,9 +884,11 @@ impl Engine {
         args_expr: &[Expr],
         def_val: Option<bool>,
         hash_script: u64,
+        pos: Position,
         level: usize,
     ) -> Result<Dynamic, Box<EvalAltResult>> {
         let modules = modules.as_ref().unwrap();
+        let module = self.search_imports(scope, mods, state, modules)?;
 
         let mut arg_values: StaticVec<_>;
         let mut args: StaticVec<_>;
@@ -826,8 +929,6 @@ impl Engine {
             }
         }
 
-        let module = search_imports(mods, state, modules)?;
-
         // First search in script-defined functions (can override built-in)
         let func = match module.get_qualified_fn(hash_script) {
             Err(err) if matches!(*err, EvalAltResult::ErrorFunctionNotFound(_, _)) => {
@@ -855,11 +956,39 @@ impl Engine {
                 let fn_def = f.get_fn_def();
                 let mut scope = Scope::new();
                 let mut mods = Imports::new();
+
+                // Make the variables and imports of the function's own module visible
+                #[cfg(not(feature = "no_module"))]
+                if let Some(env) = &fn_def.env {
+                    env.variables.iter().for_each(|(name, value)| {
+                        scope.push_constant_dynamic(name.as_str(), value.clone());
+                    });
+                    mods.extend(
+                        env.modules
+                            .iter()
+                            .map(|(name, m)| (name.as_str().into(), m.clone())),
+                    );
+                }
+
                 self.call_script_fn(
                     &mut scope, &mut mods, state, lib, &mut None, name, fn_def, args, level,
                 )
             }
-            Ok(f) => f.get_native_fn()(self, lib, args.as_mut()),
+            Ok(f) => {
+                #[cfg(not(feature = "no_std"))]
+                self.profile_enter_fn(state, name, false);
+
+                let ops = Cell::new(state.operations);
+                let context =
+                    NativeCallContext::new_with_all_fields(self, mods, lib, pos, &ops, level);
+                let result = f.get_native_fn()(&context, args.as_mut());
+                state.operations = ops.get();
+
+                #[cfg(not(feature = "no_std"))]
+                self.profile_exit_fn(state);
+
+                result
+            }
             Err(err) => match *err {
                 EvalAltResult::ErrorFunctionNotFound(_, _) if def_val.is_some() => {
                     Ok(def_val.unwrap().into())
@@ -0,0 +1,238 @@
+//! Module defining machine-readable metadata for registered functions.
+
+use crate::any::Dynamic;
+use crate::engine::Engine;
+use crate::fn_native::CallableFunction;
+use crate::json::write_string;
+use crate::module::Module;
+use crate::parser::FnAccess;
+use crate::utils::StaticVec;
+
+use crate::stdlib::{
+    any::type_name,
+    fmt,
+    iter::repeat,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Signature information kept alongside each function registered into a `Module`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FnSignature {
+    /// Names of the parameters. Empty if not known.
+    pub param_names: StaticVec<String>,
+    /// Rust type names of the parameters.
+    pub param_types: StaticVec<&'static str>,
+    /// Rust type name of the return value. Empty if not known.
+    pub return_type: &'static str,
+    /// Documentation of the function, if any.
+    pub doc: Option<String>,
+}
+
+impl FnSignature {
+    /// Create a new `FnSignature` with parameter and return types.
+    pub fn new(param_types: &[&'static str], return_type: &'static str) -> Self {
+        Self {
+            param_types: param_types.iter().cloned().collect(),
+            return_type,
+            ..Default::default()
+        }
+    }
+}
+
+/// Machine-readable description of a function registered with an `Engine` or a `Module`.
+///
+/// Type names are mapped to their pretty-print names, including any custom names
+/// registered via `Engine::register_type_with_name`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FnMetadata {
+    /// Path of sub-modules leading to the function. Empty for functions at the top level.
+    pub namespace: Vec<String>,
+    /// Name of the function.
+    pub name: String,
+    /// Names of the parameters. `None` if not known.
+    pub param_names: Vec<Option<String>>,
+    /// Type names of the parameters.
+    pub param_types: Vec<String>,
+    /// Type name of the return value.
+    pub return_type: String,
+    /// Is this a script-defined function?
+    pub is_script: bool,
+    /// Documentation of the function, if any.
+    pub doc: Option<String>,
+}
+
+impl fmt::Display for FnMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.namespace
+            .iter()
+            .try_for_each(|ns| write!(f, "{}::", ns))?;
+
+        write!(f, "{}(", self.name)?;
+
+        for (i, (name, typ)) in self
+            .param_names
+            .iter()
+            .zip(self.param_types.iter())
+            .enumerate()
+        {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}: {}", name.as_ref().map_or("_", String::as_str), typ)?;
+        }
+
+        write!(f, ") -> {}", self.return_type)
+    }
+}
+
+impl FnMetadata {
+    /// Encode this function's metadata as a JSON object.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, RegisterFn};
+    ///
+    /// let mut engine = Engine::new_raw();
+    ///
+    /// engine.register_fn("add", |x: i64, y: i64| x + y);
+    /// engine.describe_fn("add", &["x", "y"], "Add two numbers.");
+    ///
+    /// let metadata = engine.gen_fn_metadata(false);
+    ///
+    /// assert_eq!(
+    ///     metadata[0].to_json(),
+    ///     r#"{"namespace":[],"name":"add","params":[{"name":"x","type":"i64"},{"name":"y","type":"i64"}],"return_type":"i64","is_script":false,"doc":"Add two numbers."}"#
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+
+        json.push_str(r#"{"namespace":["#);
+        for (i, ns) in self.namespace.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            write_string(&mut json, ns);
+        }
+
+        json.push_str(r#"],"name":"#);
+        write_string(&mut json, &self.name);
+
+        json.push_str(r#","params":["#);
+        for (i, (name, typ)) in self
+            .param_names
+            .iter()
+            .zip(self.param_types.iter())
+            .enumerate()
+        {
+            if i > 0 {
+                json.push(',');
+            }
+            json.push_str(r#"{"name":"#);
+            match name {
+                Some(name) => write_string(&mut json, name),
+                None => json.push_str("null"),
+            }
+            json.push_str(r#","type":"#);
+            write_string(&mut json, typ);
+            json.push('}');
+        }
+
+        json.push_str(r#"],"return_type":"#);
+        write_string(&mut json, &self.return_type);
+
+        json.push_str(r#","is_script":"#);
+        json.push_str(if self.is_script { "true" } else { "false" });
+
+        json.push_str(r#","doc":"#);
+        match &self.doc {
+            Some(doc) => write_string(&mut json, doc),
+            None => json.push_str("null"),
+        }
+
+        json.push('}');
+        json
+    }
+}
+
+/// Sort a list of `FnMetadata` by namespace, function name and number of parameters.
+pub(crate) fn sort_fn_metadata(list: &mut [FnMetadata]) {
+    list.sort_by(|a, b| {
+        a.namespace
+            .cmp(&b.namespace)
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.param_types.len().cmp(&b.param_types.len()))
+            .then_with(|| a.param_types.cmp(&b.param_types))
+    });
+}
+
+impl Engine {
+    /// Collect metadata for all public functions in a `Module` and its sub-modules.
+    pub(crate) fn collect_fn_metadata(
+        &self,
+        module: &Module,
+        namespace: &mut Vec<String>,
+        list: &mut Vec<FnMetadata>,
+    ) {
+        let dynamic_type = type_name::<Dynamic>();
+
+        for (name, access, _, func, sig) in module.iter_fn() {
+            if *access == FnAccess::Private {
+                continue;
+            }
+
+            let metadata = match func {
+                #[cfg(not(feature = "no_function"))]
+                CallableFunction::Script(fn_def) => FnMetadata {
+                    namespace: namespace.clone(),
+                    name: name.clone(),
+                    param_names: fn_def.params.iter().cloned().map(Some).collect(),
+                    param_types: fn_def
+                        .params
+                        .iter()
+                        .map(|_| self.map_type_name(dynamic_type).to_string())
+                        .collect(),
+                    return_type: self.map_type_name(dynamic_type).to_string(),
+                    is_script: true,
+                    doc: sig.doc.clone(),
+                },
+                _ => FnMetadata {
+                    namespace: namespace.clone(),
+                    name: name.clone(),
+                    param_names: sig
+                        .param_names
+                        .iter()
+                        .cloned()
+                        .map(Some)
+                        .chain(repeat(None))
+                        .take(sig.param_types.len())
+                        .collect(),
+                    param_types: sig
+                        .param_types
+                        .iter()
+                        .map(|&t| self.map_type_name(t).to_string())
+                        .collect(),
+                    return_type: self
+                        .map_type_name(if sig.return_type.is_empty() {
+                            dynamic_type
+                        } else {
+                            sig.return_type
+                        })
+                        .to_string(),
+                    is_script: false,
+                    doc: sig.doc.clone(),
+                },
+            };
+
+            list.push(metadata);
+        }
+
+        for (name, sub_module) in module.iter_sub_modules() {
+            namespace.push(name.clone());
+            self.collect_fn_metadata(sub_module, namespace, list);
+            namespace.pop();
+        }
+    }
+}
@@ -1,16 +1,19 @@
 //! Module defining interfaces to native-Rust functions.
 
 use crate::any::Dynamic;
-use crate::engine::Engine;
+use crate::engine::{Engine, Imports, State};
 use crate::module::{FuncReturn, Module};
 use crate::parser::ScriptFnDef;
 use crate::result::EvalAltResult;
 use crate::stdlib::vec::Vec;
 use crate::token::{is_valid_identifier, Position};
 use crate::utils::{ImmutableString, StaticVec};
-use crate::Scope;
+use crate::{calc_fn_hash, Scope};
 
-use crate::stdlib::{boxed::Box, convert::TryFrom, fmt, mem, rc::Rc, string::String, sync::Arc};
+use crate::stdlib::{
+    boxed::Box, cell::Cell, convert::TryFrom, fmt, iter::empty, mem, rc::Rc, string::String,
+    sync::Arc,
+};
 
 /// Trait that maps to `Send + Sync` only under the `sync` feature.
 #[cfg(feature = "sync")]
@@ -50,17 +53,197 @@ pub fn shared_take<T: Clone>(value: Shared<T>) -> T {
     return Arc::try_unwrap(value).map_err(|_| ()).unwrap();
 }
 
+/// Consume a `Shared` resource, returning the wrapped value if it is unique (i.e. not shared),
+/// or a cloned copy otherwise.
+#[cfg(not(feature = "no_module"))]
+pub fn shared_take_or_clone<T: Clone>(value: Shared<T>) -> T {
+    #[cfg(not(feature = "sync"))]
+    return Rc::try_unwrap(value).unwrap_or_else(|v| v.as_ref().clone());
+    #[cfg(feature = "sync")]
+    return Arc::try_unwrap(value).unwrap_or_else(|v| v.as_ref().clone());
+}
+
 pub type FnCallArgs<'a> = [&'a mut Dynamic];
 
+/// Context of a native Rust function call.
+///
+/// It provides access to the calling [`Engine`], the imported modules and the library of
+/// script-defined functions in effect at the call site, as well as the position of the call.
+#[derive(Clone, Copy)]
+pub struct NativeCallContext<'e, 'a, 'm> {
+    engine: &'e Engine,
+    mods: Option<&'a Imports<'a>>,
+    lib: &'m Module,
+    pos: Position,
+    ops: Option<&'a Cell<u64>>,
+    level: usize,
+}
+
+impl<'e, 'm> NativeCallContext<'e, '_, 'm> {
+    /// Create a new `NativeCallContext` with an [`Engine`] and a library of script-defined functions.
+    pub fn new(engine: &'e Engine, lib: &'m Module) -> Self {
+        Self {
+            engine,
+            mods: None,
+            lib,
+            pos: Position::none(),
+            ops: None,
+            level: 0,
+        }
+    }
+}
+
+impl<'e, 'a, 'm> NativeCallContext<'e, 'a, 'm> {
+    /// Create a new `NativeCallContext` with all fields.
+    ///
+    /// Functions called within this context are counted towards `ops`, the number of
+    /// operations performed so far, and run at call stack depth `level`.
+    pub(crate) fn new_with_all_fields(
+        engine: &'e Engine,
+        mods: &'a Imports<'a>,
+        lib: &'m Module,
+        pos: Position,
+        ops: &'a Cell<u64>,
+        level: usize,
+    ) -> Self {
+        Self {
+            engine,
+            mods: Some(mods),
+            lib,
+            pos,
+            ops: Some(ops),
+            level,
+        }
+    }
+    /// The current [`Engine`].
+    pub fn engine(&self) -> &'e Engine {
+        self.engine
+    }
+    /// The modules imported at the call site, if any.
+    pub fn imports(&self) -> Option<&'a Imports<'a>> {
+        self.mods
+    }
+    /// The current library of script-defined functions.
+    pub fn lib(&self) -> &'m Module {
+        self.lib
+    }
+    /// Position of the function call.
+    pub fn position(&self) -> Position {
+        self.pos
+    }
+
+    /// Count one operation towards the maximum number of operations of the calling script.
+    #[allow(dead_code)]
+    pub(crate) fn inc_operations(&self) -> FuncReturn<()> {
+        let mut state = State::new();
+        state.operations = self.ops.map_or(0, Cell::get);
+
+        let result = self.engine.inc_operations(&mut state);
+
+        if let Some(ops) = self.ops {
+            ops.set(state.operations);
+        }
+
+        result.map_err(|err| err.new_position(self.pos))
+    }
+
+    /// Call a function (native Rust or script-defined) within this context.
+    ///
+    /// If `is_method` is `true`, the first argument is passed by reference
+    /// and is bound to `this` in a script-defined function.
+    /// A method call without any argument fails with `ErrorFunctionNotFound`.
+    ///
+    /// ## WARNING
+    ///
+    /// All arguments may be _consumed_, meaning that they may be replaced by `()`.
+    /// This is to avoid unnecessarily cloning the arguments.
+    /// Do not use the arguments after this call. If they are needed afterwards,
+    /// clone them _before_ calling this function.
+    pub fn call_fn_dynamic_raw(
+        &self,
+        fn_name: &str,
+        is_method: bool,
+        args: &mut FnCallArgs,
+    ) -> FuncReturn<Dynamic> {
+        self.call_fn_in_lib(None, fn_name, is_method, args)
+    }
+
+    /// Call a function within this context, searching script-defined functions in `fn_lib`
+    /// (if any) instead of the current library.
+    pub(crate) fn call_fn_in_lib(
+        &self,
+        fn_lib: Option<&Shared<Module>>,
+        fn_name: &str,
+        is_method: bool,
+        args: &mut FnCallArgs,
+    ) -> FuncReturn<Dynamic> {
+        // A method call needs an object to bind to `this`
+        if is_method && args.is_empty() {
+            return Err(Box::new(EvalAltResult::ErrorFunctionNotFound(
+                fn_name.into(),
+                self.pos,
+            )));
+        }
+
+        let empty_mods = Imports::new();
+        let mods = self.mods.unwrap_or(&empty_mods);
+        // Qualifiers (none) + function name + number of arguments (excluding `this`).
+        let num_args = if is_method { args.len() - 1 } else { args.len() };
+        let hash_script = calc_fn_hash(empty(), fn_name, num_args, empty());
+
+        let mut state = State::new();
+        state.lib = fn_lib.cloned();
+        state.operations = self.ops.map_or(0, Cell::get);
+        let lib = fn_lib.map_or(self.lib, |lib| lib.as_ref());
+
+        let result = self
+            .engine
+            .exec_fn_call(
+                mods,
+                &mut state,
+                lib,
+                fn_name,
+                false,
+                hash_script,
+                args,
+                is_method,
+                is_method,
+                None,
+                self.pos,
+                self.level,
+            )
+            .map(|(v, _)| v);
+
+        // Keep the operations count of the caller up to date
+        if let Some(ops) = self.ops {
+            ops.set(state.operations);
+        }
+
+        result
+    }
+}
+
 /// A general function pointer, which may carry additional (i.e. curried) argument values
 /// to be passed onto a function during a call.
+///
+/// A function pointer created by a script also carries a reference to the library of
+/// script-defined functions of that script, so it can be called later without the original `AST`.
 #[derive(Debug, Clone, Default)]
-pub struct FnPtr(ImmutableString, Vec<Dynamic>);
+pub struct FnPtr(ImmutableString, Vec<Dynamic>, Option<Shared<Module>>);
 
 impl FnPtr {
     /// Create a new function pointer.
-    pub(crate) fn new_unchecked<S: Into<ImmutableString>>(name: S, curry: Vec<Dynamic>) -> Self {
-        Self(name.into(), curry)
+    pub(crate) fn new_unchecked<S: Into<ImmutableString>>(
+        name: S,
+        curry: Vec<Dynamic>,
+        lib: Option<Shared<Module>>,
+    ) -> Self {
+        Self(name.into(), curry, lib)
+    }
+    /// Set the library of script-defined functions carried by the function pointer.
+    pub(crate) fn with_lib(mut self, lib: Option<Shared<Module>>) -> Self {
+        self.2 = lib;
+        self
     }
     /// Get the name of the function.
     pub fn fn_name(&self) -> &str {
@@ -71,16 +254,27 @@ impl FnPtr {
         &self.0
     }
     /// Get the underlying data of the function pointer.
-    pub(crate) fn take_data(self) -> (ImmutableString, Vec<Dynamic>) {
-        (self.0, self.1)
+    pub(crate) fn take_data(self) -> (ImmutableString, Vec<Dynamic>, Option<Shared<Module>>) {
+        (self.0, self.1, self.2)
     }
     /// Get the curried arguments.
     pub fn curry(&self) -> &[Dynamic] {
         &self.1
     }
+    /// Get the library of script-defined functions carried by the function pointer, if any.
+    pub fn lib(&self) -> Option<&Module> {
+        self.2.as_ref().map(|lib| lib.as_ref())
+    }
+    /// Get the shared library of script-defined functions carried by the function pointer, if any.
+    pub(crate) fn shared_lib(&self) -> Option<&Shared<Module>> {
+        self.2.as_ref()
+    }
 
     /// Call the function pointer with curried arguments (if any).
     ///
+    /// If the function pointer carries a library of script-defined functions,
+    /// it is used in place of `lib`.
+    ///
     /// ## WARNING
     ///
     /// All the arguments are _consumed_, meaning that they're replaced by `()`.
@@ -94,14 +288,123 @@ impl FnPtr {
         this_ptr: Option<&mut Dynamic>,
         mut arg_values: impl AsMut<[Dynamic]>,
     ) -> FuncReturn<Dynamic> {
-        let args = self
+        let mut args = self
             .1
             .iter()
             .cloned()
             .chain(arg_values.as_mut().iter_mut().map(|v| mem::take(v)))
             .collect::<StaticVec<_>>();
+        let args = args.as_mut();
+
+        let mut this_ptr = this_ptr;
+
+        match &self.2 {
+            Some(fn_lib) => engine.call_fn_dynamic_raw(
+                &mut Scope::new(),
+                fn_lib.as_ref(),
+                Some(fn_lib),
+                self.0.as_str(),
+                &mut this_ptr,
+                args,
+            ),
+            None => engine.call_fn_dynamic_raw(
+                &mut Scope::new(),
+                lib,
+                None,
+                self.0.as_str(),
+                &mut this_ptr,
+                args,
+            ),
+        }
+    }
+
+    /// Call the function pointer with curried arguments (if any), using only an [`Engine`].
+    ///
+    /// Functions are searched in the library of script-defined functions carried by the
+    /// function pointer (if any), then among the functions registered with the [`Engine`].
+    ///
+    /// If `this_ptr` is not `None`, it is bound to `this` in a script-defined function.
+    ///
+    /// ## WARNING
+    ///
+    /// All the arguments are _consumed_, meaning that they're replaced by `()`.
+    /// This is to avoid unnecessarily cloning the arguments.
+    /// Do not use the arguments after this call. If they are needed afterwards,
+    /// clone them _before_ calling this function.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::{Dynamic, Engine, FnPtr, INT};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let f = engine.eval::<FnPtr>(r#"
+    ///             fn double(x) { x * 2 }
+    ///             Fn("double")
+    ///         "#)?;
+    ///
+    /// // The original script is no longer needed
+    /// let result = f.call_with_engine(&engine, None, [Dynamic::from(21 as INT)])?;
+    /// assert_eq!(result.cast::<INT>(), 42);
+    ///
+    /// // Anonymous functions work the same way
+    /// let f = engine.eval::<FnPtr>("|x, y| x + y")?;
+    ///
+    /// let result = f.call_with_engine(&engine, None, [Dynamic::from(40 as INT), Dynamic::from(2 as INT)])?;
+    /// assert_eq!(result.cast::<INT>(), 42);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn call_with_engine(
+        &self,
+        engine: &Engine,
+        this_ptr: Option<&mut Dynamic>,
+        arg_values: impl AsMut<[Dynamic]>,
+    ) -> FuncReturn<Dynamic> {
+        let lib = Module::new();
+        self.call_with_context(&NativeCallContext::new(engine, &lib), this_ptr, arg_values)
+    }
+
+    /// Call the function pointer with curried arguments (if any) from within a native Rust function,
+    /// using the current [`NativeCallContext`].
+    ///
+    /// If the function pointer carries a library of script-defined functions,
+    /// it is used in place of the library in the context.
+    ///
+    /// If `this_ptr` is not `None`, it is bound to `this` in a script-defined function.
+    ///
+    /// ## WARNING
+    ///
+    /// All the arguments are _consumed_, meaning that they're replaced by `()`.
+    /// This is to avoid unnecessarily cloning the arguments.
+    /// Do not use the arguments after this call. If they are needed afterwards,
+    /// clone them _before_ calling this function.
+    pub fn call_with_context(
+        &self,
+        context: &NativeCallContext,
+        this_ptr: Option<&mut Dynamic>,
+        mut arg_values: impl AsMut<[Dynamic]>,
+    ) -> FuncReturn<Dynamic> {
+        let mut args_data = self
+            .1
+            .iter()
+            .cloned()
+            .chain(arg_values.as_mut().iter_mut().map(mem::take))
+            .collect::<StaticVec<_>>();
+
+        let is_method = this_ptr.is_some();
+
+        let mut args = this_ptr
+            .into_iter()
+            .chain(args_data.iter_mut())
+            .collect::<StaticVec<_>>();
 
-        engine.call_fn_dynamic(&mut Scope::new(), lib, self.0.as_str(), this_ptr, args)
+        context.call_fn_in_lib(self.2.as_ref(), self.0.as_str(), is_method, args.as_mut())
     }
 }
 
@@ -116,7 +419,7 @@ impl TryFrom<ImmutableString> for FnPtr {
 
     fn try_from(value: ImmutableString) -> Result<Self, Self::Error> {
         if is_valid_identifier(value.chars()) {
-            Ok(Self(value, Default::default()))
+            Ok(Self(value, Default::default(), None))
         } else {
             Err(Box::new(EvalAltResult::ErrorFunctionNotFound(
                 value.into(),
@@ -146,11 +449,13 @@ impl TryFrom<&str> for FnPtr {
 
 /// A general function trail object.
 #[cfg(not(feature = "sync"))]
-pub type FnAny = dyn Fn(&Engine, &Module, &mut FnCallArgs) -> Result<Dynamic, Box<EvalAltResult>>;
+pub type FnAny =
+    dyn Fn(&NativeCallContext, &mut FnCallArgs) -> Result<Dynamic, Box<EvalAltResult>>;
 /// A general function trail object.
 #[cfg(feature = "sync")]
-pub type FnAny =
-    dyn Fn(&Engine, &Module, &mut FnCallArgs) -> Result<Dynamic, Box<EvalAltResult>> + Send + Sync;
+pub type FnAny = dyn Fn(&NativeCallContext, &mut FnCallArgs) -> Result<Dynamic, Box<EvalAltResult>>
+    + Send
+    + Sync;
 
 /// A standard function that gets an iterator from a type.
 pub type IteratorFn = fn(Dynamic) -> Box<dyn Iterator<Item = Dynamic>>;
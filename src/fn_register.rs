@@ -4,15 +4,15 @@
 
 use crate::any::{Dynamic, Variant};
 use crate::engine::Engine;
-use crate::fn_native::{CallableFunction, FnAny, FnCallArgs, SendSync};
-use crate::module::Module;
+use crate::fn_metadata::FnSignature;
+use crate::fn_native::{CallableFunction, FnAny, FnCallArgs, NativeCallContext, SendSync};
 use crate::parser::FnAccess;
 use crate::r#unsafe::unsafe_cast_box;
 use crate::result::EvalAltResult;
 use crate::utils::ImmutableString;
 
 use crate::stdlib::{
-    any::TypeId,
+    any::{type_name, TypeId},
     boxed::Box,
     mem,
     string::{String, ToString},
@@ -130,7 +130,7 @@ macro_rules! make_func {
 //                           ^ function parameter generic type name (A, B, C etc.)
 //                                           ^ dereferencing function
 
-		Box::new(move |_: &Engine, _: &Module, args: &mut FnCallArgs| {
+		Box::new(move |_: &NativeCallContext, args: &mut FnCallArgs| {
             // The arguments are assumed to be of the correct number and types!
 
 			#[allow(unused_variables, unused_mut)]
@@ -195,9 +195,10 @@ macro_rules! def_register {
         > RegisterFn<FN, ($($mark,)*), RET> for Engine
         {
             fn register_fn(&mut self, name: &str, f: FN) -> &mut Self {
-                self.global_module.set_fn(name, FnAccess::Public,
+                self.global_module.set_fn_with_signature(name, FnAccess::Public,
                     &[$(map_type_id::<$par>()),*],
-                    CallableFunction::$abi(make_func!(f : map_dynamic ; $($par => $clone),*))
+                    CallableFunction::$abi(make_func!(f : map_dynamic ; $($par => $clone),*)),
+                    FnSignature::new(&[$(type_name::<$par>()),*], type_name::<RET>())
                 );
                 self
             }
@@ -209,9 +210,10 @@ macro_rules! def_register {
         > RegisterResultFn<FN, ($($mark,)*)> for Engine
         {
             fn register_result_fn(&mut self, name: &str, f: FN) -> &mut Self {
-                self.global_module.set_fn(name, FnAccess::Public,
+                self.global_module.set_fn_with_signature(name, FnAccess::Public,
                     &[$(map_type_id::<$par>()),*],
-                    CallableFunction::$abi(make_func!(f : map_result ; $($par => $clone),*))
+                    CallableFunction::$abi(make_func!(f : map_result ; $($par => $clone),*)),
+                    FnSignature::new(&[$(type_name::<$par>()),*], type_name::<Dynamic>())
                 );
                 self
             }
@@ -0,0 +1,555 @@
+//! Module implementing a JSON reader and writer for `Dynamic` values.
+
+use crate::any::{Dynamic, Union};
+use crate::engine::{Engine, MAX_EXPR_DEPTH};
+use crate::error::ParseErrorType;
+use crate::parser::INT;
+use crate::result::EvalAltResult;
+use crate::token::Position;
+
+#[cfg(not(feature = "no_float"))]
+use crate::parser::FLOAT;
+
+#[cfg(not(feature = "no_index"))]
+use crate::engine::Array;
+
+#[cfg(not(feature = "no_object"))]
+use crate::engine::Map;
+
+use crate::stdlib::{
+    boxed::Box,
+    char, format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Hard limit on the nesting depth of arrays and object maps in JSON text.
+///
+/// Reading and writing are recursive, so this cap applies even when the `Engine` sets no limit,
+/// in order to avoid a stack overflow.
+const MAX_JSON_DEPTH: usize = 512;
+
+/// Limits on the JSON text being read or written.
+/// Each limit is 0 for unlimited, except that nesting never exceeds `MAX_JSON_DEPTH`.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct JsonLimits {
+    /// Maximum nesting depth of arrays and object maps.
+    pub max_depth: usize,
+    /// Maximum length of strings.
+    pub max_string_size: usize,
+    /// Maximum number of elements in an array.
+    #[cfg(not(feature = "no_index"))]
+    pub max_array_size: usize,
+    /// Maximum number of properties in an object map.
+    #[cfg(not(feature = "no_object"))]
+    pub max_map_size: usize,
+}
+
+impl JsonLimits {
+    /// Default limits, used when there is no `Engine` to take the limits from.
+    pub fn new() -> Self {
+        Self {
+            max_depth: MAX_EXPR_DEPTH,
+            ..Default::default()
+        }
+    }
+    /// Limits set on an `Engine`.
+    #[cfg(not(feature = "unchecked"))]
+    pub fn from_engine(engine: &Engine) -> Self {
+        Self {
+            max_depth: engine.max_expr_depth,
+            max_string_size: engine.max_string_size,
+            #[cfg(not(feature = "no_index"))]
+            max_array_size: engine.max_array_size,
+            #[cfg(not(feature = "no_object"))]
+            max_map_size: engine.max_map_size,
+        }
+    }
+    /// Limits set on an `Engine`.
+    #[cfg(feature = "unchecked")]
+    pub fn from_engine(_engine: &Engine) -> Self {
+        Default::default()
+    }
+    /// Maximum nesting depth actually applied, never more than `MAX_JSON_DEPTH`.
+    fn depth_limit(&self) -> usize {
+        if self.max_depth == 0 || self.max_depth > MAX_JSON_DEPTH {
+            MAX_JSON_DEPTH
+        } else {
+            self.max_depth
+        }
+    }
+}
+
+/// Check a size against a limit.
+fn check_size(
+    desc: &str,
+    max: usize,
+    size: usize,
+    pos: Position,
+) -> Result<(), Box<EvalAltResult>> {
+    if max > 0 && size > max {
+        Err(Box::new(EvalAltResult::ErrorDataTooLarge(
+            desc.to_string(),
+            max,
+            size,
+            pos,
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Encode a `Dynamic` value into JSON text.
+///
+/// `()` is encoded as `null`. Object map properties are written in sorted order.
+/// Floating-point numbers always contain a decimal point or an exponent, so that they are read
+/// back as floating-point numbers; `NaN` and infinities are encoded as `null`.
+pub(crate) fn write_json(
+    value: &Dynamic,
+    limits: JsonLimits,
+) -> Result<String, Box<EvalAltResult>> {
+    let mut output = String::new();
+    write_value(&mut output, value, limits, 0)?;
+    check_size(
+        "Length of string",
+        limits.max_string_size,
+        output.len(),
+        Position::none(),
+    )?;
+    Ok(output)
+}
+
+fn write_value(
+    output: &mut String,
+    value: &Dynamic,
+    limits: JsonLimits,
+    depth: usize,
+) -> Result<(), Box<EvalAltResult>> {
+    if depth > limits.depth_limit() {
+        return Err(Box::new(EvalAltResult::ErrorRuntime(
+            "Value is nested too deeply to convert to JSON".to_string(),
+            Position::none(),
+        )));
+    }
+
+    match &value.0 {
+        Union::Unit(_) => output.push_str("null"),
+        Union::Bool(b) => output.push_str(if *b { "true" } else { "false" }),
+        Union::Int(n) => output.push_str(&n.to_string()),
+        #[cfg(not(feature = "no_float"))]
+        Union::Float(f) if f.is_finite() => {
+            // `Debug` always has a decimal point or an exponent, unlike `Display`
+            output.push_str(&format!("{:?}", f))
+        }
+        #[cfg(not(feature = "no_float"))]
+        Union::Float(_) => output.push_str("null"),
+        Union::Char(ch) => write_string(output, ch.encode_utf8(&mut [0; 4])),
+        Union::Str(s) => write_string(output, s),
+        #[cfg(not(feature = "no_index"))]
+        Union::Array(arr) => {
+            output.push('[');
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_value(output, item, limits, depth + 1)?;
+            }
+            output.push(']');
+        }
+        #[cfg(not(feature = "no_object"))]
+        Union::Map(map) => {
+            let mut props: Vec<_> = map.iter().collect();
+            props.sort_by_key(|&(key, _)| key);
+
+            output.push('{');
+            for (i, (key, item)) in props.into_iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_string(output, key);
+                output.push(':');
+                write_value(output, item, limits, depth + 1)?;
+            }
+            output.push('}');
+        }
+        _ => {
+            return Err(Box::new(EvalAltResult::ErrorRuntime(
+                format!("Cannot convert {} to JSON", value.type_name()),
+                Position::none(),
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+/// Append a string to a JSON buffer as a quoted and escaped JSON string literal.
+pub(crate) fn write_string(output: &mut String, s: &str) {
+    output.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{08}' => output.push_str("\\b"),
+            '\u{0c}' => output.push_str("\\f"),
+            ch if (ch as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => output.push(ch),
+        }
+    }
+    output.push('"');
+}
+
+/// Decode JSON text into a `Dynamic` value.
+///
+/// `null` is decoded as `()`. Numbers without a decimal point or an exponent are decoded as
+/// integers if they fit into `INT`, otherwise as floating-point numbers.
+pub(crate) fn read_json(json: &str, limits: JsonLimits) -> Result<Dynamic, Box<EvalAltResult>> {
+    let mut reader = JsonReader {
+        json,
+        index: 0,
+        line: 1,
+        col: 1,
+        limits,
+    };
+
+    let value = reader.read_value(0)?;
+    reader.skip_whitespace();
+
+    if reader.index < json.len() {
+        return Err(reader.err_bad_input("Unexpected text after the JSON value"));
+    }
+
+    Ok(value)
+}
+
+struct JsonReader<'a> {
+    json: &'a str,
+    index: usize,
+    line: usize,
+    col: usize,
+    limits: JsonLimits,
+}
+
+impl JsonReader<'_> {
+    fn position(&self) -> Position {
+        let clamp = |n: usize| {
+            if n > u16::MAX as usize {
+                u16::MAX
+            } else {
+                n as u16
+            }
+        };
+        Position::new(clamp(self.line), clamp(self.col))
+    }
+    fn err(&self, err: ParseErrorType) -> Box<EvalAltResult> {
+        Box::new(EvalAltResult::ErrorParsing(err, self.position()))
+    }
+    fn err_bad_input(&self, msg: &str) -> Box<EvalAltResult> {
+        self.err(ParseErrorType::BadInput(msg.to_string()))
+    }
+    fn peek(&self) -> Option<char> {
+        self.json[self.index..].chars().next()
+    }
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.index += ch.len_utf8();
+
+        if ch == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(ch)
+    }
+    fn skip_whitespace(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.peek() {
+            self.next();
+        }
+    }
+    fn expect(&mut self, text: &str) -> Result<(), Box<EvalAltResult>> {
+        if self.json[self.index..].starts_with(text) {
+            text.chars().for_each(|_| {
+                self.next();
+            });
+            Ok(())
+        } else {
+            Err(self.err_bad_input(&format!("Expecting '{}'", text)))
+        }
+    }
+
+    fn read_value(&mut self, depth: usize) -> Result<Dynamic, Box<EvalAltResult>> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            None => Err(self.err(ParseErrorType::UnexpectedEOF)),
+            Some('n') => self.expect("null").map(|_| ().into()),
+            Some('t') => self.expect("true").map(|_| true.into()),
+            Some('f') => self.expect("false").map(|_| false.into()),
+            Some('"') => self.read_string().map(Into::into),
+            Some('-') | Some('0'..='9') => self.read_number(),
+            Some('[') | Some('{') if depth >= self.limits.depth_limit() => {
+                Err(self.err(ParseErrorType::ExprTooDeep))
+            }
+            #[cfg(not(feature = "no_index"))]
+            Some('[') => self.read_array(depth),
+            #[cfg(not(feature = "no_object"))]
+            Some('{') => self.read_map(depth),
+            Some(ch) => Err(self.err_bad_input(&format!("Unexpected character '{}'", ch))),
+        }
+    }
+
+    fn read_string(&mut self) -> Result<String, Box<EvalAltResult>> {
+        let pos = self.position();
+        let mut result = String::new();
+
+        self.next();
+
+        loop {
+            match self.next() {
+                None => return Err(self.err(ParseErrorType::UnexpectedEOF)),
+                Some('"') => break,
+                Some('\\') => {
+                    let ch = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{08}',
+                        Some('f') => '\u{0c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.read_unicode_escape()?,
+                        _ => return Err(self.err_bad_input("Invalid escape sequence in string")),
+                    };
+                    result.push(ch);
+                }
+                Some(ch) if (ch as u32) < 0x20 => {
+                    return Err(self.err_bad_input("Control character in string"))
+                }
+                Some(ch) => result.push(ch),
+            }
+
+            check_size(
+                "Length of string",
+                self.limits.max_string_size,
+                result.len(),
+                pos,
+            )?;
+        }
+
+        Ok(result)
+    }
+
+    fn read_hex4(&mut self) -> Result<u32, Box<EvalAltResult>> {
+        let mut value = 0;
+
+        for _ in 0..4 {
+            let digit = self
+                .next()
+                .and_then(|ch| ch.to_digit(16))
+                .ok_or_else(|| self.err_bad_input("Invalid unicode escape sequence in string"))?;
+            value = value * 16 + digit;
+        }
+        Ok(value)
+    }
+
+    fn read_unicode_escape(&mut self) -> Result<char, Box<EvalAltResult>> {
+        let high = self.read_hex4()?;
+
+        let code = if (0xD800..0xDC00).contains(&high) {
+            // Surrogate pair
+            self.expect("\\u")?;
+            let low = self.read_hex4()?;
+
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.err_bad_input("Invalid surrogate pair in string"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code)
+            .ok_or_else(|| self.err_bad_input("Invalid unicode escape sequence in string"))
+    }
+
+    fn read_number(&mut self) -> Result<Dynamic, Box<EvalAltResult>> {
+        let pos = self.position();
+        let start = self.index;
+        let mut is_float = false;
+
+        if self.peek() == Some('-') {
+            self.next();
+        }
+        while let Some(ch) = self.peek() {
+            match ch {
+                '0'..='9' => (),
+                '.' | 'e' | 'E' | '+' | '-' => is_float = true,
+                _ => break,
+            }
+            self.next();
+        }
+
+        let text = &self.json[start..self.index];
+        let err = || {
+            Box::new(EvalAltResult::ErrorParsing(
+                ParseErrorType::BadInput(format!("Invalid number: {}", text)),
+                pos,
+            ))
+        };
+
+        if !is_valid_number(text) {
+            return Err(err());
+        }
+
+        if !is_float {
+            if let Ok(n) = text.parse::<INT>() {
+                return Ok(n.into());
+            }
+        }
+
+        #[cfg(not(feature = "no_float"))]
+        return text.parse::<FLOAT>().map(Into::into).map_err(|_| err());
+
+        #[cfg(feature = "no_float")]
+        return Err(err());
+    }
+
+    #[cfg(not(feature = "no_index"))]
+    fn read_array(&mut self, depth: usize) -> Result<Dynamic, Box<EvalAltResult>> {
+        let pos = self.position();
+        let mut result = Array::new();
+
+        self.next();
+        self.skip_whitespace();
+
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(result.into());
+        }
+
+        loop {
+            result.push(self.read_value(depth + 1)?);
+            check_size(
+                "Size of array",
+                self.limits.max_array_size,
+                result.len(),
+                pos,
+            )?;
+
+            self.skip_whitespace();
+
+            match self.next() {
+                Some(',') => (),
+                Some(']') => break,
+                None => return Err(self.err(ParseErrorType::UnexpectedEOF)),
+                Some(_) => return Err(self.err_bad_input("Expecting ',' or ']' in array")),
+            }
+        }
+
+        Ok(result.into())
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    fn read_map(&mut self, depth: usize) -> Result<Dynamic, Box<EvalAltResult>> {
+        let pos = self.position();
+        let mut result = Map::new();
+
+        self.next();
+        self.skip_whitespace();
+
+        if self.peek() == Some('}') {
+            self.next();
+            return Ok(result.into());
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            if self.peek() != Some('"') {
+                return Err(self.err_bad_input("Expecting a property name in object"));
+            }
+
+            let key = self.read_string()?;
+
+            self.skip_whitespace();
+            self.expect(":")?;
+
+            let value = self.read_value(depth + 1)?;
+            result.insert(key.into(), value);
+            check_size(
+                "Number of properties in object map",
+                self.limits.max_map_size,
+                result.len(),
+                pos,
+            )?;
+
+            self.skip_whitespace();
+
+            match self.next() {
+                Some(',') => (),
+                Some('}') => break,
+                None => return Err(self.err(ParseErrorType::UnexpectedEOF)),
+                Some(_) => return Err(self.err_bad_input("Expecting ',' or '}' in object")),
+            }
+        }
+
+        Ok(result.into())
+    }
+}
+
+/// Check that a number follows the JSON grammar:
+/// an optional minus sign, an integer part without leading zeros,
+/// an optional fraction and an optional exponent, each with at least one digit.
+fn is_valid_number(text: &str) -> bool {
+    fn skip_digits(bytes: &[u8], mut i: usize) -> Option<usize> {
+        let start = i;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i > start {
+            Some(i)
+        } else {
+            None
+        }
+    }
+
+    let bytes = text.as_bytes();
+    let mut i = 0;
+
+    if bytes.get(i) == Some(&b'-') {
+        i += 1;
+    }
+
+    i = match bytes.get(i) {
+        Some(b'0') => i + 1,
+        _ => match skip_digits(bytes, i) {
+            Some(i) => i,
+            None => return false,
+        },
+    };
+
+    if bytes.get(i) == Some(&b'.') {
+        i = match skip_digits(bytes, i + 1) {
+            Some(i) => i,
+            None => return false,
+        };
+    }
+
+    if let Some(b'e') | Some(b'E') = bytes.get(i) {
+        i += 1;
+        if let Some(b'+') | Some(b'-') = bytes.get(i) {
+            i += 1;
+        }
+        i = match skip_digits(bytes, i) {
+            Some(i) => i,
+            None => return false,
+        };
+    }
+
+    i == bytes.len()
+}
@@ -65,6 +65,7 @@
 //! | `sync`        | Restrict all values types to those that are `Send + Sync`. Under this feature, `Engine`, `Scope` and `AST` are all `Send + Sync`. |
 //! | `serde`       | Enable serialization/deserialization via `serde`. Notice that the [`serde`](https://crates.io/crates/serde) crate will be pulled in together with its dependencies. |
 //! | `internals`   | Expose internal data structures (beware they may be volatile from version to version).                                            |
+//! | `plugins`     | Enable procedural macros (e.g. `#[export_module]`) to export Rust modules as Rhai modules.                                        |
 //!
 //! See [The Rhai Book](https://schungx.github.io/rhai) for details on the Rhai script engine and language.
 
@@ -75,17 +76,26 @@ extern crate alloc;
 
 mod any;
 mod api;
+mod clock;
+mod coverage;
+mod datetime;
 mod engine;
 mod error;
 mod fn_args;
 mod fn_call;
 mod fn_func;
+mod fn_metadata;
 mod fn_native;
 mod fn_register;
+mod json;
 mod module;
 mod optimize;
 pub mod packages;
 mod parser;
+#[cfg(feature = "plugins")]
+#[cfg(not(feature = "no_object"))]
+mod plugin;
+mod profiler;
 mod result;
 mod scope;
 #[cfg(feature = "serde")]
@@ -98,9 +108,11 @@ mod r#unsafe;
 mod utils;
 
 pub use any::Dynamic;
+pub use datetime::{DateTime, Duration};
 pub use engine::Engine;
 pub use error::{ParseError, ParseErrorType};
-pub use fn_native::{FnPtr, IteratorFn};
+pub use fn_metadata::FnMetadata;
+pub use fn_native::{FnPtr, IteratorFn, NativeCallContext};
 pub use fn_register::{RegisterFn, RegisterResultFn};
 pub use module::Module;
 pub use parser::{ImmutableString, AST, INT};
@@ -110,6 +122,12 @@ pub use syntax::{EvalContext, Expression};
 pub use token::Position;
 pub use utils::calc_fn_spec as calc_fn_hash;
 
+#[cfg(not(feature = "no_std"))]
+pub use coverage::{Coverage, SourceCoverage, BRANCH_NOT_TAKEN, BRANCH_TAKEN};
+
+#[cfg(not(feature = "no_std"))]
+pub use profiler::{FnProfile, LineProfile, Profile, ProfileMetric};
+
 #[cfg(not(feature = "no_function"))]
 pub use parser::FnAccess;
 
@@ -154,6 +172,23 @@ pub mod de {
 #[cfg(not(feature = "no_optimize"))]
 pub use optimize::OptimizationLevel;
 
+/// Procedural macros to export Rust modules as Rhai [`Module`]s and Rust types as custom types.
+///
+/// Requires the `plugins` feature.
+#[cfg(feature = "plugins")]
+pub mod plugins {
+    pub use rhai_codegen::{combine_with_exported_module, export_module, exported_module};
+
+    #[cfg(not(feature = "no_object"))]
+    pub use crate::plugin::RhaiType;
+    #[cfg(not(feature = "no_object"))]
+    pub use rhai_codegen::RhaiType;
+
+    #[cfg(not(feature = "no_object"))]
+    #[doc(hidden)]
+    pub use crate::plugin::private as __private;
+}
+
 // Expose internal data structures.
 
 #[cfg(feature = "internals")]
@@ -170,7 +205,7 @@ pub use parser::{CustomExpr, Expr, ReturnType, ScriptFnDef, Stmt};
 
 #[cfg(feature = "internals")]
 #[deprecated(note = "this type is volatile and may change")]
-pub use engine::{Imports, State as EvalState};
+pub use engine::{ImportedModule, Imports, State as EvalState};
 
 #[cfg(feature = "internals")]
 #[deprecated(note = "this type is volatile and may change")]
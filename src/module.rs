@@ -1,13 +1,18 @@
 //! Module defining external-loaded modules for Rhai.
 
-use crate::any::{Dynamic, Variant};
+use crate::any::{std_type_name_of, Dynamic, Variant};
 use crate::calc_fn_hash;
+#[cfg(not(feature = "no_module"))]
+use crate::engine::ImportedModule;
 use crate::engine::{make_getter, make_setter, Engine, Imports, FN_IDX_GET, FN_IDX_SET};
-use crate::fn_native::{CallableFunction as Func, FnCallArgs, IteratorFn, SendSync, Shared};
+use crate::fn_metadata::FnSignature;
+use crate::fn_native::{
+    CallableFunction as Func, FnCallArgs, IteratorFn, NativeCallContext, SendSync, Shared,
+};
 use crate::parser::{
     FnAccess,
     FnAccess::{Private, Public},
-    ScriptFnDef, AST,
+    ImmutableString, ScriptFnDef, AST,
 };
 use crate::result::EvalAltResult;
 use crate::scope::{Entry as ScopeEntry, Scope};
@@ -15,27 +20,54 @@ use crate::token::{Position, Token};
 use crate::utils::{StaticVec, StraightHasherBuilder};
 
 use crate::stdlib::{
-    any::TypeId,
+    any::{type_name, TypeId},
     boxed::Box,
     cell::RefCell,
     collections::HashMap,
     fmt, format,
-    iter::empty,
+    iter::{empty, once},
     mem,
     num::NonZeroUsize,
     ops::{Deref, DerefMut},
     string::{String, ToString},
+    sync::Arc,
     vec,
     vec::Vec,
 };
 
+#[cfg(not(feature = "no_module"))]
+use crate::fn_native::shared_take_or_clone;
+
+#[cfg(not(feature = "no_module"))]
+use crate::parser::Stmt;
+
+#[cfg(not(feature = "no_module"))]
+use crate::stdlib::collections::HashSet;
+
+#[cfg(not(feature = "sync"))]
+use crate::stdlib::rc::Rc;
+
 #[cfg(not(feature = "no_std"))]
 #[cfg(feature = "sync")]
 use crate::stdlib::sync::RwLock;
 
+/// Exported variables and imported modules of a script-defined module,
+/// visible to the script-defined functions in it when called via a module-qualified name.
+#[cfg(not(feature = "no_module"))]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ModuleEnv {
+    /// Exported variables.
+    pub variables: Vec<(String, Dynamic)>,
+    /// Imported modules, which may not be resolved yet.
+    pub modules: Vec<(String, ImportedModule)>,
+}
+
 /// Return type of module-level Rust function.
 pub type FuncReturn<T> = Result<T, Box<EvalAltResult>>;
 
+/// A registered function: name, access mode, parameter types, function and signature.
+type FuncInfo = (String, FnAccess, StaticVec<TypeId>, Func, FnSignature);
+
 /// An imported module, which may contain variables, sub-modules,
 /// external Rust functions, and script-defined functions.
 ///
@@ -52,7 +84,7 @@ pub struct Module {
     all_variables: HashMap<u64, Dynamic, StraightHasherBuilder>,
 
     /// External Rust functions.
-    functions: HashMap<u64, (String, FnAccess, StaticVec<TypeId>, Func), StraightHasherBuilder>,
+    functions: HashMap<u64, FuncInfo, StraightHasherBuilder>,
 
     /// Iterator functions, keyed by the type producing the iterator.
     type_iterators: HashMap<TypeId, IteratorFn>,
@@ -82,7 +114,7 @@ impl fmt::Debug for Module {
                 .join(", "),
             self.functions
                 .values()
-                .map(|(_, _, _, f)| f.to_string())
+                .map(|(_, _, _, f, _)| f.to_string())
                 .collect::<Vec<_>>()
                 .join(", "),
         )
@@ -223,6 +255,13 @@ impl Module {
         self
     }
 
+    /// Get a reference to a modules-qualified variable.
+    ///
+    /// The `u64` hash is calculated by the function `crate::calc_fn_hash`.
+    pub(crate) fn get_qualified_var(&self, hash_var: u64) -> Option<&Dynamic> {
+        self.all_variables.get(&hash_var)
+    }
+
     /// Get a mutable reference to a modules-qualified variable.
     /// Name and Position in `EvalAltResult` are None and must be set afterwards.
     ///
@@ -253,6 +292,7 @@ impl Module {
                 fn_def.access,
                 Default::default(),
                 fn_def.into(),
+                Default::default(),
             ),
         );
         self.indexed = false;
@@ -358,6 +398,22 @@ impl Module {
         access: FnAccess,
         arg_types: &[TypeId],
         func: Func,
+    ) -> u64 {
+        let param_types: StaticVec<_> = arg_types.iter().cloned().map(std_type_name_of).collect();
+        let signature = FnSignature::new(&param_types, "");
+        self.set_fn_with_signature(name, access, arg_types, func, signature)
+    }
+
+    /// Set a Rust function into the module together with its signature, returning a hash key.
+    ///
+    /// If there is an existing Rust function of the same hash, it is replaced.
+    pub(crate) fn set_fn_with_signature(
+        &mut self,
+        name: impl Into<String>,
+        access: FnAccess,
+        arg_types: &[TypeId],
+        func: Func,
+        signature: FnSignature,
     ) -> u64 {
         let name = name.into();
 
@@ -366,14 +422,73 @@ impl Module {
         let params = arg_types.into_iter().cloned().collect();
 
         self.functions
-            .insert(hash_fn, (name, access, params, func.into()));
+            .insert(hash_fn, (name, access, params, func.into(), signature));
 
         self.indexed = false;
 
         hash_fn
     }
 
-    /// Set a Rust function taking a reference to the scripting `Engine`, the current set of functions,
+    /// Set the names of the parameters of a function in the module, for use in metadata.
+    ///
+    /// The `u64` hash is calculated by the function `crate::calc_fn_hash`.
+    /// It is also returned by the `set_fn_XXX` calls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rhai::Module;
+    ///
+    /// let mut module = Module::new();
+    /// let hash = module.set_fn_2("add", |x: i64, y: i64| Ok(x + y));
+    /// module.set_fn_param_names(hash, &["x", "y"]);
+    /// ```
+    pub fn set_fn_param_names(&mut self, hash_fn: u64, param_names: &[&str]) -> &mut Self {
+        if let Some((_, _, _, _, sig)) = self.functions.get_mut(&hash_fn) {
+            sig.param_names = param_names.iter().map(|&s| s.into()).collect();
+        }
+        self
+    }
+
+    /// Set the documentation of a function in the module, for use in metadata.
+    ///
+    /// The `u64` hash is calculated by the function `crate::calc_fn_hash`.
+    /// It is also returned by the `set_fn_XXX` calls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rhai::Module;
+    ///
+    /// let mut module = Module::new();
+    /// let hash = module.set_fn_2("add", |x: i64, y: i64| Ok(x + y));
+    /// module.set_fn_doc(hash, "Add two numbers together.");
+    /// ```
+    pub fn set_fn_doc(&mut self, hash_fn: u64, doc: impl Into<String>) -> &mut Self {
+        if let Some((_, _, _, _, sig)) = self.functions.get_mut(&hash_fn) {
+            sig.doc = Some(doc.into());
+        }
+        self
+    }
+
+    /// Set the parameter names and documentation of all functions in the module
+    /// with a particular name and number of parameters.
+    pub(crate) fn describe_fn(&mut self, name: &str, param_names: &[&str], doc: &str) {
+        self.functions
+            .values_mut()
+            .filter(|(n, _, params, _, _)| n == name && params.len() == param_names.len())
+            .for_each(|(_, _, _, _, sig)| {
+                sig.param_names = param_names.iter().map(|&s| s.into()).collect();
+                sig.doc = if doc.is_empty() {
+                    None
+                } else {
+                    Some(doc.into())
+                };
+            });
+    }
+
+    /// Set a Rust function taking a [`NativeCallContext`] (which provides access to the scripting
+    /// `Engine`, the current set of functions and imported modules, and the position of the call),
     /// plus a list of mutable `Dynamic` references into the module, returning a hash key.
     ///
     /// Use this to register a built-in function which must reference settings on the scripting
@@ -408,7 +523,7 @@ impl Module {
     ///                 // Pass parameter types via a slice with TypeId's
     ///                 &[std::any::TypeId::of::<i64>(), std::any::TypeId::of::<bool>() ],
     ///                 // Fixed closure signature
-    ///                 |engine, lib, args| {
+    ///                 |context, args| {
     ///                     // 'args' is guaranteed to be the right length and of the correct types
     ///
     ///                     // Get the second parameter by 'consuming' it
@@ -433,12 +548,20 @@ impl Module {
         &mut self,
         name: impl Into<String>,
         arg_types: &[TypeId],
-        func: impl Fn(&Engine, &Module, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
+        func: impl Fn(&NativeCallContext, &mut [&mut Dynamic]) -> FuncReturn<T> + SendSync + 'static,
     ) -> u64 {
-        let f = move |engine: &Engine, lib: &Module, args: &mut FnCallArgs| {
-            func(engine, lib, args).map(Dynamic::from)
+        let f = move |context: &NativeCallContext, args: &mut FnCallArgs| {
+            func(context, args).map(Dynamic::from)
         };
-        self.set_fn(name, Public, arg_types, Func::from_method(Box::new(f)))
+        let param_types: StaticVec<_> = arg_types.iter().cloned().map(std_type_name_of).collect();
+        let signature = FnSignature::new(&param_types, type_name::<T>());
+        self.set_fn_with_signature(
+            name,
+            Public,
+            arg_types,
+            Func::from_method(Box::new(f)),
+            signature,
+        )
     }
 
     /// Set a Rust function taking no parameters into the module, returning a hash key.
@@ -459,9 +582,16 @@ impl Module {
         name: impl Into<String>,
         func: impl Fn() -> FuncReturn<T> + SendSync + 'static,
     ) -> u64 {
-        let f = move |_: &Engine, _: &Module, _: &mut FnCallArgs| func().map(Dynamic::from);
+        let f = move |_: &NativeCallContext, _: &mut FnCallArgs| func().map(Dynamic::from);
         let arg_types = [];
-        self.set_fn(name, Public, &arg_types, Func::from_pure(Box::new(f)))
+        let signature = FnSignature::new(&[], type_name::<T>());
+        self.set_fn_with_signature(
+            name,
+            Public,
+            &arg_types,
+            Func::from_pure(Box::new(f)),
+            signature,
+        )
     }
 
     /// Set a Rust function taking one parameter into the module, returning a hash key.
@@ -482,11 +612,18 @@ impl Module {
         name: impl Into<String>,
         func: impl Fn(A) -> FuncReturn<T> + SendSync + 'static,
     ) -> u64 {
-        let f = move |_: &Engine, _: &Module, args: &mut FnCallArgs| {
+        let f = move |_: &NativeCallContext, args: &mut FnCallArgs| {
             func(mem::take(args[0]).cast::<A>()).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>()];
-        self.set_fn(name, Public, &arg_types, Func::from_pure(Box::new(f)))
+        let signature = FnSignature::new(&[type_name::<A>()], type_name::<T>());
+        self.set_fn_with_signature(
+            name,
+            Public,
+            &arg_types,
+            Func::from_pure(Box::new(f)),
+            signature,
+        )
     }
 
     /// Set a Rust function taking one mutable parameter into the module, returning a hash key.
@@ -507,11 +644,18 @@ impl Module {
         name: impl Into<String>,
         func: impl Fn(&mut A) -> FuncReturn<T> + SendSync + 'static,
     ) -> u64 {
-        let f = move |_: &Engine, _: &Module, args: &mut FnCallArgs| {
+        let f = move |_: &NativeCallContext, args: &mut FnCallArgs| {
             func(args[0].downcast_mut::<A>().unwrap()).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>()];
-        self.set_fn(name, Public, &arg_types, Func::from_method(Box::new(f)))
+        let signature = FnSignature::new(&[type_name::<A>()], type_name::<T>());
+        self.set_fn_with_signature(
+            name,
+            Public,
+            &arg_types,
+            Func::from_method(Box::new(f)),
+            signature,
+        )
     }
 
     /// Set a Rust getter function taking one mutable parameter, returning a hash key.
@@ -556,14 +700,21 @@ impl Module {
         name: impl Into<String>,
         func: impl Fn(A, B) -> FuncReturn<T> + SendSync + 'static,
     ) -> u64 {
-        let f = move |_: &Engine, _: &Module, args: &mut FnCallArgs| {
+        let f = move |_: &NativeCallContext, args: &mut FnCallArgs| {
             let a = mem::take(args[0]).cast::<A>();
             let b = mem::take(args[1]).cast::<B>();
 
             func(a, b).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>(), TypeId::of::<B>()];
-        self.set_fn(name, Public, &arg_types, Func::from_pure(Box::new(f)))
+        let signature = FnSignature::new(&[type_name::<A>(), type_name::<B>()], type_name::<T>());
+        self.set_fn_with_signature(
+            name,
+            Public,
+            &arg_types,
+            Func::from_pure(Box::new(f)),
+            signature,
+        )
     }
 
     /// Set a Rust function taking two parameters (the first one mutable) into the module,
@@ -587,14 +738,21 @@ impl Module {
         name: impl Into<String>,
         func: impl Fn(&mut A, B) -> FuncReturn<T> + SendSync + 'static,
     ) -> u64 {
-        let f = move |_: &Engine, _: &Module, args: &mut FnCallArgs| {
+        let f = move |_: &NativeCallContext, args: &mut FnCallArgs| {
             let b = mem::take(args[1]).cast::<B>();
             let a = args[0].downcast_mut::<A>().unwrap();
 
             func(a, b).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>(), TypeId::of::<B>()];
-        self.set_fn(name, Public, &arg_types, Func::from_method(Box::new(f)))
+        let signature = FnSignature::new(&[type_name::<A>(), type_name::<B>()], type_name::<T>());
+        self.set_fn_with_signature(
+            name,
+            Public,
+            &arg_types,
+            Func::from_method(Box::new(f)),
+            signature,
+        )
     }
 
     /// Set a Rust setter function taking two parameters (the first one mutable) into the module,
@@ -673,7 +831,7 @@ impl Module {
         name: impl Into<String>,
         func: impl Fn(A, B, C) -> FuncReturn<T> + SendSync + 'static,
     ) -> u64 {
-        let f = move |_: &Engine, _: &Module, args: &mut FnCallArgs| {
+        let f = move |_: &NativeCallContext, args: &mut FnCallArgs| {
             let a = mem::take(args[0]).cast::<A>();
             let b = mem::take(args[1]).cast::<B>();
             let c = mem::take(args[2]).cast::<C>();
@@ -681,7 +839,17 @@ impl Module {
             func(a, b, c).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>(), TypeId::of::<B>(), TypeId::of::<C>()];
-        self.set_fn(name, Public, &arg_types, Func::from_pure(Box::new(f)))
+        let signature = FnSignature::new(
+            &[type_name::<A>(), type_name::<B>(), type_name::<C>()],
+            type_name::<T>(),
+        );
+        self.set_fn_with_signature(
+            name,
+            Public,
+            &arg_types,
+            Func::from_pure(Box::new(f)),
+            signature,
+        )
     }
 
     /// Set a Rust function taking three parameters (the first one mutable) into the module,
@@ -710,7 +878,7 @@ impl Module {
         name: impl Into<String>,
         func: impl Fn(&mut A, B, C) -> FuncReturn<T> + SendSync + 'static,
     ) -> u64 {
-        let f = move |_: &Engine, _: &Module, args: &mut FnCallArgs| {
+        let f = move |_: &NativeCallContext, args: &mut FnCallArgs| {
             let b = mem::take(args[1]).cast::<B>();
             let c = mem::take(args[2]).cast::<C>();
             let a = args[0].downcast_mut::<A>().unwrap();
@@ -718,7 +886,17 @@ impl Module {
             func(a, b, c).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>(), TypeId::of::<B>(), TypeId::of::<C>()];
-        self.set_fn(name, Public, &arg_types, Func::from_method(Box::new(f)))
+        let signature = FnSignature::new(
+            &[type_name::<A>(), type_name::<B>(), type_name::<C>()],
+            type_name::<T>(),
+        );
+        self.set_fn_with_signature(
+            name,
+            Public,
+            &arg_types,
+            Func::from_method(Box::new(f)),
+            signature,
+        )
     }
 
     /// Set a Rust index setter taking three parameters (the first one mutable) into the module,
@@ -742,7 +920,7 @@ impl Module {
         &mut self,
         func: impl Fn(&mut A, B, C) -> FuncReturn<()> + SendSync + 'static,
     ) -> u64 {
-        let f = move |_: &Engine, _: &Module, args: &mut FnCallArgs| {
+        let f = move |_: &NativeCallContext, args: &mut FnCallArgs| {
             let b = mem::take(args[1]).cast::<B>();
             let c = mem::take(args[2]).cast::<C>();
             let a = args[0].downcast_mut::<A>().unwrap();
@@ -750,11 +928,16 @@ impl Module {
             func(a, b, c).map(Dynamic::from)
         };
         let arg_types = [TypeId::of::<A>(), TypeId::of::<B>(), TypeId::of::<C>()];
-        self.set_fn(
+        let signature = FnSignature::new(
+            &[type_name::<A>(), type_name::<B>(), type_name::<C>()],
+            type_name::<()>(),
+        );
+        self.set_fn_with_signature(
             FN_IDX_SET,
             Public,
             &arg_types,
             Func::from_method(Box::new(f)),
+            signature,
         )
     }
 
@@ -784,7 +967,7 @@ impl Module {
         name: impl Into<String>,
         func: impl Fn(A, B, C, D) -> FuncReturn<T> + SendSync + 'static,
     ) -> u64 {
-        let f = move |_: &Engine, _: &Module, args: &mut FnCallArgs| {
+        let f = move |_: &NativeCallContext, args: &mut FnCallArgs| {
             let a = mem::take(args[0]).cast::<A>();
             let b = mem::take(args[1]).cast::<B>();
             let c = mem::take(args[2]).cast::<C>();
@@ -798,7 +981,22 @@ impl Module {
             TypeId::of::<C>(),
             TypeId::of::<D>(),
         ];
-        self.set_fn(name, Public, &arg_types, Func::from_pure(Box::new(f)))
+        let signature = FnSignature::new(
+            &[
+                type_name::<A>(),
+                type_name::<B>(),
+                type_name::<C>(),
+                type_name::<D>(),
+            ],
+            type_name::<T>(),
+        );
+        self.set_fn_with_signature(
+            name,
+            Public,
+            &arg_types,
+            Func::from_pure(Box::new(f)),
+            signature,
+        )
     }
 
     /// Set a Rust function taking four parameters (the first one mutable) into the module,
@@ -828,7 +1026,7 @@ impl Module {
         name: impl Into<String>,
         func: impl Fn(&mut A, B, C, D) -> FuncReturn<T> + SendSync + 'static,
     ) -> u64 {
-        let f = move |_: &Engine, _: &Module, args: &mut FnCallArgs| {
+        let f = move |_: &NativeCallContext, args: &mut FnCallArgs| {
             let b = mem::take(args[1]).cast::<B>();
             let c = mem::take(args[2]).cast::<C>();
             let d = mem::take(args[3]).cast::<D>();
@@ -840,9 +1038,24 @@ impl Module {
             TypeId::of::<A>(),
             TypeId::of::<B>(),
             TypeId::of::<C>(),
-            TypeId::of::<C>(),
+            TypeId::of::<D>(),
         ];
-        self.set_fn(name, Public, &arg_types, Func::from_method(Box::new(f)))
+        let signature = FnSignature::new(
+            &[
+                type_name::<A>(),
+                type_name::<B>(),
+                type_name::<C>(),
+                type_name::<D>(),
+            ],
+            type_name::<T>(),
+        );
+        self.set_fn_with_signature(
+            name,
+            Public,
+            &arg_types,
+            Func::from_method(Box::new(f)),
+            signature,
+        )
     }
 
     /// Get a Rust function.
@@ -850,7 +1063,7 @@ impl Module {
     /// The `u64` hash is calculated by the function `crate::calc_fn_hash`.
     /// It is also returned by the `set_fn_XXX` calls.
     pub(crate) fn get_fn(&self, hash_fn: u64) -> Option<&Func> {
-        self.functions.get(&hash_fn).map(|(_, _, _, v)| v)
+        self.functions.get(&hash_fn).map(|(_, _, _, v, _)| v)
     }
 
     /// Get a modules-qualified function.
@@ -888,7 +1101,7 @@ impl Module {
             other
                 .functions
                 .iter()
-                .filter(|(_, (_, _, _, v))| match v {
+                .filter(|(_, (_, _, _, v, _))| match v {
                     #[cfg(not(feature = "no_function"))]
                     Func::Script(ref f) => filter(f.access, f.name.as_str(), f.params.len()),
                     _ => true,
@@ -905,13 +1118,42 @@ impl Module {
         self
     }
 
+    /// Set the source of all script-defined functions in the module.
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) fn set_script_fn_source(&mut self, source: &ImmutableString) -> &mut Self {
+        self.functions.values_mut().for_each(|(_, _, _, f, _)| {
+            if let Func::Script(ref mut f) = f {
+                Shared::make_mut(f).source = Some(source.clone());
+            }
+        });
+
+        self.all_functions.clear();
+        self.indexed = false;
+        self
+    }
+
+    /// Set the environment of all script-defined functions in the module.
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_module"))]
+    fn set_script_fn_env(&mut self, env: &Shared<ModuleEnv>) -> &mut Self {
+        self.functions.values_mut().for_each(|(_, _, _, f, _)| {
+            if let Func::Script(ref mut f) = f {
+                Shared::make_mut(f).env = Some(env.clone());
+            }
+        });
+
+        self.all_functions.clear();
+        self.indexed = false;
+        self
+    }
+
     /// Filter out the functions, retaining only some based on a filter predicate.
     #[cfg(not(feature = "no_function"))]
     pub(crate) fn retain_functions(
         &mut self,
         filter: impl Fn(FnAccess, &str, usize) -> bool,
     ) -> &mut Self {
-        self.functions.retain(|_, (_, _, _, v)| match v {
+        self.functions.retain(|_, (_, _, _, v, _)| match v {
             Func::Script(ref f) => filter(f.access, f.name.as_str(), f.params.len()),
             _ => true,
         });
@@ -941,24 +1183,30 @@ impl Module {
     }
 
     /// Get an iterator to the functions in the module.
-    pub(crate) fn iter_fn(
-        &self,
-    ) -> impl Iterator<Item = &(String, FnAccess, StaticVec<TypeId>, Func)> {
+    pub(crate) fn iter_fn(&self) -> impl Iterator<Item = &FuncInfo> {
         self.functions.values()
     }
 
+    /// Get an iterator to the sub-modules in the module.
+    pub(crate) fn iter_sub_modules(&self) -> impl Iterator<Item = (&String, &Module)> {
+        self.modules.iter()
+    }
+
     /// Get an iterator over all script-defined functions in the module.
     #[cfg(not(feature = "no_function"))]
     pub fn iter_script_fn<'a>(&'a self) -> impl Iterator<Item = Shared<ScriptFnDef>> + 'a {
         self.functions
             .values()
-            .map(|(_, _, _, f)| f)
+            .map(|(_, _, _, f, _)| f)
             .filter(|f| f.is_script())
             .map(|f| f.get_shared_fn_def())
     }
 
     /// Create a new `Module` by evaluating an `AST`.
     ///
+    /// Script-defined functions in the module can access the module's exported variables
+    /// (as constants) and the modules it imports, when called via a module-qualified name.
+    ///
     /// # Examples
     ///
     /// ```
@@ -980,6 +1228,93 @@ impl Module {
         // Run the script
         engine.eval_ast_with_scope_raw(&mut scope, &mut mods, &ast)?;
 
+        // Modules imported lazily stay unresolved, unless they are needed for re-exporting
+        for stmt in ast.statements() {
+            if let Stmt::Export(list) = stmt {
+                for ((id, _), modules, _) in list.iter() {
+                    let root = modules.as_ref().map_or(id, |modules| &modules.get(0).0);
+
+                    if let Some((_, m)) = mods.iter_mut().rev().find(|(alias, _)| alias == root) {
+                        if let ImportedModule::Lazy(path, source, pos) = m {
+                            let source = source.as_ref().map(|s| s.as_str());
+                            *m = engine.resolve_import(source, path, *pos)?.into();
+                        }
+                    }
+                }
+            }
+        }
+
+        // Collect items re-exported from imported modules
+        let mut exports = Module::new();
+
+        ast.statements().iter().try_for_each(|stmt| match stmt {
+            Stmt::Export(list) => list.iter().try_for_each(|((id, id_pos), modules, rename)| {
+                let name = rename.as_ref().map(|(n, _)| n).unwrap_or(id);
+
+                match modules {
+                    // Re-export an imported module - export mod as name
+                    None if !scope.contains(id) => {
+                        if let Some(m) = mods
+                            .iter()
+                            .rev()
+                            .find(|(alias, _)| alias == id)
+                            .and_then(|(_, m)| m.module())
+                        {
+                            exports.modules.insert(name.clone(), m.as_ref().clone());
+                        }
+                        Ok(())
+                    }
+                    // Variables are exported via the scope
+                    None => Ok(()),
+                    Some(modules) => {
+                        let (root, root_pos) = modules.get(0);
+
+                        let root = mods
+                            .iter()
+                            .rev()
+                            .find(|(alias, _)| alias == root)
+                            .and_then(|(_, m)| m.module())
+                            .map(|m| m.as_ref())
+                            .or_else(|| {
+                                scope
+                                    .to_iter()
+                                    .find(|ScopeEntry { name, .. }| name == root)
+                                    .and_then(|ScopeEntry { value, .. }| {
+                                        value.downcast_ref::<Shared<Module>>()
+                                    })
+                                    .map(|m| m.as_ref())
+                            })
+                            .ok_or_else(|| {
+                                Box::new(EvalAltResult::ErrorModuleNotFound(
+                                    root.clone(),
+                                    *root_pos,
+                                ))
+                            })?;
+
+                        let source = modules.iter().skip(1).try_fold(root, |m, (name, pos)| {
+                            m.modules.get(name).ok_or_else(|| {
+                                Box::new(EvalAltResult::ErrorModuleNotFound(name.clone(), *pos))
+                            })
+                        })?;
+
+                        if id == "*" {
+                            // export mod::*
+                            exports.reexport_all(source);
+                            Ok(())
+                        } else if exports.reexport(source, id, name, true) {
+                            Ok(())
+                        } else {
+                            Err(Box::new(EvalAltResult::ErrorVariableNotFound(
+                                format!("{}{}", modules, id),
+                                *id_pos,
+                            )))
+                        }
+                    }
+                }
+            }),
+            _ => Ok(()),
+        })?;
+
         // Create new module
         let mut module = Module::new();
 
@@ -992,16 +1327,129 @@ impl Module {
                 }
             });
 
-        // Modules left in the scope become sub-modules
+        // Script-defined functions can access the module's variables and sub-modules
+        #[cfg(not(feature = "no_function"))]
+        let env = if ast.lib().iter_script_fn().next().is_some() {
+            Some(Shared::new(ModuleEnv {
+                variables: module
+                    .variables
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect(),
+                modules: mods
+                    .iter()
+                    .map(|(alias, m)| (alias.to_string(), m.clone()))
+                    .collect(),
+            }))
+        } else {
+            None
+        };
+
+        // Modules left in the scope become sub-modules, except those never resolved
         mods.into_iter().for_each(|(alias, m)| {
-            module.modules.insert(alias.to_string(), m);
+            if let ImportedModule::Resolved(m) = m {
+                module
+                    .modules
+                    .insert(alias.to_string(), shared_take_or_clone(m));
+            }
         });
 
         module.merge(ast.lib());
 
+        #[cfg(not(feature = "no_function"))]
+        if let Some(env) = env {
+            module.set_script_fn_env(&env);
+        }
+
+        // Items defined in the script take precedence over re-exported items
+        module.reexport_all(&exports);
+
         Ok(module)
     }
 
+    /// Copy the sub-module, variable and public functions with a particular name from another module
+    /// under a new name. Returns `false` if the other module contains no such item.
+    #[cfg(not(feature = "no_module"))]
+    fn reexport(&mut self, other: &Module, name: &str, new_name: &str, replace: bool) -> bool {
+        let mut found = false;
+
+        if let Some(m) = other.modules.get(name) {
+            if replace || !self.modules.contains_key(new_name) {
+                self.modules.insert(new_name.into(), m.clone());
+            }
+            found = true;
+        }
+
+        if let Some(value) = other.variables.get(name) {
+            if replace || !self.variables.contains_key(new_name) {
+                self.variables.insert(new_name.into(), value.clone());
+            }
+            found = true;
+        }
+
+        for (fn_name, access, params, func, signature) in other.functions.values() {
+            if fn_name != name || *access == Private {
+                continue;
+            }
+
+            #[cfg(not(feature = "no_function"))]
+            let (hash_fn, func) = if func.is_script() {
+                let mut fn_def = func.get_fn_def().clone();
+                fn_def.name = new_name.into();
+                // None + function name + number of arguments.
+                let hash_script = calc_fn_hash(empty(), new_name, fn_def.params.len(), empty());
+                (hash_script, fn_def.into())
+            } else {
+                let arg_types = params.iter().cloned();
+                let hash_fn = calc_fn_hash(empty(), new_name, params.len(), arg_types);
+                (hash_fn, func.clone())
+            };
+            #[cfg(feature = "no_function")]
+            let (hash_fn, func) = (
+                calc_fn_hash(empty(), new_name, params.len(), params.iter().cloned()),
+                func.clone(),
+            );
+
+            if replace || !self.functions.contains_key(&hash_fn) {
+                let info = (
+                    new_name.into(),
+                    *access,
+                    params.clone(),
+                    func,
+                    signature.clone(),
+                );
+                self.functions.insert(hash_fn, info);
+            }
+            found = true;
+        }
+
+        self.indexed = false;
+        found
+    }
+
+    /// Copy all sub-modules, variables and public functions from another module,
+    /// keeping existing items with the same names.
+    #[cfg(not(feature = "no_module"))]
+    fn reexport_all(&mut self, other: &Module) {
+        let names: HashSet<&str> = other
+            .modules
+            .keys()
+            .chain(other.variables.keys())
+            .chain(other.functions.values().map(|(name, _, _, _, _)| name))
+            .map(|name| name.as_str())
+            .collect();
+
+        names.into_iter().for_each(|name| {
+            self.reexport(other, name, name, false);
+        });
+    }
+
+    /// Is the module indexed?
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) fn is_indexed(&self) -> bool {
+        self.indexed
+    }
+
     /// Scan through all the sub-modules in the module build an index of all
     /// variables and external Rust functions via hashing.
     pub(crate) fn index_all_sub_modules(&mut self) {
@@ -1026,7 +1474,7 @@ impl Module {
                 variables.push((hash_var, value.clone()));
             }
             // Index all Rust functions
-            for (name, access, params, func) in module.functions.values() {
+            for (name, access, params, func, _) in module.functions.values() {
                 match access {
                     // Private functions are not exported
                     Private => continue,
@@ -1156,6 +1604,70 @@ impl ModuleRef {
 pub trait ModuleResolver: SendSync {
     /// Resolve a module based on a path string.
     fn resolve(&self, _: &Engine, path: &str, pos: Position) -> Result<Module, Box<EvalAltResult>>;
+
+    /// Resolve a module based on a path string, imported by a script with a particular source
+    /// (e.g. the path of the script file), if any.
+    ///
+    /// The module returned may be shared with the resolver and other importers
+    /// (e.g. if the resolver caches modules), in which case it is copied before being modified.
+    ///
+    /// The default implementation ignores the source and calls `resolve`.
+    fn resolve_with_source(
+        &self,
+        engine: &Engine,
+        _source: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        self.resolve(engine, path, pos).map(Into::into)
+    }
+}
+
+/// A shared module resolver, so that it can still be accessed (e.g. to clear its cache)
+/// after being set into an `Engine`.
+#[cfg(not(feature = "sync"))]
+impl<T: ModuleResolver + ?Sized> ModuleResolver for Rc<T> {
+    fn resolve(
+        &self,
+        engine: &Engine,
+        path: &str,
+        pos: Position,
+    ) -> Result<Module, Box<EvalAltResult>> {
+        self.as_ref().resolve(engine, path, pos)
+    }
+
+    fn resolve_with_source(
+        &self,
+        engine: &Engine,
+        source: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        self.as_ref().resolve_with_source(engine, source, path, pos)
+    }
+}
+
+/// A shared module resolver, so that it can still be accessed (e.g. to clear its cache)
+/// after being set into an `Engine`.
+impl<T: ModuleResolver + ?Sized> ModuleResolver for Arc<T> {
+    fn resolve(
+        &self,
+        engine: &Engine,
+        path: &str,
+        pos: Position,
+    ) -> Result<Module, Box<EvalAltResult>> {
+        self.as_ref().resolve(engine, path, pos)
+    }
+
+    fn resolve_with_source(
+        &self,
+        engine: &Engine,
+        source: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+        self.as_ref().resolve_with_source(engine, source, path, pos)
+    }
 }
 
 /// Re-export module resolvers.
@@ -1165,18 +1677,102 @@ pub mod resolvers {
     #[cfg(not(feature = "no_std"))]
     #[cfg(not(target_arch = "wasm32"))]
     pub use super::file::FileModuleResolver;
+    #[cfg(not(feature = "no_std"))]
+    pub use super::memory::MemoryModuleResolver;
     pub use super::stat::StaticModuleResolver;
 }
 #[cfg(feature = "no_module")]
 pub mod resolvers {}
 
+/// Stack of modules being resolved, used to detect circular imports.
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "no_std"))]
+mod import_stack {
+    use super::*;
+
+    #[cfg(feature = "sync")]
+    use crate::stdlib::thread::{self, ThreadId};
+
+    /// Stack of modules being resolved (keyed by `K`, with a name for display),
+    /// kept separately for each thread under the `sync` feature.
+    #[derive(Debug)]
+    pub(crate) struct ImportStack<K> {
+        #[cfg(not(feature = "sync"))]
+        stack: RefCell<Vec<(K, String)>>,
+        #[cfg(feature = "sync")]
+        stack: RwLock<HashMap<ThreadId, Vec<(K, String)>>>,
+    }
+
+    impl<K> Default for ImportStack<K> {
+        fn default() -> Self {
+            Self {
+                stack: Default::default(),
+            }
+        }
+    }
+
+    impl<K: PartialEq> ImportStack<K> {
+        /// Run a closure on the stack of the current thread.
+        fn with<T>(&self, f: impl FnOnce(&mut Vec<(K, String)>) -> T) -> T {
+            #[cfg(not(feature = "sync"))]
+            return f(&mut self.stack.borrow_mut());
+
+            #[cfg(feature = "sync")]
+            {
+                let id = thread::current().id();
+                let mut stacks = self.stack.write().unwrap();
+                let stack = stacks.entry(id).or_default();
+                let result = f(stack);
+
+                if stack.is_empty() {
+                    stacks.remove(&id);
+                }
+
+                return result;
+            }
+        }
+
+        /// Push a module onto the stack.
+        ///
+        /// Fails with `ErrorCircularImport` if the module is already being resolved.
+        pub fn push(&self, key: K, name: String, pos: Position) -> Result<(), Box<EvalAltResult>> {
+            self.with(|stack| {
+                if let Some(index) = stack.iter().position(|(k, _)| *k == key) {
+                    let mut chain: Vec<_> = stack[index..].iter().map(|(_, n)| n.clone()).collect();
+                    chain.push(name);
+                    Err(Box::new(EvalAltResult::ErrorCircularImport(chain, pos)))
+                } else {
+                    stack.push((key, name));
+                    Ok(())
+                }
+            })
+        }
+
+        /// Pop the last module off the stack.
+        pub fn pop(&self) {
+            self.with(|stack| stack.pop());
+        }
+    }
+}
+
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "no_std"))]
+use import_stack::ImportStack;
+
 /// Script file-based module resolver.
 #[cfg(not(feature = "no_module"))]
 #[cfg(not(feature = "no_std"))]
 #[cfg(not(target_arch = "wasm32"))]
 mod file {
     use super::*;
-    use crate::stdlib::path::PathBuf;
+    use crate::stdlib::{
+        collections::HashSet,
+        env,
+        fs::{self, File},
+        io::Read,
+        path::{Component, Path, PathBuf},
+        time::SystemTime,
+    };
 
     /// Module resolution service that loads module script files from the file system.
     ///
@@ -1187,6 +1783,28 @@ mod file {
     /// to the base directory. The script file is then forced to be in a specified extension
     /// (default `.rhai`).
     ///
+    /// Import paths are resolved as follows:
+    ///
+    /// * Absolute paths are used as-is.
+    /// * Paths starting with `./` or `../` are relative to the directory of the importing script
+    ///   (if it has a source, e.g. it was loaded via `Engine::compile_file` or by this resolver).
+    /// * All other paths are searched in the base directory, then in each additional directory
+    ///   added via `add_path`, in order.
+    ///
+    /// A module that imports itself, directly or indirectly, fails with `ErrorCircularImport`.
+    ///
+    /// By default, a module is evaluated each time it is imported. Turn on `set_cache_modules`
+    /// to evaluate each script file only once and share the resulting module among all importers.
+    ///
+    /// For untrusted scripts, turn on `set_sandboxed` to confine script files to the search
+    /// directories, and use `set_max_file_size` to limit the size of script files.
+    /// Script files that are not allowed fail with `ErrorModuleAccessDenied`.
+    ///
+    /// Cached script files can be evicted via `clear_cache`, `invalidate` or `invalidate_changed`.
+    /// Alternatively, turn on `set_auto_reload` to check the modification time of a script file
+    /// each time it is imported. Wrap the resolver in an `Rc` (or `Arc` under the `sync` feature)
+    /// to keep access to it after setting it into an `Engine`.
+    ///
     /// # Examples
     ///
     /// ```
@@ -1202,14 +1820,74 @@ mod file {
     /// ```
     #[derive(Debug)]
     pub struct FileModuleResolver {
-        path: PathBuf,
+        paths: Vec<PathBuf>,
         extension: String,
 
+        auto_reload: bool,
+        cache_modules: bool,
+        sandboxed: bool,
+        follow_symlinks: bool,
+        max_file_size: usize,
+
+        #[cfg(not(feature = "sync"))]
+        cache: RefCell<HashMap<PathBuf, CachedScript>>,
+
+        #[cfg(feature = "sync")]
+        cache: RwLock<HashMap<PathBuf, CachedScript>>,
+
+        /// Script files imported by each script file.
         #[cfg(not(feature = "sync"))]
-        cache: RefCell<HashMap<PathBuf, AST>>,
+        imports: RefCell<HashMap<PathBuf, HashSet<PathBuf>>>,
 
+        /// Script files imported by each script file.
         #[cfg(feature = "sync")]
-        cache: RwLock<HashMap<PathBuf, AST>>,
+        imports: RwLock<HashMap<PathBuf, HashSet<PathBuf>>>,
+
+        /// Script files being resolved, used to detect circular imports.
+        resolving: ImportStack<PathBuf>,
+    }
+
+    /// A compiled script file.
+    #[derive(Debug, Clone)]
+    struct CachedScript {
+        ast: AST,
+        /// Modification time of the script file when it was compiled.
+        modified: Option<SystemTime>,
+        /// Module evaluated from the script file, if modules are cached.
+        module: Option<Shared<Module>>,
+    }
+
+    /// Get the modification time of a file, if available.
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Make a path absolute and resolve `.` and `..` components, without accessing the file system.
+    fn normalize_path(path: &Path) -> PathBuf {
+        let path = if path.is_absolute() {
+            path.into()
+        } else {
+            env::current_dir().unwrap_or_default().join(path)
+        };
+
+        let mut result = PathBuf::new();
+
+        path.components().for_each(|component| match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                result.pop();
+            }
+            c => result.push(c),
+        });
+
+        result
+    }
+
+    /// Get the key of a script file in the cache.
+    ///
+    /// The same script file may be reached via different paths.
+    fn cache_key(path: &Path) -> PathBuf {
+        path.canonicalize().unwrap_or_else(|_| path.into())
     }
 
     impl Default for FileModuleResolver {
@@ -1260,9 +1938,16 @@ mod file {
             extension: E,
         ) -> Self {
             Self {
-                path: path.into(),
+                paths: vec![path.into()],
                 extension: extension.into(),
+                auto_reload: false,
+                cache_modules: false,
+                sandboxed: false,
+                follow_symlinks: true,
+                max_file_size: 0,
                 cache: Default::default(),
+                imports: Default::default(),
+                resolving: Default::default(),
             }
         }
 
@@ -1285,14 +1970,541 @@ mod file {
             Default::default()
         }
 
-        /// Create a `Module` from a file path.
-        pub fn create_module<P: Into<PathBuf>>(
-            &self,
-            engine: &Engine,
+        /// Add a directory to search for script files, after the base directory
+        /// and all directories previously added.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use rhai::Engine;
+        /// use rhai::module_resolvers::FileModuleResolver;
+        ///
+        /// // Search for scripts in the 'scripts' subdirectory, then in 'vendor/lib'.
+        /// let mut resolver = FileModuleResolver::new_with_path("./scripts");
+        /// resolver.add_path("./vendor/lib");
+        ///
+        /// let mut engine = Engine::new();
+        /// engine.set_module_resolver(Some(resolver));
+        /// ```
+        pub fn add_path<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+            self.paths.push(path.into());
+            self
+        }
+
+        /// Get the directories searched for script files, starting with the base directory.
+        pub fn paths(&self) -> &[PathBuf] {
+            &self.paths
+        }
+
+        /// Turn automatic reloading of script files on or off.
+        ///
+        /// When turned on, the modification time of a cached script file is checked each time
+        /// it is imported, and the script file is recompiled if it has changed.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use rhai::Engine;
+        /// use rhai::module_resolvers::FileModuleResolver;
+        ///
+        /// let mut resolver = FileModuleResolver::new_with_path("./scripts");
+        /// resolver.set_auto_reload(true);
+        ///
+        /// let mut engine = Engine::new();
+        /// engine.set_module_resolver(Some(resolver));
+        /// ```
+        pub fn set_auto_reload(&mut self, enable: bool) -> &mut Self {
+            self.auto_reload = enable;
+            self
+        }
+
+        /// Is automatic reloading of script files turned on?
+        pub fn is_auto_reload(&self) -> bool {
+            self.auto_reload
+        }
+
+        /// Turn sandboxing on or off.
+        ///
+        /// When turned on, script files must be inside one of the search directories
+        /// (after resolving `..` and symbolic links), otherwise importing them fails with
+        /// `ErrorModuleAccessDenied`. This includes absolute import paths and relative paths
+        /// starting with `../`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use rhai::{Engine, EvalAltResult};
+        /// use rhai::module_resolvers::FileModuleResolver;
+        ///
+        /// let mut resolver = FileModuleResolver::new_with_path("./scripts");
+        /// resolver.set_sandboxed(true);
+        ///
+        /// let mut engine = Engine::new();
+        /// engine.set_module_resolver(Some(resolver));
+        ///
+        /// let err = engine.consume(r#"import "../secret" as s;"#).expect_err("should error");
+        /// assert!(matches!(*err, EvalAltResult::ErrorModuleAccessDenied(_, _, _)));
+        /// ```
+        pub fn set_sandboxed(&mut self, enable: bool) -> &mut Self {
+            self.sandboxed = enable;
+            self
+        }
+
+        /// Is sandboxing turned on?
+        pub fn is_sandboxed(&self) -> bool {
+            self.sandboxed
+        }
+
+        /// Allow or disallow symbolic links inside the search directories when sandboxing is turned on.
+        ///
+        /// Symbolic links are allowed by default (as long as they do not lead outside of the search
+        /// directories). When disallowed, importing a script file through a symbolic link fails with
+        /// `ErrorModuleAccessDenied`.
+        pub fn set_follow_symlinks(&mut self, enable: bool) -> &mut Self {
+            self.follow_symlinks = enable;
+            self
+        }
+
+        /// Are symbolic links allowed when sandboxing is turned on?
+        pub fn is_following_symlinks(&self) -> bool {
+            self.follow_symlinks
+        }
+
+        /// Set the maximum size, in bytes, of script files. Zero (the default) means no limit.
+        ///
+        /// Script files are read up to this size only.
+        /// Importing a larger script file fails with `ErrorModuleAccessDenied`.
+        pub fn set_max_file_size(&mut self, max_size: usize) -> &mut Self {
+            self.max_file_size = max_size;
+            self
+        }
+
+        /// The maximum size, in bytes, of script files. Zero means no limit.
+        pub fn max_file_size(&self) -> usize {
+            self.max_file_size
+        }
+
+        /// Turn caching of modules on or off.
+        ///
+        /// When turned on, each script file is evaluated only once (until it is removed from the cache
+        /// or reloaded) and the same module is shared by all scripts importing it.
+        /// Module-level statements (e.g. `print`) therefore run only once.
+        ///
+        /// Modules are always evaluated in a new, empty `Scope`, never in the `Scope` of the importing
+        /// script, so a module does not depend on who imports it first. However, module-level code
+        /// depending on anything else (e.g. a registered function returning a different value each time)
+        /// is not re-run; leave caching of modules off (the default) for such modules.
+        ///
+        /// Module variables are constants. Modifying one in place (e.g. via a method call) only modifies
+        /// the importing script's own copy of the module.
+        ///
+        /// Under the `sync` feature, modules are shared among threads. Two threads importing the same
+        /// script file at the same time may both evaluate it, with only one of the resulting modules kept.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use rhai::Engine;
+        /// use rhai::module_resolvers::FileModuleResolver;
+        ///
+        /// let mut resolver = FileModuleResolver::new_with_path("./scripts");
+        /// resolver.set_cache_modules(true);
+        ///
+        /// let mut engine = Engine::new();
+        /// engine.set_module_resolver(Some(resolver));
+        /// ```
+        pub fn set_cache_modules(&mut self, enable: bool) -> &mut Self {
+            self.cache_modules = enable;
+            self
+        }
+
+        /// Is caching of modules turned on?
+        pub fn is_caching_modules(&self) -> bool {
+            self.cache_modules
+        }
+
+        /// Remove all script files from the cache, so they are reloaded the next time
+        /// they are imported.
+        pub fn clear_cache(&self) {
+            self.with_cache(|cache| cache.clear());
+        }
+
+        /// Remove a script file from the cache, so it is reloaded the next time it is imported.
+        ///
+        /// The path must include the file extension.
+        /// Returns `true` if the script file was cached.
+        pub fn invalidate<P: AsRef<Path>>(&self, path: P) -> bool {
+            let key = cache_key(path.as_ref());
+            let removed = self.with_cache(|cache| cache.remove(&key).is_some());
+            self.remove_dependent_modules(&[key]);
+            removed
+        }
+
+        /// Remove all script files that have been modified (or deleted) since they were compiled
+        /// from the cache, returning their paths.
+        ///
+        /// Use `depends_on` to find out which top-level scripts import any of them.
+        pub fn invalidate_changed(&self) -> Vec<PathBuf> {
+            let changed = self.with_cache(|cache| {
+                let changed: Vec<_> = cache
+                    .iter()
+                    .filter(|(key, script)| modified_time(key) != script.modified)
+                    .map(|(key, _)| key.clone())
+                    .collect();
+
+                changed.iter().for_each(|key| {
+                    cache.remove(key);
+                });
+
+                changed
+            });
+
+            self.remove_dependent_modules(&changed);
+            changed
+        }
+
+        /// Does a script, directly or indirectly, import any of the specified script files?
+        ///
+        /// The script is identified by the source of the `AST` (see `Engine::compile_file`),
+        /// and only imports resolved by this resolver while evaluating it are known.
+        /// A script is also considered to depend on itself.
+        ///
+        /// This is useful to find out which top-level scripts must be recompiled (or re-run)
+        /// after some script files have changed.
+        ///
+        /// # Examples
+        ///
+        /// ```no_run
+        /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+        /// use rhai::Engine;
+        /// use rhai::module_resolvers::FileModuleResolver;
+        /// use std::sync::Arc;
+        ///
+        /// let resolver = Arc::new(FileModuleResolver::new_with_path("./scripts"));
+        ///
+        /// let mut engine = Engine::new();
+        /// engine.set_module_resolver(Some(resolver.clone()));
+        ///
+        /// let mut ast = engine.compile_file("main.rhai".into())?;
+        /// engine.consume_ast(&ast)?;
+        ///
+        /// // Some time later...
+        /// let changed = resolver.invalidate_changed();
+        ///
+        /// if resolver.depends_on(&ast, &changed) {
+        ///     ast = engine.compile_file("main.rhai".into())?;
+        ///     engine.consume_ast(&ast)?;
+        /// }
+        /// # Ok(())
+        /// # }
+        /// ```
+        pub fn depends_on<P: AsRef<Path>>(&self, ast: &AST, files: &[P]) -> bool {
+            let source = match ast.source() {
+                Some(source) => cache_key(Path::new(source)),
+                None => return false,
+            };
+
+            let files: HashSet<_> = files.iter().map(|p| cache_key(p.as_ref())).collect();
+
+            self.dependencies(source)
+                .iter()
+                .any(|key| files.contains(key))
+        }
+
+        /// Get a script file together with all script files it imports, directly or indirectly.
+        fn dependencies(&self, key: PathBuf) -> HashSet<PathBuf> {
+            self.with_imports(|imports| {
+                let mut visited = HashSet::new();
+                let mut pending = vec![key];
+
+                while let Some(key) = pending.pop() {
+                    if let Some(children) = imports.get(&key) {
+                        pending.extend(children.iter().filter(|&k| !visited.contains(k)).cloned());
+                    }
+                    visited.insert(key);
+                }
+
+                visited
+            })
+        }
+
+        /// Has a cached script file changed since it was compiled?
+        ///
+        /// If modules are cached, script files it imports (directly or indirectly) are also checked,
+        /// as they are not imported again.
+        fn is_changed(&self, key: &Path) -> bool {
+            let files = if self.cache_modules {
+                self.dependencies(key.into())
+            } else {
+                once(key.into()).collect()
+            };
+
+            self.with_cache(|cache| {
+                files.iter().any(|key| match cache.get(key) {
+                    Some(script) => modified_time(key) != script.modified,
+                    None => true,
+                })
+            })
+        }
+
+        /// Remove cached modules that import (directly or indirectly) any of the specified script files.
+        fn remove_dependent_modules(&self, files: &[PathBuf]) {
+            if files.is_empty() {
+                return;
+            }
+
+            let keys: Vec<_> = self.with_cache(|cache| {
+                cache
+                    .iter()
+                    .filter(|(_, script)| script.module.is_some())
+                    .map(|(key, _)| key.clone())
+                    .collect()
+            });
+
+            let stale: Vec<_> = keys
+                .into_iter()
+                .filter(|key| {
+                    self.dependencies(key.clone())
+                        .iter()
+                        .any(|k| files.contains(k))
+                })
+                .collect();
+
+            self.with_cache(|cache| {
+                stale.iter().for_each(|key| {
+                    if let Some(script) = cache.get_mut(key) {
+                        script.module = None;
+                    }
+                })
+            });
+        }
+
+        /// Create a `Module` from a file path.
+        pub fn create_module<P: Into<PathBuf>>(
+            &self,
+            engine: &Engine,
             path: &str,
         ) -> Result<Module, Box<EvalAltResult>> {
             self.resolve(engine, path, Default::default())
         }
+
+        /// Find the script file for an import path.
+        ///
+        /// If the script file cannot be found in any search directory,
+        /// the path relative to the base directory is returned.
+        fn find_file(&self, source: Option<&str>, path: &str) -> PathBuf {
+            let with_extension = |mut file_path: PathBuf| {
+                file_path.set_extension(&self.extension); // Force extension
+                file_path
+            };
+
+            let import_path = Path::new(path);
+
+            if import_path.is_absolute() {
+                return with_extension(import_path.into());
+            }
+
+            // Relative to the directory of the importing script
+            match import_path.components().next() {
+                Some(Component::CurDir) | Some(Component::ParentDir) => {
+                    if let Some(dir) = source.and_then(|s| Path::new(s).parent()) {
+                        return with_extension(dir.join(import_path));
+                    }
+                }
+                _ => (),
+            }
+
+            // Search all directories in order
+            self.paths
+                .iter()
+                .map(|dir| with_extension(dir.join(import_path)))
+                .find(|file_path| file_path.is_file())
+                .unwrap_or_else(|| with_extension(self.paths[0].join(import_path)))
+        }
+
+        /// Check that a script file is inside one of the search paths.
+        fn check_sandbox(&self, file_path: &Path, pos: Position) -> Result<(), Box<EvalAltResult>> {
+            let deny = |reason: &str| {
+                Err(Box::new(EvalAltResult::ErrorModuleAccessDenied(
+                    file_path.into(),
+                    reason.into(),
+                    pos,
+                )))
+            };
+
+            let path = normalize_path(file_path);
+
+            let root = match self
+                .paths
+                .iter()
+                .map(|p| normalize_path(p))
+                .find(|root| path.starts_with(root))
+            {
+                Some(root) => root,
+                None => return deny("outside of the module search paths"),
+            };
+
+            if !self.follow_symlinks {
+                let mut current = root.clone();
+
+                for component in path.strip_prefix(&root).unwrap().components() {
+                    current.push(component);
+
+                    if fs::symlink_metadata(&current)
+                        .map(|m| m.file_type().is_symlink())
+                        .unwrap_or(false)
+                    {
+                        return deny("symbolic links are not allowed");
+                    }
+                }
+            }
+
+            // Symbolic links must not lead outside of the search paths
+            if let Ok(real_path) = path.canonicalize() {
+                if !self
+                    .paths
+                    .iter()
+                    .filter_map(|p| p.canonicalize().ok())
+                    .any(|root| real_path.starts_with(root))
+                {
+                    return deny("outside of the module search paths");
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Run a closure on the cache of compiled script files.
+        fn with_cache<T>(&self, f: impl FnOnce(&mut HashMap<PathBuf, CachedScript>) -> T) -> T {
+            #[cfg(not(feature = "sync"))]
+            return f(&mut self.cache.borrow_mut());
+            #[cfg(feature = "sync")]
+            return f(&mut self.cache.write().unwrap());
+        }
+
+        /// Run a closure on the script files imported by each script file.
+        fn with_imports<T>(
+            &self,
+            f: impl FnOnce(&mut HashMap<PathBuf, HashSet<PathBuf>>) -> T,
+        ) -> T {
+            #[cfg(not(feature = "sync"))]
+            return f(&mut self.imports.borrow_mut());
+            #[cfg(feature = "sync")]
+            return f(&mut self.imports.write().unwrap());
+        }
+
+        /// Load a script file and evaluate it into a `Module`, using the cache if possible.
+        fn load_module(
+            &self,
+            engine: &Engine,
+            file_path: PathBuf,
+            key: &PathBuf,
+            pos: Position,
+        ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+            // See if it is cached (and not changed, if reloading automatically)
+            // Do not hold on to the cache while evaluating, as the module may import others
+            let cached = self.with_cache(|cache| cache.get(key).cloned());
+
+            if let Some(script) = cached {
+                if !self.auto_reload || !self.is_changed(key) {
+                    match script.module {
+                        Some(module) if self.cache_modules => return Ok(module),
+                        _ => (),
+                    }
+
+                    let module = self.eval_module(engine, &script.ast, pos)?;
+
+                    if self.cache_modules {
+                        self.with_cache(|cache| {
+                            if let Some(script) = cache.get_mut(key) {
+                                script.module = Some(module.clone());
+                            }
+                        });
+                    }
+
+                    return Ok(module);
+                }
+            }
+
+            // Load the file and compile it if not found
+            let modified = modified_time(&file_path);
+            let contents = self.read_script(&file_path, pos)?;
+
+            let mut ast = engine
+                .compile(&contents)
+                .map_err(|err| Box::<EvalAltResult>::from(err).new_position(pos))?;
+            ast.set_source(file_path.to_string_lossy().to_string());
+
+            // Imports are recorded again during evaluation
+            self.with_imports(|imports| imports.remove(key));
+
+            let module = self.eval_module(engine, &ast, pos)?;
+
+            // Put it into the cache
+            let script = CachedScript {
+                ast,
+                modified,
+                module: if self.cache_modules {
+                    Some(module.clone())
+                } else {
+                    None
+                },
+            };
+            self.with_cache(|cache| cache.insert(key.clone(), script));
+
+            Ok(module)
+        }
+
+        /// Read a script file into a string, failing if it is larger than the maximum file size.
+        ///
+        /// At most one byte past the maximum file size is read, so files that grow after being
+        /// opened, or that have no size (e.g. pipes), cannot get past the limit.
+        fn read_script(&self, file_path: &Path, pos: Position) -> Result<String, Box<EvalAltResult>> {
+            let err_reading = |err| {
+                Box::new(EvalAltResult::ErrorReadingScriptFile(
+                    file_path.to_path_buf(),
+                    pos,
+                    err,
+                ))
+            };
+
+            let mut file = File::open(file_path).map_err(err_reading)?;
+            let mut contents = String::new();
+
+            if self.max_file_size == 0 {
+                file.read_to_string(&mut contents).map_err(err_reading)?;
+            } else {
+                file.take(self.max_file_size as u64 + 1)
+                    .read_to_string(&mut contents)
+                    .map_err(err_reading)?;
+
+                if contents.len() > self.max_file_size {
+                    return Err(Box::new(EvalAltResult::ErrorModuleAccessDenied(
+                        file_path.to_path_buf(),
+                        "file too large".into(),
+                        pos,
+                    )));
+                }
+            }
+
+            Ok(contents)
+        }
+
+        /// Evaluate a compiled script file into an indexed `Module`.
+        fn eval_module(
+            &self,
+            engine: &Engine,
+            ast: &AST,
+            pos: Position,
+        ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+            let mut module = Module::eval_ast_as_new(Default::default(), ast, engine)
+                .map_err(|err| err.new_position(pos))?;
+
+            // Index the module so that importers need not copy it
+            module.index_all_sub_modules();
+
+            Ok(module.into())
+        }
     }
 
     impl ModuleResolver for FileModuleResolver {
@@ -1302,49 +2514,346 @@ mod file {
             path: &str,
             pos: Position,
         ) -> Result<Module, Box<EvalAltResult>> {
+            self.resolve_with_source(engine, None, path, pos)
+                .map(shared_take_or_clone)
+        }
+
+        fn resolve_with_source(
+            &self,
+            engine: &Engine,
+            source: Option<&str>,
+            path: &str,
+            pos: Position,
+        ) -> Result<Shared<Module>, Box<EvalAltResult>> {
             // Construct the script file path
-            let mut file_path = self.path.clone();
-            file_path.push(path);
-            file_path.set_extension(&self.extension); // Force extension
-
-            let scope = Default::default();
-
-            // See if it is cached
-            let (module, ast) = {
-                #[cfg(not(feature = "sync"))]
-                let c = self.cache.borrow();
-                #[cfg(feature = "sync")]
-                let c = self.cache.read().unwrap();
-
-                match c.get(&file_path) {
-                    Some(ast) => (
-                        Module::eval_ast_as_new(scope, ast, engine)
-                            .map_err(|err| err.new_position(pos))?,
-                        None,
-                    ),
-                    None => {
-                        // Load the file and compile it if not found
-                        let ast = engine
-                            .compile_file(file_path.clone())
-                            .map_err(|err| err.new_position(pos))?;
-
-                        (
-                            Module::eval_ast_as_new(scope, &ast, engine)
-                                .map_err(|err| err.new_position(pos))?,
-                            Some(ast),
-                        )
-                    }
+            let file_path = self.find_file(source, path);
+
+            if self.sandboxed {
+                self.check_sandbox(&file_path, pos)?;
+            }
+
+            let key = cache_key(&file_path);
+
+            // Record the import
+            if let Some(source) = source {
+                let importer = cache_key(Path::new(source));
+                self.with_imports(|imports| {
+                    imports.entry(importer).or_default().insert(key.clone())
+                });
+            }
+
+            // Guard against circular imports
+            let name = file_path.display().to_string();
+
+            self.resolving.push(key.clone(), name, pos)?;
+            let result = self.load_module(engine, file_path, &key, pos);
+            self.resolving.pop();
+
+            result
+        }
+    }
+}
+
+/// In-memory script module resolver.
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "no_std"))]
+mod memory {
+    use super::*;
+
+    /// A script held by a `MemoryModuleResolver`.
+    #[derive(Debug, Clone)]
+    enum Script {
+        /// Script text, with its source name.
+        Text(String, ImmutableString),
+        /// Compiled script.
+        Ast(Box<AST>),
+    }
+
+    impl Script {
+        /// Get the source name of the script.
+        fn source(&self) -> Option<&str> {
+            match self {
+                Self::Text(_, source) => Some(source.as_str()),
+                Self::Ast(ast) => ast.source(),
+            }
+        }
+    }
+
+    /// Module resolution service that serves modules from scripts held in memory
+    /// (e.g. loaded from a database), keyed by path.
+    ///
+    /// Scripts are compiled and evaluated only when first imported.
+    /// The resulting module is cached and shared by all importers.
+    ///
+    /// Paths are `/`-separated. Import paths starting with `./` or `../` are relative to the path
+    /// of the importing script, if it is held by the same resolver.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    /// use rhai::module_resolvers::MemoryModuleResolver;
+    ///
+    /// let mut resolver = MemoryModuleResolver::new();
+    /// resolver.insert_script("lib/math", r#"import "./consts" as c; fn double(x) { x * c::two }"#);
+    /// resolver.insert_script("lib/consts", "let two = 2; export two;");
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.set_module_resolver(Some(resolver));
+    ///
+    /// assert_eq!(engine.eval::<i64>(r#"import "lib/math" as m; m::double(21)"#)?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[derive(Debug, Default)]
+    pub struct MemoryModuleResolver {
+        scripts: HashMap<String, Script>,
+
+        #[cfg(not(feature = "sync"))]
+        cache: RefCell<HashMap<String, Shared<Module>>>,
+
+        #[cfg(feature = "sync")]
+        cache: RwLock<HashMap<String, Shared<Module>>>,
+
+        /// Scripts being resolved, used to detect circular imports.
+        resolving: ImportStack<String>,
+    }
+
+    /// Normalize a `/`-separated path, resolving `.` and `..` segments.
+    ///
+    /// If the path starts with `./` or `../`, it is relative to the directory of `base`.
+    /// Returns `None` if the path goes above the root.
+    fn normalize_path(base: Option<&str>, path: &str) -> Option<String> {
+        let mut segments = Vec::new();
+
+        if path.starts_with("./") || path.starts_with("../") {
+            if let Some(base) = base {
+                segments.extend(base.split('/').filter(|s| !s.is_empty()));
+                segments.pop();
+            }
+        }
+
+        for segment in path.split('/') {
+            match segment {
+                "" | "." => (),
+                ".." => {
+                    segments.pop()?;
+                }
+                s => segments.push(s),
+            }
+        }
+
+        Some(segments.join("/"))
+    }
+
+    impl MemoryModuleResolver {
+        /// Create a new `MemoryModuleResolver`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use rhai::Engine;
+        /// use rhai::module_resolvers::MemoryModuleResolver;
+        ///
+        /// let mut resolver = MemoryModuleResolver::new();
+        /// resolver.insert_script("hello", "let x = 42; export x;");
+        ///
+        /// let mut engine = Engine::new();
+        /// engine.set_module_resolver(Some(resolver));
+        /// ```
+        pub fn new() -> Self {
+            Default::default()
+        }
+
+        /// Add a script keyed by its path. The source name of the script is its path.
+        ///
+        /// Any script previously added with the same path is replaced.
+        pub fn insert_script<P: Into<String>, S: Into<String>>(
+            &mut self,
+            path: P,
+            script: S,
+        ) -> &mut Self {
+            let path = path.into();
+            let source = path.clone().into();
+            self.insert(path, Script::Text(script.into(), source))
+        }
+
+        /// Add a script keyed by its path, with a specific source name
+        /// (e.g. to identify the script in error messages, profiles and coverage reports).
+        ///
+        /// Any script previously added with the same path is replaced.
+        pub fn insert_script_with_source<P: Into<String>, S: Into<String>>(
+            &mut self,
+            path: P,
+            script: S,
+            source: impl Into<ImmutableString>,
+        ) -> &mut Self {
+            self.insert(path.into(), Script::Text(script.into(), source.into()))
+        }
+
+        /// Add a compiled script keyed by its path.
+        /// The source name of the script is the source of the `AST`, if any, otherwise its path.
+        ///
+        /// Any script previously added with the same path is replaced.
+        pub fn insert_ast<P: Into<String>>(&mut self, path: P, mut ast: AST) -> &mut Self {
+            let path = path.into();
+            if ast.source().is_none() {
+                ast.set_source(path.as_str());
+            }
+            self.insert(path, Script::Ast(Box::new(ast)))
+        }
+
+        /// Add a script, removing any module previously evaluated from the same path.
+        fn insert(&mut self, path: String, script: Script) -> &mut Self {
+            let path = normalize_path(None, &path).unwrap_or(path);
+            self.cache_mut().remove(&path);
+            self.scripts.insert(path, script);
+            self
+        }
+
+        /// Remove a script given its path.
+        ///
+        /// Returns `true` if the script existed.
+        pub fn remove(&mut self, path: &str) -> bool {
+            let path = normalize_path(None, path).unwrap_or_else(|| path.into());
+            self.cache_mut().remove(&path);
+            self.scripts.remove(&path).is_some()
+        }
+
+        /// Does the path exist?
+        pub fn contains_path(&self, path: &str) -> bool {
+            match normalize_path(None, path) {
+                Some(path) => self.scripts.contains_key(&path),
+                None => false,
+            }
+        }
+
+        /// Get an iterator of all the script paths.
+        pub fn paths(&self) -> impl Iterator<Item = &str> {
+            self.scripts.keys().map(String::as_str)
+        }
+
+        /// Remove all scripts.
+        pub fn clear(&mut self) {
+            self.scripts.clear();
+            self.cache_mut().clear();
+        }
+
+        /// Remove all evaluated modules, so that scripts are evaluated again the next time
+        /// they are imported.
+        pub fn clear_cache(&self) {
+            #[cfg(not(feature = "sync"))]
+            self.cache.borrow_mut().clear();
+            #[cfg(feature = "sync")]
+            self.cache.write().unwrap().clear();
+        }
+
+        /// Get a mutable reference to the evaluated modules.
+        fn cache_mut(&mut self) -> &mut HashMap<String, Shared<Module>> {
+            #[cfg(not(feature = "sync"))]
+            return self.cache.get_mut();
+            #[cfg(feature = "sync")]
+            return self.cache.get_mut().unwrap();
+        }
+
+        /// Find the path of the script with a particular source name.
+        fn find_source<'a>(&'a self, source: &'a str) -> Option<&'a str> {
+            if self.scripts.contains_key(source) {
+                return Some(source);
+            }
+
+            self.scripts
+                .iter()
+                .find(|(_, script)| script.source() == Some(source))
+                .map(|(path, _)| path.as_str())
+        }
+
+        /// Compile and evaluate a script into a `Module`.
+        fn eval_module(
+            &self,
+            engine: &Engine,
+            script: &Script,
+            pos: Position,
+        ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+            let compiled;
+
+            let ast = match script {
+                Script::Text(text, source) => {
+                    let mut ast = engine
+                        .compile(text)
+                        .map_err(|err| Box::<EvalAltResult>::from(err).new_position(pos))?;
+                    ast.set_source(source.clone());
+                    compiled = ast;
+                    &compiled
+                }
+                Script::Ast(ast) => ast.as_ref(),
+            };
+
+            let mut module = Module::eval_ast_as_new(Default::default(), ast, engine)
+                .map_err(|err| err.new_position(pos))?;
+            module.index_all_sub_modules();
+
+            Ok(module.into())
+        }
+    }
+
+    impl ModuleResolver for MemoryModuleResolver {
+        fn resolve(
+            &self,
+            engine: &Engine,
+            path: &str,
+            pos: Position,
+        ) -> Result<Module, Box<EvalAltResult>> {
+            self.resolve_with_source(engine, None, path, pos)
+                .map(shared_take_or_clone)
+        }
+
+        fn resolve_with_source(
+            &self,
+            engine: &Engine,
+            source: Option<&str>,
+            path: &str,
+            pos: Position,
+        ) -> Result<Shared<Module>, Box<EvalAltResult>> {
+            // Relative paths are based off the path of the importing script
+            let base = source.map(|s| self.find_source(s).unwrap_or(s));
+
+            let (key, script) = match normalize_path(base, path)
+                .and_then(|key| self.scripts.get(&key).map(|script| (key, script)))
+            {
+                Some(entry) => entry,
+                None => {
+                    return Err(Box::new(EvalAltResult::ErrorModuleNotFound(
+                        path.into(),
+                        pos,
+                    )))
                 }
             };
 
-            if let Some(ast) = ast {
-                // Put it into the cache
-                #[cfg(not(feature = "sync"))]
-                self.cache.borrow_mut().insert(file_path, ast);
-                #[cfg(feature = "sync")]
-                self.cache.write().unwrap().insert(file_path, ast);
+            // See if it is already evaluated
+            #[cfg(not(feature = "sync"))]
+            let cached = self.cache.borrow().get(&key).cloned();
+            #[cfg(feature = "sync")]
+            let cached = self.cache.read().unwrap().get(&key).cloned();
+
+            if let Some(module) = cached {
+                return Ok(module);
             }
 
+            // Guard against circular imports
+            self.resolving.push(key.clone(), key.clone(), pos)?;
+            let result = self.eval_module(engine, script, pos);
+            self.resolving.pop();
+
+            let module = result?;
+
+            // Put it into the cache
+            #[cfg(not(feature = "sync"))]
+            self.cache.borrow_mut().insert(key, module.clone());
+            #[cfg(feature = "sync")]
+            self.cache.write().unwrap().insert(key, module.clone());
+
             Ok(module)
         }
     }
@@ -1520,9 +3029,25 @@ mod collection {
             path: &str,
             pos: Position,
         ) -> Result<Module, Box<EvalAltResult>> {
+            self.resolve_with_source(engine, None, path, pos)
+                .map(shared_take_or_clone)
+        }
+
+        fn resolve_with_source(
+            &self,
+            engine: &Engine,
+            source: Option<&str>,
+            path: &str,
+            pos: Position,
+        ) -> Result<Shared<Module>, Box<EvalAltResult>> {
             for resolver in self.0.iter() {
-                if let Ok(module) = resolver.resolve(engine, path, pos) {
-                    return Ok(module);
+                match resolver.resolve_with_source(engine, source, path, pos) {
+                    Ok(module) => return Ok(module),
+                    // Do not hide circular imports behind 'module not found'
+                    Err(err) if matches!(*err, EvalAltResult::ErrorCircularImport(_, _)) => {
+                        return Err(err)
+                    }
+                    Err(_) => (),
                 }
             }
 
@@ -8,7 +8,7 @@ use crate::engine::{
 use crate::module::Module;
 use crate::parser::{map_dynamic_to_expr, Expr, ReturnType, ScriptFnDef, Stmt, AST};
 use crate::scope::{Entry as ScopeEntry, EntryType as ScopeEntryType, Scope};
-use crate::token::is_valid_identifier;
+use crate::token::{is_valid_identifier, Position};
 use crate::utils::StaticVec;
 
 #[cfg(feature = "internals")]
@@ -112,12 +112,44 @@ impl<'a> State<'a> {
     }
 }
 
+/// Is an expression a constant that can be passed to a function during optimization?
+///
+/// Function pointers (including closures) are not, as calling a function with them may run
+/// script code.
+fn is_foldable(expr: &Expr) -> bool {
+    match expr {
+        Expr::Expr(x) => is_foldable(x),
+        Expr::FnPointer(_) => false,
+        Expr::Array(x) => x.0.iter().all(is_foldable),
+        Expr::Map(x) => x.0.iter().all(|(_, v)| is_foldable(v)),
+        expr => expr.is_constant(),
+    }
+}
+
+/// Built-in functions that return different results on each call, even with the same arguments,
+/// and so must never be evaluated during optimization.
+const IMPURE_FUNCTIONS: &[&str] = &[
+    "rand",
+    "rand_int",
+    "rand_float",
+    "shuffle",
+    "sample",
+    "choice",
+    "timestamp",
+    "now",
+];
+
 /// Call a registered function
 fn call_fn_with_constant_arguments(
     state: &State,
     fn_name: &str,
     arg_values: &mut [Dynamic],
+    pos: Position,
 ) -> Option<Dynamic> {
+    if IMPURE_FUNCTIONS.contains(&fn_name) {
+        return None;
+    }
+
     // Search built-in's and external functions
     let hash_fn = calc_fn_hash(
         empty(),
@@ -130,7 +162,7 @@ fn call_fn_with_constant_arguments(
         .engine
         .call_fn_raw(
             &mut Scope::new(),
-            &mut Imports::new(),
+            &Imports::new(),
             &mut Default::default(),
             state.lib,
             fn_name,
@@ -139,6 +171,7 @@ fn call_fn_with_constant_arguments(
             false,
             false,
             None,
+            pos,
             0,
         )
         .map(|(v, _)| Some(v))
@@ -550,14 +583,14 @@ fn optimize_expr(expr: Expr, state: &mut State) -> Expr {
         Expr::FnCall(mut x)
                 if x.1.is_none() // Non-qualified
                 && state.optimization_level == OptimizationLevel::Full // full optimizations
-                && x.3.iter().all(|expr| expr.is_constant()) // all arguments are constants
+                && x.3.iter().all(is_foldable) // all arguments are constants
         => {
             let ((name, _, pos), _, _, args, def_value) = x.as_mut();
 
             // First search in functions lib (can override built-in)
             // Cater for both normal function call style and method call style (one additional arguments)
             #[cfg(not(feature = "no_function"))]
-            let has_script_fn = state.lib.iter_fn().find(|(_, _, _, f)| {
+            let has_script_fn = state.lib.iter_fn().find(|(_, _, _, f, _)| {
                 if !f.is_script() { return false; }
                 let fn_def = f.get_fn_def();
                 &fn_def.name == name && (args.len()..=args.len() + 1).contains(&fn_def.params.len())
@@ -582,7 +615,7 @@ fn optimize_expr(expr: Expr, state: &mut State) -> Expr {
                 ""
             };
 
-            call_fn_with_constant_arguments(&state, name, arg_values.as_mut())
+            call_fn_with_constant_arguments(&state, name, arg_values.as_mut(), *pos)
                 .or_else(|| {
                     if !arg_for_type_of.is_empty() {
                         // Handle `type_of()`
@@ -727,6 +760,15 @@ pub fn optimize_into_ast(
     #[cfg(feature = "no_optimize")]
     const level: OptimizationLevel = OptimizationLevel::None;
 
+    // Dead code must be kept when collecting coverage
+    #[cfg(not(feature = "no_optimize"))]
+    #[cfg(not(feature = "no_std"))]
+    let level = if engine.coverage.is_some() {
+        OptimizationLevel::None
+    } else {
+        level
+    };
+
     #[cfg(not(feature = "no_function"))]
     let lib = {
         let mut module = Module::new();
@@ -744,6 +786,9 @@ pub fn optimize_into_ast(
                         body: Default::default(),
                         params: fn_def.params.clone(),
                         pos: fn_def.pos,
+                        source: fn_def.source.clone(),
+                        #[cfg(not(feature = "no_module"))]
+                        env: fn_def.env.clone(),
                     }
                     .into()
                 })
@@ -1,14 +1,186 @@
 #![cfg(not(feature = "no_index"))]
 
-use crate::any::{Dynamic, Variant};
+use super::check_array_size;
+
+use crate::any::{Dynamic, Union, Variant};
 use crate::def_package;
-use crate::engine::{Array, Engine};
-use crate::module::{FuncReturn, Module};
+use crate::engine::Array;
+
+#[cfg(not(feature = "no_object"))]
+use crate::engine::Map;
+use crate::fn_native::{FnPtr, NativeCallContext};
+use crate::module::FuncReturn;
 use crate::parser::{ImmutableString, INT};
 use crate::result::EvalAltResult;
 use crate::token::Position;
 
-use crate::stdlib::{any::TypeId, boxed::Box, string::ToString};
+use crate::stdlib::{
+    any::{type_name, TypeId},
+    boxed::Box,
+    cmp::Ordering,
+    mem, slice,
+    string::ToString,
+    vec::Vec,
+};
+
+/// Wrap an error raised by a callback into an error of the calling array function.
+fn err_in_call(fn_name: &str, err: Box<EvalAltResult>) -> Box<EvalAltResult> {
+    Box::new(EvalAltResult::ErrorInFunctionCall(
+        fn_name.to_string(),
+        err,
+        Position::none(),
+    ))
+}
+
+/// Call a function pointer with `args`.
+/// If no function takes that many parameters, call it again with the element index appended.
+fn call_with_index(
+    context: &NativeCallContext,
+    fn_ptr: &FnPtr,
+    fn_name: &str,
+    args: &[Dynamic],
+    index: usize,
+) -> FuncReturn<Dynamic> {
+    fn_ptr
+        .call_with_context(context, None, args.to_vec())
+        .or_else(|err| match *err {
+            EvalAltResult::ErrorFunctionNotFound(ref sig, _)
+                if sig.starts_with(fn_ptr.fn_name()) =>
+            {
+                let mut args = args.to_vec();
+                args.push((index as INT).into());
+                fn_ptr.call_with_context(context, None, args)
+            }
+            _ => Err(err),
+        })
+        .map_err(|err| err_in_call(fn_name, err))
+}
+
+/// Call a predicate function pointer on an element, passing the element index if required.
+fn test_item(
+    context: &NativeCallContext,
+    fn_ptr: &FnPtr,
+    fn_name: &str,
+    item: &Dynamic,
+    index: usize,
+) -> FuncReturn<bool> {
+    Ok(
+        call_with_index(context, fn_ptr, fn_name, slice::from_ref(item), index)?
+            .as_bool()
+            .unwrap_or(false),
+    )
+}
+
+/// Compare two values with the `==` operator.
+/// Values that cannot be compared are not equal.
+fn values_equal(context: &NativeCallContext, x: &Dynamic, y: &Dynamic) -> FuncReturn<bool> {
+    match context.call_fn_dynamic_raw("==", false, &mut [&mut x.clone(), &mut y.clone()]) {
+        Ok(r) => Ok(r.as_bool().unwrap_or(false)),
+        Err(err) => match *err {
+            EvalAltResult::ErrorFunctionNotFound(_, _) => Ok(false),
+            _ => Err(err),
+        },
+    }
+}
+
+/// The natural order of two values.
+///
+/// Numbers, characters, strings and booleans of the same type compare by value,
+/// with `NaN` ordered after all other floating-point numbers.
+/// Values of different types are ordered by type name.
+fn natural_order(x: &Dynamic, y: &Dynamic) -> Ordering {
+    match (&x.0, &y.0) {
+        (Union::Int(x), Union::Int(y)) => x.cmp(y),
+        #[cfg(not(feature = "no_float"))]
+        (Union::Float(x), Union::Float(y)) => match (x.is_nan(), y.is_nan()) {
+            (false, false) => x.partial_cmp(y).unwrap(),
+            (nan_x, nan_y) => nan_x.cmp(&nan_y),
+        },
+        (Union::Char(x), Union::Char(y)) => x.cmp(y),
+        (Union::Str(x), Union::Str(y)) => x.cmp(y),
+        (Union::Bool(x), Union::Bool(y)) => x.cmp(y),
+        _ => x.type_name().cmp(y.type_name()),
+    }
+}
+
+/// Fold the items of an array into an accumulated value, starting from an initial value.
+fn fold_items(
+    context: &NativeCallContext,
+    fn_ptr: &FnPtr,
+    fn_name: &str,
+    list: &Array,
+    initial: Dynamic,
+) -> FuncReturn<Dynamic> {
+    let mut result = initial;
+
+    for (i, item) in list.iter().enumerate() {
+        result = call_with_index(context, fn_ptr, fn_name, &[result, item.clone()], i)?;
+    }
+    Ok(result)
+}
+
+/// Stable merge sort of an array, with a fallible comparison function.
+///
+/// Unlike `slice::sort_by`, this never panics if the comparisons are not consistent.
+/// Sorting stops at the first error, leaving the array unchanged.
+fn merge_sort(
+    list: &mut Array,
+    mut compare: impl FnMut(&Dynamic, &Dynamic) -> FuncReturn<Ordering>,
+) -> FuncReturn<()> {
+    let len = list.len();
+    let mut order: Vec<usize> = (0..len).collect();
+    let mut merged = Vec::with_capacity(len);
+    let mut width = 1;
+
+    // Sort the indices of the items bottom-up, merging runs of `width` items
+    while width < len {
+        merged.clear();
+
+        for start in (0..len).step_by(2 * width) {
+            let mid = (start + width).min(len);
+            let end = (start + 2 * width).min(len);
+            let (mut i, mut j) = (start, mid);
+
+            while i < mid && j < end {
+                if compare(&list[order[i]], &list[order[j]])? == Ordering::Greater {
+                    merged.push(order[j]);
+                    j += 1;
+                } else {
+                    merged.push(order[i]);
+                    i += 1;
+                }
+            }
+            merged.extend_from_slice(&order[i..mid]);
+            merged.extend_from_slice(&order[j..end]);
+        }
+
+        mem::swap(&mut order, &mut merged);
+        width *= 2;
+    }
+
+    let mut items: Vec<_> = list.drain(..).map(Some).collect();
+    list.extend(order.into_iter().map(|i| items[i].take().unwrap()));
+    Ok(())
+}
+
+/// Convert a starting position and a length into a range within an array of `size` elements.
+fn calc_range(size: usize, start: INT, len: INT) -> (usize, usize) {
+    let start = if start <= 0 {
+        0
+    } else if start as usize >= size {
+        size
+    } else {
+        start as usize
+    };
+    let len = if len <= 0 {
+        0
+    } else if len as usize > size - start {
+        size - start
+    } else {
+        len as usize
+    };
+    (start, start + len)
+}
 
 // Register array utility functions
 fn push<T: Variant + Clone>(list: &mut Array, item: T) -> FuncReturn<()> {
@@ -26,24 +198,14 @@ fn ins<T: Variant + Clone>(list: &mut Array, position: INT, item: T) -> FuncRetu
     Ok(())
 }
 fn pad<T: Variant + Clone>(
-    engine: &Engine,
-    _: &Module,
+    context: &NativeCallContext,
     args: &mut [&mut Dynamic],
 ) -> FuncReturn<()> {
     let len = *args[1].downcast_ref::<INT>().unwrap();
 
     // Check if array will be over max size limit
-    #[cfg(not(feature = "unchecked"))]
-    if engine.max_array_size > 0 && len > 0 && (len as usize) > engine.max_array_size {
-        return Err(Box::new(EvalAltResult::ErrorDataTooLarge(
-            "Size of array".to_string(),
-            engine.max_array_size,
-            len as usize,
-            Position::none(),
-        )));
-    }
-
     if len > 0 {
+        check_array_size(context.engine(), len as usize)?;
         let item = args[2].clone();
         let list = args[0].downcast_mut::<Array>().unwrap();
 
@@ -53,6 +215,225 @@ fn pad<T: Variant + Clone>(
     }
     Ok(())
 }
+fn contains(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<bool> {
+    let value = args[1].clone();
+    let list = args[0].downcast_ref::<Array>().unwrap();
+
+    for item in list {
+        if values_equal(context, item, &value)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn map(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Array> {
+    let fn_ptr = args[1].downcast_ref::<FnPtr>().unwrap().clone();
+    let list = args[0].downcast_ref::<Array>().unwrap();
+
+    list.iter()
+        .enumerate()
+        .map(|(i, item)| call_with_index(context, &fn_ptr, "map", slice::from_ref(item), i))
+        .collect()
+}
+fn filter(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Array> {
+    let fn_ptr = args[1].downcast_ref::<FnPtr>().unwrap().clone();
+    let list = args[0].downcast_ref::<Array>().unwrap();
+    let mut result = Array::new();
+
+    for (i, item) in list.iter().enumerate() {
+        if test_item(context, &fn_ptr, "filter", item, i)? {
+            result.push(item.clone());
+        }
+    }
+    Ok(result)
+}
+fn reduce(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Dynamic> {
+    let fn_ptr = args[1].downcast_ref::<FnPtr>().unwrap().clone();
+    let list = args[0].downcast_ref::<Array>().unwrap();
+    let mut items = list.iter().enumerate();
+
+    let mut result = match items.next() {
+        Some((_, item)) => item.clone(),
+        None => return Ok(().into()),
+    };
+    for (i, item) in items {
+        result = call_with_index(context, &fn_ptr, "reduce", &[result, item.clone()], i)?;
+    }
+    Ok(result)
+}
+fn reduce_with_initial(
+    context: &NativeCallContext,
+    args: &mut [&mut Dynamic],
+) -> FuncReturn<Dynamic> {
+    let fn_ptr = args[1].downcast_ref::<FnPtr>().unwrap().clone();
+    let initial = mem::take(args[2]);
+    let list = args[0].downcast_ref::<Array>().unwrap();
+
+    fold_items(context, &fn_ptr, "reduce", list, initial)
+}
+fn fold(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Dynamic> {
+    let fn_ptr = args[2].downcast_ref::<FnPtr>().unwrap().clone();
+    let initial = mem::take(args[1]);
+    let list = args[0].downcast_ref::<Array>().unwrap();
+
+    fold_items(context, &fn_ptr, "fold", list, initial)
+}
+fn some(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<bool> {
+    let fn_ptr = args[1].downcast_ref::<FnPtr>().unwrap().clone();
+    let list = args[0].downcast_ref::<Array>().unwrap();
+
+    for (i, item) in list.iter().enumerate() {
+        if test_item(context, &fn_ptr, "some", item, i)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+fn all(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<bool> {
+    let fn_ptr = args[1].downcast_ref::<FnPtr>().unwrap().clone();
+    let list = args[0].downcast_ref::<Array>().unwrap();
+
+    for (i, item) in list.iter().enumerate() {
+        if !test_item(context, &fn_ptr, "all", item, i)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+fn find(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Dynamic> {
+    let fn_ptr = args[1].downcast_ref::<FnPtr>().unwrap().clone();
+    let list = args[0].downcast_ref::<Array>().unwrap();
+
+    for (i, item) in list.iter().enumerate() {
+        if test_item(context, &fn_ptr, "find", item, i)? {
+            return Ok(item.clone());
+        }
+    }
+    Ok(().into())
+}
+fn index_of(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<INT> {
+    let fn_ptr = args[1].downcast_ref::<FnPtr>().unwrap().clone();
+    let list = args[0].downcast_ref::<Array>().unwrap();
+
+    for (i, item) in list.iter().enumerate() {
+        if test_item(context, &fn_ptr, "index_of", item, i)? {
+            return Ok(i as INT);
+        }
+    }
+    Ok(-1)
+}
+fn sort_with(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<()> {
+    let fn_ptr = args[1].downcast_ref::<FnPtr>().unwrap().clone();
+    let list = args[0].downcast_mut::<Array>().unwrap();
+
+    merge_sort(list, |x, y| {
+        fn_ptr
+            .call_with_context(context, None, [x.clone(), y.clone()])
+            .and_then(|v| {
+                v.as_int().map_err(|typ| {
+                    Box::new(EvalAltResult::ErrorMismatchOutputType(
+                        context.engine().map_type_name(type_name::<INT>()).into(),
+                        typ.into(),
+                        Position::none(),
+                    ))
+                })
+            })
+            .map(|v| v.cmp(&0))
+            .map_err(|err| err_in_call("sort", err))
+    })
+}
+/// Keep the items of an array for which `keep` is set, returning the other items.
+///
+/// All callbacks are run before the array is modified, so that it is unchanged on error.
+fn partition(list: &mut Array, keep: &[bool]) -> Array {
+    let mut kept = Array::with_capacity(list.len());
+    let mut removed = Array::new();
+
+    for (item, &keep) in mem::take(list).into_iter().zip(keep) {
+        if keep {
+            kept.push(item);
+        } else {
+            removed.push(item);
+        }
+    }
+    *list = kept;
+    removed
+}
+/// Flag the items of an array that are not duplicates of the previous kept item.
+fn dedup_flags(
+    list: &Array,
+    mut is_dup: impl FnMut(&Dynamic, &Dynamic) -> FuncReturn<bool>,
+) -> FuncReturn<Vec<bool>> {
+    let mut keep = Vec::with_capacity(list.len());
+    let mut last: Option<&Dynamic> = None;
+
+    for item in list.iter() {
+        let dup = match last {
+            Some(last) => is_dup(last, item)?,
+            None => false,
+        };
+        if !dup {
+            last = Some(item);
+        }
+        keep.push(!dup);
+    }
+    Ok(keep)
+}
+fn dedup(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<()> {
+    let list = args[0].downcast_mut::<Array>().unwrap();
+    let keep = dedup_flags(list, |last, item| values_equal(context, last, item))?;
+
+    partition(list, &keep);
+    Ok(())
+}
+fn dedup_with(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<()> {
+    let fn_ptr = args[1].downcast_ref::<FnPtr>().unwrap().clone();
+    let list = args[0].downcast_mut::<Array>().unwrap();
+    let keep = dedup_flags(list, |last, item| {
+        Ok(fn_ptr
+            .call_with_context(context, None, [last.clone(), item.clone()])
+            .map_err(|err| err_in_call("dedup", err))?
+            .as_bool()
+            .unwrap_or(false))
+    })?;
+
+    partition(list, &keep);
+    Ok(())
+}
+fn drain(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Array> {
+    let fn_ptr = args[1].downcast_ref::<FnPtr>().unwrap().clone();
+    let list = args[0].downcast_mut::<Array>().unwrap();
+    let mut keep = Vec::with_capacity(list.len());
+
+    for (i, item) in list.iter().enumerate() {
+        keep.push(!test_item(context, &fn_ptr, "drain", item, i)?);
+    }
+    Ok(partition(list, &keep))
+}
+fn retain(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Array> {
+    let fn_ptr = args[1].downcast_ref::<FnPtr>().unwrap().clone();
+    let list = args[0].downcast_mut::<Array>().unwrap();
+    let mut keep = Vec::with_capacity(list.len());
+
+    for (i, item) in list.iter().enumerate() {
+        keep.push(test_item(context, &fn_ptr, "retain", item, i)?);
+    }
+    Ok(partition(list, &keep))
+}
+fn splice(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<()> {
+    let start = *args[1].downcast_ref::<INT>().unwrap();
+    let len = *args[2].downcast_ref::<INT>().unwrap();
+    let replace = mem::take(args[3]).cast::<Array>();
+    let list = args[0].downcast_mut::<Array>().unwrap();
+    let (start, end) = calc_range(list.len(), start, len);
+
+    // Check if array will be over max size limit
+    check_array_size(context.engine(), list.len() - (end - start) + replace.len())?;
+
+    list.splice(start..end, replace);
+    Ok(())
+}
 
 macro_rules! reg_op {
     ($lib:expr, $op:expr, $func:ident, $($par:ty),*) => {
@@ -64,6 +445,30 @@ macro_rules! reg_tri {
         $( $lib.set_fn_3_mut($op, $func::<$par>); )*
     };
 }
+macro_rules! reg_contains {
+    ($lib:expr, $op:expr, $func:ident, $($par:ty),*) => {
+        $({
+            $lib.set_raw_fn($op,
+                &[TypeId::of::<Array>(), TypeId::of::<$par>()],
+                $func
+            );
+        })*
+    };
+}
+macro_rules! reg_fold {
+    ($lib:expr, $($par:ty),*) => {
+        $({
+            $lib.set_raw_fn("reduce",
+                &[TypeId::of::<Array>(), TypeId::of::<FnPtr>(), TypeId::of::<$par>()],
+                reduce_with_initial
+            );
+            $lib.set_raw_fn("fold",
+                &[TypeId::of::<Array>(), TypeId::of::<$par>(), TypeId::of::<FnPtr>()],
+                fold
+            );
+        })*
+    };
+}
 macro_rules! reg_pad {
     ($lib:expr, $op:expr, $func:ident, $($par:ty),*) => {
         $({
@@ -80,6 +485,11 @@ def_package!(crate:BasicArrayPackage:"Basic array utilities.", lib, {
     reg_op!(lib, "push", push, INT, bool, char, ImmutableString, Array, ());
     reg_pad!(lib, "pad", pad, INT, bool, char, ImmutableString, Array, ());
     reg_tri!(lib, "insert", ins, INT, bool, char, ImmutableString, Array, ());
+    reg_contains!(lib, "contains", contains, INT, bool, char, ImmutableString, Array, ());
+    reg_fold!(lib, INT, bool, char, ImmutableString, Array, ());
+
+    #[cfg(not(feature = "no_object"))]
+    reg_fold!(lib, Map);
 
     lib.set_fn_2_mut("append", |x: &mut Array, y: Array| {
         x.extend(y);
@@ -103,12 +513,16 @@ def_package!(crate:BasicArrayPackage:"Basic array utilities.", lib, {
         reg_op!(lib, "push", push, i8, u8, i16, u16, i32, i64, u32, u64);
         reg_pad!(lib, "pad", pad, i8, u8, i16, u16, i32, u32, i64, u64);
         reg_tri!(lib, "insert", ins, i8, u8, i16, u16, i32, i64, u32, u64);
+        reg_contains!(lib, "contains", contains, i8, u8, i16, u16, i32, i64, u32, u64);
+        reg_fold!(lib, i8, u8, i16, u16, i32, i64, u32, u64);
 
         #[cfg(not(target_arch = "wasm32"))]
         {
             reg_op!(lib, "push", push, i128, u128);
             reg_pad!(lib, "pad", pad, i128, u128);
             reg_tri!(lib, "insert", ins, i128, u128);
+            reg_contains!(lib, "contains", contains, i128, u128);
+            reg_fold!(lib, i128, u128);
         }
     }
 
@@ -117,6 +531,8 @@ def_package!(crate:BasicArrayPackage:"Basic array utilities.", lib, {
         reg_op!(lib, "push", push, f32, f64);
         reg_pad!(lib, "pad", pad, f32, f64);
         reg_tri!(lib, "insert", ins, f32, f64);
+        reg_contains!(lib, "contains", contains, f32, f64);
+        reg_fold!(lib, f32, f64);
     }
 
     lib.set_fn_1_mut(
@@ -164,6 +580,77 @@ def_package!(crate:BasicArrayPackage:"Basic array utilities.", lib, {
         },
     );
 
+    lib.set_fn_1_mut("reverse", |list: &mut Array| {
+        list.reverse();
+        Ok(())
+    });
+    lib.set_fn_1_mut("sort", |list: &mut Array| {
+        list.sort_by(natural_order);
+        Ok(())
+    });
+    lib.set_fn_3_mut(
+        "drain",
+        |list: &mut Array, start: INT, len: INT| {
+            let (start, end) = calc_range(list.len(), start, len);
+            Ok(list.drain(start..end).collect::<Array>())
+        },
+    );
+    lib.set_fn_3_mut(
+        "retain",
+        |list: &mut Array, start: INT, len: INT| {
+            let (start, end) = calc_range(list.len(), start, len);
+            let mut removed = list.split_off(end);
+            let kept = list.split_off(start);
+            removed.splice(0..0, list.drain(..));
+            *list = kept;
+            Ok(removed)
+        },
+    );
+    lib.set_fn_2_mut(
+        "extract",
+        |list: &mut Array, start: INT| {
+            let (start, _) = calc_range(list.len(), start, 0);
+            Ok(list[start..].to_vec())
+        },
+    );
+    lib.set_fn_3_mut(
+        "extract",
+        |list: &mut Array, start: INT, len: INT| {
+            let (start, end) = calc_range(list.len(), start, len);
+            Ok(list[start..end].to_vec())
+        },
+    );
+    lib.set_fn_2_mut(
+        "chunks",
+        |list: &mut Array, size: INT| {
+            Ok(if size <= 0 {
+                Array::new()
+            } else {
+                list.chunks(size as usize)
+                    .map(|chunk| chunk.to_vec().into())
+                    .collect::<Array>()
+            })
+        },
+    );
+
+    // Register functions taking callbacks
+    let arr = TypeId::of::<Array>();
+    let fn_ptr = TypeId::of::<FnPtr>();
+
+    lib.set_raw_fn("map", &[arr, fn_ptr], map);
+    lib.set_raw_fn("filter", &[arr, fn_ptr], filter);
+    lib.set_raw_fn("reduce", &[arr, fn_ptr], reduce);
+    lib.set_raw_fn("some", &[arr, fn_ptr], some);
+    lib.set_raw_fn("all", &[arr, fn_ptr], all);
+    lib.set_raw_fn("find", &[arr, fn_ptr], find);
+    lib.set_raw_fn("index_of", &[arr, fn_ptr], index_of);
+    lib.set_raw_fn("sort", &[arr, fn_ptr], sort_with);
+    lib.set_raw_fn("dedup", &[arr], dedup);
+    lib.set_raw_fn("dedup", &[arr, fn_ptr], dedup_with);
+    lib.set_raw_fn("drain", &[arr, fn_ptr], drain);
+    lib.set_raw_fn("retain", &[arr, fn_ptr], retain);
+    lib.set_raw_fn("splice", &[arr, TypeId::of::<INT>(), TypeId::of::<INT>(), arr], splice);
+
     // Register array iterator
     lib.set_iter(
         TypeId::of::<Array>(),
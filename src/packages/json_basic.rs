@@ -0,0 +1,46 @@
+use crate::any::Dynamic;
+use crate::def_package;
+use crate::fn_native::NativeCallContext;
+use crate::json::{read_json, write_json, JsonLimits};
+use crate::module::FuncReturn;
+use crate::parser::{ImmutableString, INT};
+
+#[cfg(not(feature = "no_float"))]
+use crate::parser::FLOAT;
+
+#[cfg(not(feature = "no_index"))]
+use crate::engine::Array;
+
+#[cfg(not(feature = "no_object"))]
+use crate::engine::Map;
+
+use crate::stdlib::any::TypeId;
+
+fn to_json(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<ImmutableString> {
+    write_json(args[0], JsonLimits::from_engine(context.engine())).map(Into::into)
+}
+fn parse_json(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Dynamic> {
+    let json = args[0].as_str().unwrap();
+    read_json(json, JsonLimits::from_engine(context.engine()))
+}
+
+macro_rules! reg_to_json {
+    ($lib:expr, $op:expr, $func:ident, $($par:ty),*) => {
+        $( $lib.set_raw_fn($op, &[TypeId::of::<$par>()], $func); )*
+    };
+}
+
+def_package!(crate:BasicJsonPackage:"Basic JSON encoding and decoding.", lib, {
+    reg_to_json!(lib, "to_json", to_json, INT, bool, char, ImmutableString, ());
+
+    #[cfg(not(feature = "no_float"))]
+    reg_to_json!(lib, "to_json", to_json, FLOAT);
+
+    #[cfg(not(feature = "no_index"))]
+    reg_to_json!(lib, "to_json", to_json, Array);
+
+    #[cfg(not(feature = "no_object"))]
+    reg_to_json!(lib, "to_json", to_json, Map);
+
+    lib.set_raw_fn("parse_json", &[TypeId::of::<ImmutableString>()], parse_json);
+});
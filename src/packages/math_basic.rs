@@ -1,25 +1,221 @@
 use crate::def_package;
+use crate::module::FuncReturn;
 use crate::parser::INT;
 use crate::result::EvalAltResult;
 use crate::token::Position;
 
+#[cfg(not(feature = "unchecked"))]
+use crate::packages::arithmetic::pow_i_i;
+
+#[cfg(feature = "unchecked")]
+use crate::packages::arithmetic::pow_i_i_u;
+
 #[cfg(not(feature = "no_float"))]
 use crate::parser::FLOAT;
 
+#[cfg(not(feature = "no_float"))]
+use crate::packages::arithmetic::pow_f_f;
+
+#[cfg(not(feature = "no_float"))]
+#[cfg(not(feature = "unchecked"))]
+use crate::packages::arithmetic::pow_f_i;
+
+#[cfg(not(feature = "no_float"))]
+#[cfg(feature = "unchecked")]
+use crate::packages::arithmetic::pow_f_i_u;
+
 #[cfg(not(feature = "no_float"))]
 #[cfg(feature = "no_std")]
 use num_traits::*;
 
-use crate::stdlib::{boxed::Box, format, i32, i64};
+use crate::stdlib::{boxed::Box, cmp::Ordering, fmt::Display, format, i32, i64};
+
+#[cfg(not(feature = "no_float"))]
+use crate::stdlib::f64::consts;
 
 #[cfg(feature = "only_i32")]
 pub const MAX_INT: INT = i32::MAX;
 #[cfg(not(feature = "only_i32"))]
 pub const MAX_INT: INT = i64::MAX;
 
+/// Number of bits in an `INT`.
+const INT_BITS: INT = INT::BITS as INT;
+
+// Clamp a value between two limits
+fn clamp<T: Display + PartialOrd>(x: T, lo: T, hi: T) -> FuncReturn<T> {
+    match lo.partial_cmp(&hi) {
+        Some(Ordering::Less) | Some(Ordering::Equal) => (),
+        _ => {
+            return Err(Box::new(EvalAltResult::ErrorRuntime(
+                format!("Invalid range for clamp: {} > {}", lo, hi),
+                Position::none(),
+            )))
+        }
+    }
+
+    Ok(if x < lo {
+        lo
+    } else if x > hi {
+        hi
+    } else {
+        x
+    })
+}
+// Magnitude of the greatest common divisor - Euclid's algorithm
+fn gcd_abs(x: INT, y: INT) -> u128 {
+    let (mut a, mut b) = ((x as i128).unsigned_abs(), (y as i128).unsigned_abs());
+
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+// Greatest common divisor, always non-negative
+fn gcd(x: INT, y: INT) -> FuncReturn<INT> {
+    let result = gcd_abs(x, y);
+
+    #[cfg(not(feature = "unchecked"))]
+    if result > MAX_INT as u128 {
+        return Err(Box::new(EvalAltResult::ErrorArithmetic(
+            format!("Integer overflow: gcd({}, {})", x, y),
+            Position::none(),
+        )));
+    }
+
+    Ok(result as INT)
+}
+// Least common multiple, always non-negative
+fn lcm(x: INT, y: INT) -> FuncReturn<INT> {
+    if x == 0 || y == 0 {
+        return Ok(0);
+    }
+
+    let result = (x as i128).unsigned_abs() / gcd_abs(x, y) * (y as i128).unsigned_abs();
+
+    #[cfg(not(feature = "unchecked"))]
+    if result > MAX_INT as u128 {
+        return Err(Box::new(EvalAltResult::ErrorArithmetic(
+            format!("Integer overflow: lcm({}, {})", x, y),
+            Position::none(),
+        )));
+    }
+
+    Ok(result as INT)
+}
+// Integer square root, rounded down
+fn sqrt_i(x: INT) -> FuncReturn<INT> {
+    if x < 0 {
+        return Err(Box::new(EvalAltResult::ErrorArithmetic(
+            format!("Square root of a negative number: sqrt({})", x),
+            Position::none(),
+        )));
+    }
+
+    if x < 2 {
+        return Ok(x);
+    }
+
+    // Newton's method, starting from an over-estimate
+    let n = x as u64;
+    let mut root = n;
+    let mut next = n / 2;
+
+    while next < root {
+        root = next;
+        next = (root + n / root) / 2;
+    }
+    Ok(root as INT)
+}
+// Test a bit
+fn get_bit(x: INT, bit: INT) -> FuncReturn<bool> {
+    if !(0..INT_BITS).contains(&bit) {
+        return Err(Box::new(EvalAltResult::ErrorArithmetic(
+            format!("Bit index out of range: get_bit({}, {})", x, bit),
+            Position::none(),
+        )));
+    }
+
+    Ok(x & (1 << bit) != 0)
+}
+// Set or clear a bit
+fn set_bit(x: &mut INT, bit: INT, value: bool) -> FuncReturn<()> {
+    if !(0..INT_BITS).contains(&bit) {
+        return Err(Box::new(EvalAltResult::ErrorArithmetic(
+            format!("Bit index out of range: set_bit({}, {}, {})", x, bit, value),
+            Position::none(),
+        )));
+    }
+
+    if value {
+        *x |= 1 << bit;
+    } else {
+        *x &= !(1 << bit);
+    }
+    Ok(())
+}
+// Extract a range of bits, starting from the lowest bit
+fn get_bits(x: INT, start: INT, len: INT) -> FuncReturn<INT> {
+    if !(0..=INT_BITS).contains(&start) || !(0..=INT_BITS - start).contains(&len) {
+        return Err(Box::new(EvalAltResult::ErrorArithmetic(
+            format!("Bit range out of range: get_bits({}, {}, {})", x, start, len),
+            Position::none(),
+        )));
+    }
+
+    let mask = (1_u128 << len) - 1;
+    Ok(((x as u128 >> start) & mask) as INT)
+}
+
 def_package!(crate:BasicMathPackage:"Basic mathematic functions.", lib, {
+    // Integer functions
+    lib.set_fn_2("min", |x: INT, y: INT| Ok(x.min(y)));
+    lib.set_fn_2("max", |x: INT, y: INT| Ok(x.max(y)));
+    lib.set_fn_3("clamp", clamp::<INT>);
+    lib.set_fn_1("signum", |x: INT| Ok(x.signum()));
+    lib.set_fn_1("sqrt", sqrt_i);
+    lib.set_fn_2("gcd", gcd);
+    lib.set_fn_2("lcm", lcm);
+
+    #[cfg(not(feature = "unchecked"))]
+    lib.set_fn_2("pow", pow_i_i);
+
+    #[cfg(feature = "unchecked")]
+    lib.set_fn_2("pow", pow_i_i_u);
+
+    // Bit manipulation
+    lib.set_fn_1("count_ones", |x: INT| Ok(x.count_ones() as INT));
+    lib.set_fn_1("count_zeros", |x: INT| Ok(x.count_zeros() as INT));
+    lib.set_fn_1("leading_zeros", |x: INT| Ok(x.leading_zeros() as INT));
+    lib.set_fn_1("trailing_zeros", |x: INT| Ok(x.trailing_zeros() as INT));
+    lib.set_fn_2("rotate_left", |x: INT, n: INT| Ok(x.rotate_left(n.rem_euclid(INT_BITS) as u32)));
+    lib.set_fn_2("rotate_right", |x: INT, n: INT| Ok(x.rotate_right(n.rem_euclid(INT_BITS) as u32)));
+    lib.set_fn_2("get_bit", get_bit);
+    lib.set_fn_3_mut("set_bit", set_bit);
+    lib.set_fn_3("get_bits", get_bits);
+
     #[cfg(not(feature = "no_float"))]
     {
+        // Constants
+        lib.set_fn_0("PI", || Ok(consts::PI));
+        lib.set_fn_0("E", || Ok(consts::E));
+
+        lib.set_fn_2("min", |x: FLOAT, y: FLOAT| Ok(x.min(y)));
+        lib.set_fn_2("max", |x: FLOAT, y: FLOAT| Ok(x.max(y)));
+        lib.set_fn_3("clamp", clamp::<FLOAT>);
+        lib.set_fn_1("signum", |x: FLOAT| Ok(x.signum()));
+        lib.set_fn_2("pow", pow_f_f);
+
+        #[cfg(not(feature = "unchecked"))]
+        lib.set_fn_2("pow", pow_f_i);
+
+        #[cfg(feature = "unchecked")]
+        lib.set_fn_2("pow", pow_f_i_u);
+
+        lib.set_fn_2("hypot", |x: FLOAT, y: FLOAT| Ok(x.hypot(y)));
+        lib.set_fn_2("atan2", |y: FLOAT, x: FLOAT| Ok(y.atan2(x).to_degrees()));
+
         // Advanced math functions
         lib.set_fn_1("sin", |x: FLOAT| Ok(x.to_radians().sin()));
         lib.set_fn_1("cos", |x: FLOAT| Ok(x.to_radians().cos()));
@@ -1,22 +1,35 @@
 //! Module containing all built-in _packages_ available to Rhai, plus facilities to define custom packages.
 
+use crate::engine::Engine;
 use crate::fn_native::{CallableFunction, IteratorFn, Shared};
-use crate::module::Module;
+use crate::module::{FuncReturn, Module};
 use crate::utils::StaticVec;
 
+#[cfg(not(feature = "unchecked"))]
+use crate::result::EvalAltResult;
+#[cfg(not(feature = "unchecked"))]
+use crate::token::Position;
+
 use crate::stdlib::any::TypeId;
 
+#[cfg(not(feature = "unchecked"))]
+use crate::stdlib::{boxed::Box, string::ToString};
+
 pub(crate) mod arithmetic;
 mod array_basic;
 mod eval;
 mod fn_basic;
 mod iter_basic;
+mod json_basic;
 mod logic;
 mod map_basic;
 mod math_basic;
 mod pkg_core;
 mod pkg_std;
+mod random_basic;
+mod regex_basic;
 mod string_basic;
+mod string_format;
 mod string_more;
 mod time_basic;
 
@@ -27,12 +40,19 @@ pub use eval::EvalPackage;
 #[cfg(not(feature = "no_function"))]
 pub use fn_basic::BasicFnPackage;
 pub use iter_basic::BasicIteratorPackage;
+pub use json_basic::BasicJsonPackage;
 pub use logic::LogicPackage;
 #[cfg(not(feature = "no_object"))]
 pub use map_basic::BasicMapPackage;
 pub use math_basic::BasicMathPackage;
 pub use pkg_core::CorePackage;
 pub use pkg_std::StandardPackage;
+pub use random_basic::RandomPackage;
+pub(crate) use random_basic::RandomState;
+#[cfg(feature = "regex")]
+pub use regex_basic::RegexPackage;
+#[cfg(feature = "regex")]
+pub(crate) use regex_basic::RegexCache;
 pub use string_basic::BasicStringPackage;
 pub use string_more::MoreStringPackage;
 #[cfg(not(feature = "no_std"))]
@@ -73,6 +93,10 @@ impl PackagesCollection {
             .find(|f| f.is_some())
             .flatten()
     }
+    /// Get an iterator over all the `PackageLibrary` instances in the `PackagesCollection`.
+    pub fn iter(&self) -> impl Iterator<Item = &PackageLibrary> {
+        self.0.iter()
+    }
     /// Does the specified TypeId iterator exist in the `PackagesCollection`?
     pub fn contains_iter(&self, id: TypeId) -> bool {
         self.0.iter().any(|p| p.contains_iter(id))
@@ -87,6 +111,37 @@ impl PackagesCollection {
     }
 }
 
+/// Check whether a string is within the maximum allowed length.
+#[cfg_attr(feature = "unchecked", allow(unused_variables))]
+pub(crate) fn check_string_size(engine: &Engine, len: usize) -> FuncReturn<()> {
+    #[cfg(not(feature = "unchecked"))]
+    if engine.max_string_size > 0 && len > engine.max_string_size {
+        return Err(Box::new(EvalAltResult::ErrorDataTooLarge(
+            "Length of string".to_string(),
+            engine.max_string_size,
+            len,
+            Position::none(),
+        )));
+    }
+    Ok(())
+}
+
+/// Check whether an array of `size` elements is within the maximum allowed size.
+#[cfg(not(feature = "no_index"))]
+#[cfg_attr(feature = "unchecked", allow(unused_variables))]
+pub(crate) fn check_array_size(engine: &Engine, size: usize) -> FuncReturn<()> {
+    #[cfg(not(feature = "unchecked"))]
+    if engine.max_array_size > 0 && size > engine.max_array_size {
+        return Err(Box::new(EvalAltResult::ErrorDataTooLarge(
+            "Size of array".to_string(),
+            engine.max_array_size,
+            size,
+            Position::none(),
+        )));
+    }
+    Ok(())
+}
+
 /// Macro that makes it easy to define a _package_ (which is basically a shared module)
 /// and register functions into it.
 ///
@@ -1,5 +1,6 @@
 #[cfg(not(feature = "no_index"))]
 use super::array_basic::BasicArrayPackage;
+use super::json_basic::BasicJsonPackage;
 #[cfg(not(feature = "no_object"))]
 use super::map_basic::BasicMapPackage;
 use super::math_basic::BasicMathPackage;
@@ -20,4 +21,5 @@ def_package!(crate:StandardPackage:"_Standard_ package containing all built-in f
     #[cfg(not(feature = "no_std"))]
     BasicTimePackage::init(lib);
     MoreStringPackage::init(lib);
+    BasicJsonPackage::init(lib);
 });
@@ -0,0 +1,198 @@
+use crate::any::Dynamic;
+use crate::def_package;
+use crate::fn_native::NativeCallContext;
+use crate::module::FuncReturn;
+use crate::parser::INT;
+use crate::result::EvalAltResult;
+use crate::token::Position;
+
+#[cfg(not(feature = "no_float"))]
+use crate::parser::FLOAT;
+
+#[cfg(not(feature = "no_index"))]
+use crate::engine::Array;
+
+use crate::stdlib::{any::TypeId, boxed::Box, format};
+
+#[cfg(not(feature = "no_index"))]
+use crate::stdlib::vec::Vec;
+
+#[cfg(not(feature = "sync"))]
+use crate::stdlib::cell::Cell;
+
+#[cfg(feature = "sync")]
+use crate::stdlib::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(target_arch = "wasm32"))]
+use crate::stdlib::time::SystemTime;
+
+/// Increment of the SplitMix64 generator.
+const GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Seed used when there is no system clock to seed from.
+const DEFAULT_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+/// State of the random number generator of an `Engine`.
+///
+/// The generator is SplitMix64, which is small, fast and has a single word of state.
+/// It is _not_ suitable for cryptographic purposes.
+#[derive(Debug)]
+pub(crate) struct RandomState {
+    #[cfg(not(feature = "sync"))]
+    state: Cell<u64>,
+    #[cfg(feature = "sync")]
+    state: AtomicU64,
+}
+
+impl Default for RandomState {
+    /// Seed from the system clock, if available.
+    fn default() -> Self {
+        #[cfg(not(feature = "no_std"))]
+        #[cfg(not(target_arch = "wasm32"))]
+        let seed = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(DEFAULT_SEED, |d| d.as_nanos() as u64);
+
+        #[cfg(any(feature = "no_std", target_arch = "wasm32"))]
+        let seed = DEFAULT_SEED;
+
+        Self::new(seed)
+    }
+}
+
+impl RandomState {
+    /// Create a generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            #[cfg(not(feature = "sync"))]
+            state: Cell::new(seed),
+            #[cfg(feature = "sync")]
+            state: AtomicU64::new(seed),
+        }
+    }
+    /// Generate a random `u64`.
+    fn next_u64(&self) -> u64 {
+        #[cfg(not(feature = "sync"))]
+        let z = {
+            let z = self.state.get().wrapping_add(GAMMA);
+            self.state.set(z);
+            z
+        };
+        #[cfg(feature = "sync")]
+        let z = self.state.fetch_add(GAMMA, Ordering::Relaxed).wrapping_add(GAMMA);
+
+        let z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        let z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    /// Generate a random number in `0..n`, without bias. `n` must not be zero.
+    fn next_below(&self, n: u64) -> u64 {
+        // Lemire's method: reject the few values that would make some results more likely
+        let threshold = n.wrapping_neg() % n;
+
+        loop {
+            let m = self.next_u64() as u128 * n as u128;
+
+            if m as u64 >= threshold {
+                return (m >> 64) as u64;
+            }
+        }
+    }
+    /// Generate a random index into a collection of a particular length, which must not be zero.
+    #[cfg(not(feature = "no_index"))]
+    fn next_index(&self, len: usize) -> usize {
+        self.next_below(len as u64) as usize
+    }
+}
+
+fn rand(context: &NativeCallContext, _: &mut [&mut Dynamic]) -> FuncReturn<INT> {
+    Ok(context.engine().random.next_u64() as INT)
+}
+fn rand_int(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<INT> {
+    let lo = args[0].as_int().unwrap();
+    let hi = args[1].as_int().unwrap();
+
+    if lo > hi {
+        return Err(Box::new(EvalAltResult::ErrorRuntime(
+            format!("Invalid range for rand_int: {} > {}", lo, hi),
+            Position::none(),
+        )));
+    }
+
+    let random = &context.engine().random;
+    let span = (hi as i128 - lo as i128) as u128 + 1;
+
+    if span > u64::MAX as u128 {
+        // The full range of INT
+        Ok(random.next_u64() as INT)
+    } else {
+        Ok((lo as i128 + random.next_below(span as u64) as i128) as INT)
+    }
+}
+#[cfg(not(feature = "no_float"))]
+fn rand_float(context: &NativeCallContext, _: &mut [&mut Dynamic]) -> FuncReturn<FLOAT> {
+    // 53 random bits, the precision of a double, scaled into 0.0..1.0
+    let bits = context.engine().random.next_u64() >> 11;
+    Ok(bits as FLOAT / (1_u64 << 53) as FLOAT)
+}
+#[cfg(not(feature = "no_index"))]
+fn shuffle(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<()> {
+    let random = &context.engine().random;
+    let list = args[0].downcast_mut::<Array>().unwrap();
+
+    // Fisher-Yates
+    for i in (1..list.len()).rev() {
+        list.swap(i, random.next_index(i + 1));
+    }
+    Ok(())
+}
+#[cfg(not(feature = "no_index"))]
+fn sample(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Array> {
+    let random = &context.engine().random;
+    let n = args[1].as_int().unwrap();
+    let list = args[0].downcast_ref::<Array>().unwrap();
+
+    let n = if n <= 0 {
+        0
+    } else if n as usize > list.len() {
+        list.len()
+    } else {
+        n as usize
+    };
+
+    // Partial Fisher-Yates on the indices
+    let mut indices: Vec<_> = (0..list.len()).collect();
+
+    for i in 0..n {
+        let j = i + random.next_index(list.len() - i);
+        indices.swap(i, j);
+    }
+
+    Ok(indices[..n].iter().map(|&i| list[i].clone()).collect())
+}
+#[cfg(not(feature = "no_index"))]
+fn choice(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Dynamic> {
+    let list = args[0].downcast_ref::<Array>().unwrap();
+
+    if list.is_empty() {
+        Ok(().into())
+    } else {
+        Ok(list[context.engine().random.next_index(list.len())].clone())
+    }
+}
+
+def_package!(crate:RandomPackage:"Random number generation, seedable via `Engine::set_random_seed`.", lib, {
+    lib.set_raw_fn("rand", &[], rand);
+    lib.set_raw_fn("rand_int", &[TypeId::of::<INT>(), TypeId::of::<INT>()], rand_int);
+
+    #[cfg(not(feature = "no_float"))]
+    lib.set_raw_fn("rand_float", &[], rand_float);
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        lib.set_raw_fn("shuffle", &[TypeId::of::<Array>()], shuffle);
+        lib.set_raw_fn("sample", &[TypeId::of::<Array>(), TypeId::of::<INT>()], sample);
+        lib.set_raw_fn("choice", &[TypeId::of::<Array>()], choice);
+    }
+});
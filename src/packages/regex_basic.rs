@@ -0,0 +1,208 @@
+#![cfg(feature = "regex")]
+
+use super::check_string_size;
+#[cfg(not(feature = "no_index"))]
+use super::check_array_size;
+
+use crate::any::Dynamic;
+use crate::def_package;
+use crate::engine::{FN_TO_STRING, KEYWORD_DEBUG, KEYWORD_PRINT};
+use crate::fn_native::NativeCallContext;
+use crate::module::FuncReturn;
+use crate::parser::ImmutableString;
+use crate::result::EvalAltResult;
+use crate::token::Position;
+
+#[cfg(not(feature = "no_index"))]
+use crate::engine::Array;
+
+#[cfg(not(feature = "no_object"))]
+use crate::engine::Map;
+
+use crate::stdlib::{
+    any::TypeId,
+    boxed::Box,
+    collections::HashMap,
+    format,
+    string::{String, ToString},
+};
+
+#[cfg(not(feature = "sync"))]
+use crate::stdlib::cell::RefCell;
+
+#[cfg(feature = "sync")]
+use crate::stdlib::sync::RwLock;
+
+use regex::Regex;
+
+/// Maximum number of compiled regular expressions cached by an `Engine`.
+const MAX_CACHED_REGEX: usize = 256;
+
+/// Cache of compiled regular expressions, keyed by pattern.
+#[derive(Debug, Default)]
+pub(crate) struct RegexCache {
+    #[cfg(not(feature = "sync"))]
+    cache: RefCell<HashMap<ImmutableString, Regex>>,
+    #[cfg(feature = "sync")]
+    cache: RwLock<HashMap<ImmutableString, Regex>>,
+}
+
+impl RegexCache {
+    /// Get a compiled regular expression, compiling it if not yet cached.
+    fn get(&self, pattern: ImmutableString) -> FuncReturn<Regex> {
+        #[cfg(not(feature = "sync"))]
+        let mut cache = self.cache.borrow_mut();
+        #[cfg(feature = "sync")]
+        let mut cache = self.cache.write().unwrap();
+
+        if let Some(re) = cache.get(&pattern) {
+            return Ok(re.clone());
+        }
+
+        let re = Regex::new(&pattern).map_err(|err| {
+            Box::new(EvalAltResult::ErrorRuntime(
+                format!("Invalid regular expression '{}': {}", pattern, err),
+                Position::none(),
+            ))
+        })?;
+
+        // Start afresh rather than grow without bounds
+        if cache.len() >= MAX_CACHED_REGEX {
+            cache.clear();
+        }
+        cache.insert(pattern, re.clone());
+
+        Ok(re)
+    }
+}
+
+/// Get the regular expression and the string to match from the arguments,
+/// counting the match as one operation.
+fn match_args<'a>(
+    context: &NativeCallContext,
+    args: &'a [&mut Dynamic],
+) -> FuncReturn<(&'a Regex, &'a str)> {
+    let re = args[0].downcast_ref::<Regex>().unwrap();
+    let s = args[1].downcast_ref::<ImmutableString>().unwrap();
+
+    check_string_size(context.engine(), s.len())?;
+    context.inc_operations()?;
+
+    Ok((re, s.as_str()))
+}
+
+fn regex(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Regex> {
+    let pattern = args[0].downcast_ref::<ImmutableString>().unwrap().clone();
+    context.engine().regex_cache.get(pattern)
+}
+fn is_match(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<bool> {
+    let (re, s) = match_args(context, args)?;
+    Ok(re.is_match(s))
+}
+fn find(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Dynamic> {
+    let (re, s) = match_args(context, args)?;
+
+    Ok(re
+        .find(s)
+        .map_or_else(|| ().into(), |m| m.as_str().to_string().into()))
+}
+#[cfg(not(feature = "no_index"))]
+fn find_all(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Array> {
+    let (re, s) = match_args(context, args)?;
+    let mut result = Array::new();
+
+    for m in re.find_iter(s) {
+        context.inc_operations()?;
+        result.push(m.as_str().to_string().into());
+        check_array_size(context.engine(), result.len())?;
+    }
+    Ok(result)
+}
+#[cfg(not(feature = "no_object"))]
+fn captures(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Dynamic> {
+    let (re, s) = match_args(context, args)?;
+
+    let caps = match re.captures(s) {
+        Some(caps) => caps,
+        None => return Ok(().into()),
+    };
+
+    let group =
+        |m: Option<regex::Match>| m.map_or_else(|| ().into(), |m| m.as_str().to_string().into());
+    let mut map = Map::new();
+
+    for (i, name) in re.capture_names().enumerate() {
+        let value: Dynamic = group(caps.get(i));
+
+        if let Some(name) = name {
+            map.insert(name.into(), value.clone());
+        }
+        map.insert(i.to_string().into(), value);
+    }
+    Ok(map.into())
+}
+fn replace_all(
+    context: &NativeCallContext,
+    args: &mut [&mut Dynamic],
+) -> FuncReturn<ImmutableString> {
+    let replace = args[2].downcast_ref::<ImmutableString>().unwrap().clone();
+    let (re, s) = match_args(context, args)?;
+    let mut result = String::with_capacity(s.len());
+    let mut last = 0;
+
+    for caps in re.captures_iter(s) {
+        context.inc_operations()?;
+
+        let m = caps.get(0).unwrap();
+        result.push_str(&s[last..m.start()]);
+        caps.expand(&replace, &mut result);
+        last = m.end();
+
+        check_string_size(context.engine(), result.len())?;
+    }
+
+    if last == 0 && result.is_empty() {
+        // No match - return the original string without copying
+        return Ok(args[1].downcast_ref::<ImmutableString>().unwrap().clone());
+    }
+
+    result.push_str(&s[last..]);
+    check_string_size(context.engine(), result.len())?;
+
+    Ok(result.into())
+}
+#[cfg(not(feature = "no_index"))]
+fn split(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<Array> {
+    let (re, s) = match_args(context, args)?;
+    let mut result = Array::new();
+
+    for piece in re.split(s) {
+        context.inc_operations()?;
+        result.push(piece.to_string().into());
+        check_array_size(context.engine(), result.len())?;
+    }
+    Ok(result)
+}
+
+def_package!(crate:RegexPackage:"Regular expressions, via the `Regex` type.", lib, {
+    let re = TypeId::of::<Regex>();
+    let s = TypeId::of::<ImmutableString>();
+
+    lib.set_raw_fn("regex", &[s], regex);
+    lib.set_raw_fn("is_match", &[re, s], is_match);
+    lib.set_raw_fn("find", &[re, s], find);
+    lib.set_raw_fn("replace_all", &[re, s, s], replace_all);
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        lib.set_raw_fn("find_all", &[re, s], find_all);
+        lib.set_raw_fn("split", &[re, s], split);
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    lib.set_raw_fn("captures", &[re, s], captures);
+
+    lib.set_fn_1_mut(KEYWORD_PRINT, |re: &mut Regex| Ok(re.as_str().to_string()));
+    lib.set_fn_1_mut(FN_TO_STRING, |re: &mut Regex| Ok(re.as_str().to_string()));
+    lib.set_fn_1_mut(KEYWORD_DEBUG, |re: &mut Regex| Ok(format!("regex({:?})", re.as_str())));
+});
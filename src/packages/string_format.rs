@@ -0,0 +1,328 @@
+//! Implementation of the `format` function, with a template language similar to Rust's `format!`.
+
+use super::check_string_size;
+
+use crate::any::{Dynamic, Union};
+use crate::engine::FN_TO_STRING;
+use crate::fn_native::NativeCallContext;
+use crate::module::FuncReturn;
+use crate::result::EvalAltResult;
+use crate::token::Position;
+
+#[cfg(not(feature = "no_object"))]
+use crate::engine::Map;
+
+use crate::stdlib::{
+    boxed::Box,
+    format,
+    iter::Peekable,
+    str::Chars,
+    string::{String, ToString},
+};
+
+/// Arguments to a format template.
+pub(super) enum FormatArgs<'a> {
+    /// Positional arguments.
+    List(&'a [Dynamic]),
+    /// Named arguments.
+    #[cfg(not(feature = "no_object"))]
+    Named(&'a Map),
+}
+
+/// A parsed format specification, i.e. the part after `:` in a placeholder.
+#[derive(Debug, Default)]
+struct FormatSpec {
+    fill: Option<char>,
+    align: Option<char>,
+    sign: bool,
+    alternate: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    kind: String,
+}
+
+fn err_format(msg: String) -> Box<EvalAltResult> {
+    Box::new(EvalAltResult::ErrorRuntime(msg, Position::none()))
+}
+
+/// Read a decimal number, if any.
+fn parse_number(chars: &mut Peekable<Chars>) -> Option<usize> {
+    let mut result = None;
+
+    while let Some(digit) = chars.peek().and_then(|ch| ch.to_digit(10)) {
+        result = Some(
+            result
+                .unwrap_or(0usize)
+                .saturating_mul(10)
+                .saturating_add(digit as usize),
+        );
+        chars.next();
+    }
+    result
+}
+
+/// Parse a format specification, i.e. `[[fill]align][+][#][0][width][.precision][type]`.
+fn parse_spec(spec: &str) -> FuncReturn<FormatSpec> {
+    let mut result = FormatSpec::default();
+    let mut chars = spec.chars().peekable();
+
+    let is_align = |ch: char| ch == '<' || ch == '^' || ch == '>';
+    let mut lookahead = spec.chars();
+
+    match (lookahead.next(), lookahead.next()) {
+        (Some(fill), Some(align)) if is_align(align) => {
+            result.fill = Some(fill);
+            result.align = Some(align);
+            chars.next();
+            chars.next();
+        }
+        (Some(align), _) if is_align(align) => {
+            result.align = Some(align);
+            chars.next();
+        }
+        _ => (),
+    }
+
+    if chars.peek() == Some(&'+') {
+        result.sign = true;
+        chars.next();
+    }
+    if chars.peek() == Some(&'#') {
+        result.alternate = true;
+        chars.next();
+    }
+    if chars.peek() == Some(&'0') {
+        result.zero = true;
+        chars.next();
+    }
+
+    result.width = parse_number(&mut chars).unwrap_or(0);
+
+    if chars.peek() == Some(&'.') {
+        chars.next();
+        result.precision = Some(
+            parse_number(&mut chars)
+                .ok_or_else(|| err_format(format!("Missing precision in format '{}'", spec)))?,
+        );
+    }
+
+    result.kind = chars.collect();
+
+    match result.kind.as_str() {
+        "" | "?" | "x" | "X" | "o" | "b" | "e" | "E" => Ok(result),
+        kind => Err(err_format(format!(
+            "Unknown format type '{}' in format '{}'",
+            kind, spec
+        ))),
+    }
+}
+
+/// Append a character to a string a number of times.
+fn push_n(s: &mut String, ch: char, n: usize) {
+    for _ in 0..n {
+        s.push(ch);
+    }
+}
+
+/// Truncate a string to a maximum number of characters.
+fn truncate(s: String, precision: Option<usize>) -> String {
+    match precision {
+        Some(len) if s.chars().count() > len => s.chars().take(len).collect(),
+        _ => s,
+    }
+}
+
+/// Format a value according to a specification.
+/// Returns the prefix (sign and radix), the body, and whether the value is a number.
+fn format_value(
+    context: &NativeCallContext,
+    value: &Dynamic,
+    spec: &FormatSpec,
+) -> FuncReturn<(String, String, bool)> {
+    let kind = spec.kind.as_str();
+
+    if kind == "?" {
+        return Ok((String::new(), format!("{:?}", value), false));
+    }
+
+    match &value.0 {
+        Union::Int(n) => {
+            let (radix, body) = match kind {
+                "x" => ("0x", format!("{:x}", n)),
+                "X" => ("0x", format!("{:X}", n)),
+                "o" => ("0o", format!("{:o}", n)),
+                "b" => ("0b", format!("{:b}", n)),
+                "e" => return Ok(number_parts(format!("{:e}", n), spec)),
+                "E" => return Ok(number_parts(format!("{:E}", n), spec)),
+                _ => return Ok(number_parts(n.to_string(), spec)),
+            };
+            let prefix = if spec.alternate { radix } else { "" };
+            Ok((prefix.to_string(), body, true))
+        }
+        #[cfg(not(feature = "no_float"))]
+        Union::Float(f) => {
+            let s = match (kind, spec.precision) {
+                ("", None) => format!("{}", f),
+                ("", Some(p)) => format!("{:.*}", p, f),
+                ("e", None) => format!("{:e}", f),
+                ("e", Some(p)) => format!("{:.*e}", p, f),
+                ("E", None) => format!("{:E}", f),
+                ("E", Some(p)) => format!("{:.*E}", p, f),
+                _ => return Err(err_mismatch(value, spec)),
+            };
+            Ok(number_parts(s, spec))
+        }
+        _ if !kind.is_empty() => Err(err_mismatch(value, spec)),
+        Union::Unit(_) => Ok((String::new(), String::new(), false)),
+        Union::Bool(b) => Ok((
+            String::new(),
+            truncate(b.to_string(), spec.precision),
+            false,
+        )),
+        Union::Char(ch) => Ok((
+            String::new(),
+            truncate(ch.to_string(), spec.precision),
+            false,
+        )),
+        Union::Str(s) => Ok((
+            String::new(),
+            truncate(s.to_string(), spec.precision),
+            false,
+        )),
+        _ => {
+            let s = to_string(context, value)?;
+            Ok((String::new(), truncate(s, spec.precision), false))
+        }
+    }
+}
+
+/// Split a formatted number into its sign and its digits, adding the `+` sign if required.
+fn number_parts(s: String, spec: &FormatSpec) -> (String, String, bool) {
+    if let Some(digits) = s.strip_prefix('-') {
+        ("-".to_string(), digits.to_string(), true)
+    } else if spec.sign {
+        ("+".to_string(), s, true)
+    } else {
+        (String::new(), s, true)
+    }
+}
+
+fn err_mismatch(value: &Dynamic, spec: &FormatSpec) -> Box<EvalAltResult> {
+    err_format(format!(
+        "Cannot format {} with format type '{}'",
+        value.type_name(),
+        spec.kind
+    ))
+}
+
+/// Convert a value to a string via its `to_string` function, if one is registered.
+fn to_string(context: &NativeCallContext, value: &Dynamic) -> FuncReturn<String> {
+    match context.call_fn_dynamic_raw(FN_TO_STRING, false, &mut [&mut value.clone()]) {
+        Ok(s) => Ok(s.take_string().unwrap_or_else(|_| value.to_string())),
+        Err(err) => match *err {
+            EvalAltResult::ErrorFunctionNotFound(_, _) => Ok(value.to_string()),
+            _ => Err(err),
+        },
+    }
+}
+
+/// Look up the value of a placeholder.
+fn get_arg<'a>(args: &FormatArgs<'a>, name: &str, next: &mut usize) -> FuncReturn<&'a Dynamic> {
+    let index = if name.is_empty() {
+        *next += 1;
+        Some(*next - 1)
+    } else {
+        name.parse::<usize>().ok()
+    };
+
+    match (args, index) {
+        (FormatArgs::List(list), Some(index)) => list.get(index).ok_or_else(|| {
+            err_format(format!(
+                "Missing argument {} for format ({} argument(s) given)",
+                index,
+                list.len()
+            ))
+        }),
+        (FormatArgs::List(_), None) => Err(err_format(format!(
+            "Named argument '{}' requires a map of arguments for format",
+            name
+        ))),
+        #[cfg(not(feature = "no_object"))]
+        (FormatArgs::Named(map), _) => map
+            .get(name)
+            .ok_or_else(|| err_format(format!("Missing argument '{}' for format", name))),
+    }
+}
+
+/// Format a template with arguments.
+pub(super) fn format_template(
+    context: &NativeCallContext,
+    template: &str,
+    args: FormatArgs,
+) -> FuncReturn<String> {
+    let engine = context.engine();
+    let mut result = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    let mut next = 0;
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                result.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                result.push('}');
+            }
+            '}' => return Err(err_format("Unmatched '}' in format".to_string())),
+            '{' => {
+                let mut placeholder = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(ch) => placeholder.push(ch),
+                        None => return Err(err_format("Unmatched '{' in format".to_string())),
+                    }
+                }
+
+                let (name, spec) = match placeholder.find(':') {
+                    Some(index) => (&placeholder[..index], &placeholder[index + 1..]),
+                    None => (placeholder.as_str(), ""),
+                };
+                let spec = parse_spec(spec)?;
+
+                // Check if string will be over max size limit
+                check_string_size(engine, spec.width)?;
+
+                let value = get_arg(&args, name.trim(), &mut next)?;
+                let (prefix, body, numeric) = format_value(context, value, &spec)?;
+                let len = prefix.chars().count() + body.chars().count();
+                let padding = spec.width.saturating_sub(len);
+
+                if padding > 0 && spec.zero && numeric {
+                    result.push_str(&prefix);
+                    push_n(&mut result, '0', padding);
+                    result.push_str(&body);
+                } else {
+                    let fill = spec.fill.unwrap_or(' ');
+                    let align = spec.align.unwrap_or(if numeric { '>' } else { '<' });
+                    let (before, after) = match align {
+                        '>' => (padding, 0),
+                        '^' => (padding / 2, padding - padding / 2),
+                        _ => (0, padding),
+                    };
+                    push_n(&mut result, fill, before);
+                    result.push_str(&prefix);
+                    result.push_str(&body);
+                    push_n(&mut result, fill, after);
+                }
+            }
+            ch => result.push(ch),
+        }
+    }
+
+    Ok(result)
+}
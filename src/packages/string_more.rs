@@ -1,8 +1,14 @@
+use super::check_string_size;
+use super::string_format::{format_template, FormatArgs};
+
 use crate::any::Dynamic;
 use crate::def_package;
-use crate::engine::Engine;
-use crate::module::{FuncReturn, Module};
+use crate::fn_native::NativeCallContext;
+use crate::module::FuncReturn;
 use crate::parser::{ImmutableString, INT};
+
+#[cfg(not(feature = "no_float"))]
+use crate::parser::FLOAT;
 use crate::result::EvalAltResult;
 use crate::token::Position;
 use crate::utils::StaticVec;
@@ -10,11 +16,18 @@ use crate::utils::StaticVec;
 #[cfg(not(feature = "no_index"))]
 use crate::engine::Array;
 
+#[cfg(not(feature = "no_index"))]
+use crate::stdlib::iter::once;
+
+#[cfg(not(feature = "no_object"))]
+use crate::engine::Map;
+
 use crate::stdlib::{
     any::TypeId,
     boxed::Box,
     fmt::Display,
     format,
+    slice,
     string::{String, ToString},
     vec::Vec,
 };
@@ -80,11 +93,130 @@ fn crop_string(s: &mut ImmutableString, start: INT, len: INT) -> FuncReturn<()>
     Ok(())
 }
 
+/// Replace a string with a trimmed version of itself, without copying if nothing is trimmed.
+fn trim_with(s: &mut ImmutableString, trim: impl Fn(&str) -> &str) {
+    let trimmed = trim(s);
+
+    if trimmed.len() < s.len() {
+        *s = trimmed.to_string().into();
+    }
+}
+/// Convert the case of a string, returning the original string (without copying) if unchanged.
+fn change_case(
+    s: ImmutableString,
+    unchanged: impl Fn(char) -> bool,
+    convert: impl Fn(&str) -> String,
+) -> ImmutableString {
+    if s.chars().all(unchanged) {
+        s
+    } else {
+        convert(&s).into()
+    }
+}
+/// Collect the pieces of a split string into an array.
+/// If the string is not split at all, the original string is used without copying.
+#[cfg(not(feature = "no_index"))]
+fn collect_split<'a>(s: &ImmutableString, mut pieces: impl Iterator<Item = &'a str>) -> Array {
+    let first = match pieces.next() {
+        Some(piece) => piece,
+        None => return Array::new(),
+    };
+
+    match pieces.next() {
+        None if first.len() == s.len() => once(s.clone().into()).collect(),
+        None => once(first.to_string().into()).collect(),
+        Some(second) => [first, second]
+            .iter()
+            .cloned()
+            .chain(pieces)
+            .map(|piece| piece.to_string().into())
+            .collect(),
+    }
+}
+/// Convert a maximum number of pieces into a count for `splitn` (no limit if not positive).
+#[cfg(not(feature = "no_index"))]
+fn split_count(count: INT) -> usize {
+    if count <= 0 {
+        usize::MAX
+    } else {
+        count as usize
+    }
+}
+/// Join the elements of an array into a string, separated by `sep`.
+#[cfg(not(feature = "no_index"))]
+fn join(list: &Array, sep: &str) -> String {
+    let mut result = String::new();
+
+    for (i, item) in list.iter().enumerate() {
+        if i > 0 {
+            result.push_str(sep);
+        }
+        match item.as_str() {
+            Ok(s) => result.push_str(s),
+            Err(_) => result.push_str(&item.to_string()),
+        }
+    }
+    result
+}
+fn last_index_of(s: &str, index: Option<usize>) -> INT {
+    index
+        .map(|index| s[0..index].chars().count() as INT)
+        .unwrap_or(-1 as INT)
+}
+fn parse_int(s: &str, radix: INT) -> FuncReturn<INT> {
+    if !(2..=36).contains(&radix) {
+        return Err(Box::new(EvalAltResult::ErrorArithmetic(
+            format!("Invalid radix for parsing integer: {}", radix),
+            Position::none(),
+        )));
+    }
+
+    INT::from_str_radix(s.trim(), radix as u32).map_err(|err| {
+        Box::new(EvalAltResult::ErrorArithmetic(
+            format!("Error parsing integer number '{}': {}", s, err),
+            Position::none(),
+        ))
+    })
+}
+#[cfg(not(feature = "no_float"))]
+fn parse_float(s: &str) -> FuncReturn<FLOAT> {
+    s.trim().parse::<FLOAT>().map_err(|err| {
+        Box::new(EvalAltResult::ErrorArithmetic(
+            format!("Error parsing floating-point number '{}': {}", s, err),
+            Position::none(),
+        ))
+    })
+}
+
+fn format(context: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<String> {
+    let template = args[0].downcast_ref::<ImmutableString>().unwrap();
+
+    match args.get(1) {
+        None => format_template(context, template, FormatArgs::List(&[])),
+        #[cfg(not(feature = "no_index"))]
+        Some(list) if list.is::<Array>() => {
+            let list = list.downcast_ref::<Array>().unwrap();
+            format_template(context, template, FormatArgs::List(list))
+        }
+        #[cfg(not(feature = "no_object"))]
+        Some(map) if map.is::<Map>() => {
+            let map = map.downcast_ref::<Map>().unwrap();
+            format_template(context, template, FormatArgs::Named(map))
+        }
+        Some(value) => format_template(context, template, FormatArgs::List(slice::from_ref(value))),
+    }
+}
+
 macro_rules! reg_op {
     ($lib:expr, $op:expr, $func:ident, $($par:ty),*) => {
         $( $lib.set_fn_2($op, $func::<$par>); )*
     };
 }
+macro_rules! reg_format {
+    ($lib:expr, $op:expr, $func:ident, $($par:ty),*) => {
+        $( $lib.set_raw_fn($op, &[TypeId::of::<ImmutableString>(), TypeId::of::<$par>()], $func); )*
+    };
+}
 
 def_package!(crate:MoreStringPackage:"Additional string utilities, including string building.", lib, {
     reg_op!(lib, "+", append, INT, bool, char);
@@ -226,7 +358,8 @@ def_package!(crate:MoreStringPackage:"Additional string utilities, including str
     lib.set_raw_fn(
         "pad",
         &[TypeId::of::<ImmutableString>(), TypeId::of::<INT>(), TypeId::of::<char>()],
-        |engine: &Engine, _: &Module, args: &mut [&mut Dynamic]| {
+        |context: &NativeCallContext, args: &mut [&mut Dynamic]| {
+            let engine = context.engine();
             let len = *args[1].downcast_ref::< INT>().unwrap();
 
             // Check if string will be over max size limit
@@ -298,14 +431,151 @@ def_package!(crate:MoreStringPackage:"Additional string utilities, including str
     lib.set_fn_1_mut(
         "trim",
         |s: &mut ImmutableString| {
-            let trimmed = s.trim();
+            trim_with(s, str::trim);
+            Ok(())
+        },
+    );
+    lib.set_fn_1_mut(
+        "trim_start",
+        |s: &mut ImmutableString| {
+            trim_with(s, str::trim_start);
+            Ok(())
+        },
+    );
+    lib.set_fn_1_mut(
+        "trim_end",
+        |s: &mut ImmutableString| {
+            trim_with(s, str::trim_end);
+            Ok(())
+        },
+    );
+    lib.set_fn_1(
+        "to_upper",
+        |s: ImmutableString| Ok(change_case(s, |ch| !ch.is_lowercase(), str::to_uppercase)),
+    );
+    lib.set_fn_1(
+        "to_lower",
+        |s: ImmutableString| Ok(change_case(s, |ch| !ch.is_uppercase(), str::to_lowercase)),
+    );
+    lib.set_fn_1("to_upper", |ch: char| Ok(ch.to_uppercase().next().unwrap_or(ch)));
+    lib.set_fn_1("to_lower", |ch: char| Ok(ch.to_lowercase().next().unwrap_or(ch)));
+    lib.set_fn_2_mut(
+        "starts_with",
+        |s: &mut ImmutableString, find: ImmutableString| Ok(s.starts_with(find.as_str())),
+    );
+    lib.set_fn_2_mut(
+        "starts_with",
+        |s: &mut ImmutableString, ch: char| Ok(s.starts_with(ch)),
+    );
+    lib.set_fn_2_mut(
+        "ends_with",
+        |s: &mut ImmutableString, find: ImmutableString| Ok(s.ends_with(find.as_str())),
+    );
+    lib.set_fn_2_mut(
+        "ends_with",
+        |s: &mut ImmutableString, ch: char| Ok(s.ends_with(ch)),
+    );
+    lib.set_fn_2_mut(
+        "last_index_of",
+        |s: &mut ImmutableString, ch: char| Ok(last_index_of(s, s.rfind(ch))),
+    );
+    lib.set_fn_2_mut(
+        "last_index_of",
+        |s: &mut ImmutableString, find: ImmutableString| {
+            Ok(last_index_of(s, s.rfind(find.as_str())))
+        },
+    );
+    lib.set_raw_fn(
+        "repeat",
+        &[TypeId::of::<ImmutableString>(), TypeId::of::<INT>()],
+        |context: &NativeCallContext, args: &mut [&mut Dynamic]| {
+            let count = *args[1].downcast_ref::<INT>().unwrap();
+            let s = args[0].downcast_ref::<ImmutableString>().unwrap();
 
-            if trimmed.len() < s.len() {
-                *s = trimmed.to_string().into();
+            if count <= 0 {
+                return Ok(ImmutableString::default());
+            } else if count == 1 {
+                return Ok(s.clone());
             }
-            Ok(())
+
+            // Check if string will be over max size limit
+            check_string_size(context.engine(), s.len().saturating_mul(count as usize))?;
+
+            Ok(s.repeat(count as usize).into())
         },
     );
+    lib.set_raw_fn("format", &[TypeId::of::<ImmutableString>()], format);
+    reg_format!(lib, "format", format, INT, bool, char, ImmutableString, ());
+
+    #[cfg(not(feature = "no_float"))]
+    reg_format!(lib, "format", format, FLOAT);
+
+    #[cfg(not(feature = "no_index"))]
+    reg_format!(lib, "format", format, Array);
+
+    #[cfg(not(feature = "no_object"))]
+    reg_format!(lib, "format", format, Map);
+
+    lib.set_fn_1("parse_int", |s: ImmutableString| parse_int(&s, 10));
+    lib.set_fn_2("parse_int", |s: ImmutableString, radix: INT| parse_int(&s, radix));
+
+    #[cfg(not(feature = "no_float"))]
+    lib.set_fn_1("parse_float", |s: ImmutableString| parse_float(&s));
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        lib.set_fn_1_mut(
+            "split",
+            |s: &mut ImmutableString| Ok(collect_split(s, s.split_whitespace())),
+        );
+        lib.set_fn_2_mut(
+            "split",
+            |s: &mut ImmutableString, sep: ImmutableString| {
+                Ok(collect_split(s, s.split(sep.as_str())))
+            },
+        );
+        lib.set_fn_2_mut(
+            "split",
+            |s: &mut ImmutableString, sep: char| Ok(collect_split(s, s.split(sep))),
+        );
+        lib.set_fn_3_mut(
+            "split",
+            |s: &mut ImmutableString, sep: ImmutableString, count: INT| {
+                Ok(collect_split(s, s.splitn(split_count(count), sep.as_str())))
+            },
+        );
+        lib.set_fn_3_mut(
+            "split",
+            |s: &mut ImmutableString, sep: char, count: INT| {
+                Ok(collect_split(s, s.splitn(split_count(count), sep)))
+            },
+        );
+        lib.set_fn_3_mut(
+            "splitn",
+            |s: &mut ImmutableString, count: INT, sep: ImmutableString| {
+                Ok(collect_split(s, s.splitn(split_count(count), sep.as_str())))
+            },
+        );
+        lib.set_fn_3_mut(
+            "splitn",
+            |s: &mut ImmutableString, count: INT, sep: char| {
+                Ok(collect_split(s, s.splitn(split_count(count), sep)))
+            },
+        );
+        lib.set_fn_1_mut("lines", |s: &mut ImmutableString| Ok(collect_split(s, s.lines())));
+        lib.set_fn_1_mut(
+            "chars",
+            |s: &mut ImmutableString| Ok(s.chars().map(Into::into).collect::<Array>()),
+        );
+        lib.set_fn_2_mut(
+            "join",
+            |list: &mut Array, sep: ImmutableString| Ok(join(list, &sep)),
+        );
+        lib.set_fn_2_mut(
+            "join",
+            |list: &mut Array, sep: char| Ok(join(list, sep.encode_utf8(&mut [0; 4]))),
+        );
+    }
 
     // Register string iterator
     lib.set_iter(
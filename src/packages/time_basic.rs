@@ -2,12 +2,23 @@
 use super::logic::{eq, gt, gte, lt, lte, ne};
 use super::math_basic::MAX_INT;
 
+use crate::any::Dynamic;
+use crate::datetime::{DateTime, Duration};
 use crate::def_package;
+use crate::engine::{FN_TO_STRING, KEYWORD_DEBUG, KEYWORD_PRINT};
+use crate::fn_native::NativeCallContext;
 use crate::module::FuncReturn;
-use crate::parser::INT;
+use crate::parser::{ImmutableString, INT};
 use crate::result::EvalAltResult;
 use crate::token::Position;
 
+use crate::stdlib::{
+    any::TypeId,
+    boxed::Box,
+    format,
+    string::{String, ToString},
+};
+
 #[cfg(not(target_arch = "wasm32"))]
 use crate::stdlib::time::Instant;
 
@@ -17,9 +28,137 @@ use instant::Instant;
 #[cfg(not(feature = "no_float"))]
 use crate::parser::FLOAT;
 
+#[cfg(not(feature = "no_object"))]
+use crate::engine::make_getter;
+
+fn err_invalid(desc: String) -> Box<EvalAltResult> {
+    Box::new(EvalAltResult::ErrorRuntime(desc, Position::none()))
+}
+fn err_out_of_range() -> Box<EvalAltResult> {
+    Box::new(EvalAltResult::ErrorArithmetic(
+        "Date-time out of range".to_string(),
+        Position::none(),
+    ))
+}
+fn err_overflow(desc: &str) -> Box<EvalAltResult> {
+    Box::new(EvalAltResult::ErrorArithmetic(
+        format!("Duration overflow: {}", desc),
+        Position::none(),
+    ))
+}
+
+/// Convert a value to `INT`, checking for overflow.
+#[cfg_attr(feature = "unchecked", allow(unused_variables))]
+fn to_int(value: i128, desc: &str) -> FuncReturn<INT> {
+    #[cfg(not(feature = "unchecked"))]
+    if value > MAX_INT as i128 || value < -(MAX_INT as i128) - 1 {
+        return Err(Box::new(EvalAltResult::ErrorArithmetic(
+            format!("Integer overflow for {}: {}", desc, value),
+            Position::none(),
+        )));
+    }
+
+    Ok(value as INT)
+}
+
+fn make_datetime(year: INT, month: INT, day: INT, hour: INT, minute: INT, second: INT) -> FuncReturn<DateTime> {
+    DateTime::from_ymd_hms(year, month, day, hour, minute, second)
+        .ok_or_else(|| {
+            err_invalid(format!(
+                "Invalid date-time: {}-{}-{} {}:{}:{}",
+                year, month, day, hour, minute, second
+            ))
+        })
+}
+fn datetime_6(_: &NativeCallContext, args: &mut [&mut Dynamic]) -> FuncReturn<DateTime> {
+    let mut n = [0 as INT; 6];
+    n.iter_mut().zip(args.iter()).for_each(|(n, arg)| *n = arg.as_int().unwrap());
+    make_datetime(n[0], n[1], n[2], n[3], n[4], n[5])
+}
+fn parse_datetime(s: ImmutableString) -> FuncReturn<DateTime> {
+    DateTime::parse(&s).ok_or_else(|| err_invalid(format!("Invalid date-time: '{}'", s)))
+}
+fn to_offset(dt: &mut DateTime, minutes: INT) -> FuncReturn<DateTime> {
+    if minutes.abs() >= 24 * 60 {
+        return Err(err_invalid(format!("Invalid offset from UTC: {} minutes", minutes)));
+    }
+    dt.with_offset(minutes as i32).ok_or_else(err_out_of_range)
+}
+fn add_months(dt: &mut DateTime, months: INT) -> FuncReturn<DateTime> {
+    dt.checked_add_months(months).ok_or_else(err_out_of_range)
+}
+
+fn year(dt: &mut DateTime) -> FuncReturn<INT> {
+    Ok(dt.year())
+}
+fn month(dt: &mut DateTime) -> FuncReturn<INT> {
+    Ok(dt.month() as INT)
+}
+fn day(dt: &mut DateTime) -> FuncReturn<INT> {
+    Ok(dt.day() as INT)
+}
+fn hour(dt: &mut DateTime) -> FuncReturn<INT> {
+    Ok(dt.hour() as INT)
+}
+fn minute(dt: &mut DateTime) -> FuncReturn<INT> {
+    Ok(dt.minute() as INT)
+}
+fn second(dt: &mut DateTime) -> FuncReturn<INT> {
+    Ok(dt.second() as INT)
+}
+fn nanosecond(dt: &mut DateTime) -> FuncReturn<INT> {
+    Ok(dt.nanosecond() as INT)
+}
+fn weekday(dt: &mut DateTime) -> FuncReturn<INT> {
+    Ok(dt.weekday() as INT)
+}
+fn day_of_year(dt: &mut DateTime) -> FuncReturn<INT> {
+    Ok(dt.day_of_year() as INT)
+}
+fn offset(dt: &mut DateTime) -> FuncReturn<INT> {
+    Ok(dt.offset() as INT)
+}
+fn unix_timestamp(dt: &mut DateTime) -> FuncReturn<INT> {
+    to_int(dt.unix_timestamp() as i128, "unix_timestamp")
+}
+
+fn make_duration(n: INT, unit: INT, desc: &str) -> FuncReturn<Duration> {
+    Duration::from_secs(unit)
+        .checked_mul(n)
+        .ok_or_else(|| err_overflow(desc))
+}
+
+fn total_days(d: &mut Duration) -> FuncReturn<INT> {
+    to_int(d.whole_secs() / 86_400, "total_days")
+}
+fn total_hours(d: &mut Duration) -> FuncReturn<INT> {
+    to_int(d.whole_secs() / 3600, "total_hours")
+}
+fn total_minutes(d: &mut Duration) -> FuncReturn<INT> {
+    to_int(d.whole_secs() / 60, "total_minutes")
+}
+fn total_seconds(d: &mut Duration) -> FuncReturn<INT> {
+    to_int(d.whole_secs(), "total_seconds")
+}
+fn total_milliseconds(d: &mut Duration) -> FuncReturn<INT> {
+    to_int(d.whole_millis(), "total_milliseconds")
+}
+
+macro_rules! reg_getter {
+    ($lib:expr, $($name:ident),*) => {
+        $(
+            $lib.set_fn_1_mut(stringify!($name), $name);
+            #[cfg(not(feature = "no_object"))]
+            $lib.set_getter_fn(stringify!($name), $name);
+        )*
+    };
+}
+
 def_package!(crate:BasicTimePackage:"Basic timing utilities.", lib, {
     // Register date/time functions
-    lib.set_fn_0("timestamp", || Ok(Instant::now()));
+    lib.set_raw_fn("timestamp", &[], |context: &NativeCallContext, _: &mut [&mut Dynamic]| {
+        Ok(context.engine().clock.instant())
+    });
 
     lib.set_fn_2(
         "-",
@@ -75,13 +214,15 @@ def_package!(crate:BasicTimePackage:"Basic timing utilities.", lib, {
     lib.set_fn_2("!=", ne::<Instant>);
 
     #[cfg(not(feature = "no_float"))]
-    fn elapsed (timestamp: &mut Instant) -> Result<FLOAT, Box<EvalAltResult>> {
-        Ok(timestamp.elapsed().as_secs_f64())
+    fn elapsed (context: &NativeCallContext, args: &mut [&mut Dynamic]) -> Result<FLOAT, Box<EvalAltResult>> {
+        let timestamp = args[0].downcast_ref::<Instant>().unwrap();
+        Ok(context.engine().clock.instant().saturating_duration_since(*timestamp).as_secs_f64())
     }
 
     #[cfg(feature = "no_float")]
-    fn elapsed (timestamp: &mut Instant) -> Result<INT, Box<EvalAltResult>> {
-        let seconds = timestamp.elapsed().as_secs();
+    fn elapsed (context: &NativeCallContext, args: &mut [&mut Dynamic]) -> Result<INT, Box<EvalAltResult>> {
+        let timestamp = args[0].downcast_ref::<Instant>().unwrap();
+        let seconds = context.engine().clock.instant().saturating_duration_since(*timestamp).as_secs();
 
         #[cfg(not(feature = "unchecked"))]
         if seconds > (MAX_INT as u64) {
@@ -94,8 +235,74 @@ def_package!(crate:BasicTimePackage:"Basic timing utilities.", lib, {
         Ok(seconds as INT)
     }
 
-    lib.set_fn_1_mut("elapsed", elapsed);
+    lib.set_raw_fn("elapsed", &[TypeId::of::<Instant>()], elapsed);
 
     #[cfg(not(feature = "no_object"))]
-    lib.set_getter_fn("elapsed", elapsed);
+    lib.set_raw_fn(make_getter("elapsed"), &[TypeId::of::<Instant>()], elapsed);
+
+    // Calendar date-times
+    lib.set_raw_fn("now", &[], |context: &NativeCallContext, _: &mut [&mut Dynamic]| {
+        context.engine().clock.now().ok_or_else(|| {
+            err_invalid("The current date-time is not available without a clock".to_string())
+        })
+    });
+
+    lib.set_fn_3("datetime", |year: INT, month: INT, day: INT| make_datetime(year, month, day, 0, 0, 0));
+    lib.set_raw_fn("datetime", &[TypeId::of::<INT>(); 6], datetime_6);
+    lib.set_fn_1("datetime_from_unix", |secs: INT| {
+        DateTime::from_unix_timestamp(secs).ok_or_else(err_out_of_range)
+    });
+    lib.set_fn_1("parse_datetime", parse_datetime);
+
+    reg_getter!(lib, year, month, day, hour, minute, second, nanosecond, weekday, day_of_year, offset, unix_timestamp);
+
+    lib.set_fn_2_mut("to_offset", to_offset);
+    lib.set_fn_1_mut("to_utc", |dt: &mut DateTime| Ok(dt.to_utc()));
+    lib.set_fn_2_mut("add_months", add_months);
+    lib.set_fn_2_mut("add_years", |dt: &mut DateTime, years: INT| {
+        let months = years.checked_mul(12).ok_or_else(err_out_of_range)?;
+        dt.checked_add_months(months).ok_or_else(err_out_of_range)
+    });
+
+    lib.set_fn_2("+", |dt: DateTime, d: Duration| dt.checked_add(d).ok_or_else(err_out_of_range));
+    lib.set_fn_2("+", |d: Duration, dt: DateTime| dt.checked_add(d).ok_or_else(err_out_of_range));
+    lib.set_fn_2("-", |dt: DateTime, d: Duration| dt.checked_sub(d).ok_or_else(err_out_of_range));
+    lib.set_fn_2("-", |dt1: DateTime, dt2: DateTime| Ok(dt1.duration_since(dt2)));
+
+    lib.set_fn_2("<", lt::<DateTime>);
+    lib.set_fn_2("<=", lte::<DateTime>);
+    lib.set_fn_2(">", gt::<DateTime>);
+    lib.set_fn_2(">=", gte::<DateTime>);
+    lib.set_fn_2("==", eq::<DateTime>);
+    lib.set_fn_2("!=", ne::<DateTime>);
+
+    lib.set_fn_1_mut(KEYWORD_PRINT, |dt: &mut DateTime| Ok(dt.to_string()));
+    lib.set_fn_1_mut(FN_TO_STRING, |dt: &mut DateTime| Ok(dt.to_string()));
+    lib.set_fn_1_mut(KEYWORD_DEBUG, |dt: &mut DateTime| Ok(format!("datetime({})", dt)));
+
+    // Durations
+    lib.set_fn_1("days", |n: INT| make_duration(n, 86_400, "days"));
+    lib.set_fn_1("hours", |n: INT| make_duration(n, 3600, "hours"));
+    lib.set_fn_1("minutes", |n: INT| make_duration(n, 60, "minutes"));
+    lib.set_fn_1("seconds", |n: INT| make_duration(n, 1, "seconds"));
+    lib.set_fn_1("milliseconds", |n: INT| Ok(Duration::from_millis(n)));
+
+    reg_getter!(lib, total_days, total_hours, total_minutes, total_seconds, total_milliseconds);
+
+    lib.set_fn_2("+", |d1: Duration, d2: Duration| d1.checked_add(d2).ok_or_else(|| err_overflow("+")));
+    lib.set_fn_2("-", |d1: Duration, d2: Duration| d1.checked_sub(d2).ok_or_else(|| err_overflow("-")));
+    lib.set_fn_1("-", |d: Duration| d.checked_neg().ok_or_else(|| err_overflow("-")));
+    lib.set_fn_2("*", |d: Duration, n: INT| d.checked_mul(n).ok_or_else(|| err_overflow("*")));
+    lib.set_fn_2("*", |n: INT, d: Duration| d.checked_mul(n).ok_or_else(|| err_overflow("*")));
+
+    lib.set_fn_2("<", lt::<Duration>);
+    lib.set_fn_2("<=", lte::<Duration>);
+    lib.set_fn_2(">", gt::<Duration>);
+    lib.set_fn_2(">=", gte::<Duration>);
+    lib.set_fn_2("==", eq::<Duration>);
+    lib.set_fn_2("!=", ne::<Duration>);
+
+    lib.set_fn_1_mut(KEYWORD_PRINT, |d: &mut Duration| Ok(d.to_string()));
+    lib.set_fn_1_mut(FN_TO_STRING, |d: &mut Duration| Ok(d.to_string()));
+    lib.set_fn_1_mut(KEYWORD_DEBUG, |d: &mut Duration| Ok(format!("duration({})", d)));
 });
@@ -6,9 +6,15 @@ use crate::engine::{
     make_getter, make_setter, Engine, FN_ANONYMOUS, KEYWORD_THIS, MARKER_BLOCK, MARKER_EXPR,
     MARKER_IDENT,
 };
+
+#[cfg(not(feature = "no_module"))]
+use crate::engine::KEYWORD_IMPORT;
 use crate::error::{LexError, ParseError, ParseErrorType};
-use crate::fn_native::Shared;
+use crate::fn_native::{shared_make_mut, Shared};
 use crate::module::{Module, ModuleRef};
+
+#[cfg(not(feature = "no_module"))]
+use crate::module::ModuleEnv;
 use crate::optimize::{optimize_into_ast, OptimizationLevel};
 use crate::scope::{EntryType as ScopeEntryType, Scope};
 use crate::syntax::FnCustomSyntaxEval;
@@ -68,14 +74,58 @@ type FunctionsLib = HashMap<u64, ScriptFnDef, StraightHasherBuilder>;
 pub struct AST(
     /// Global statements.
     Vec<Stmt>,
-    /// Script-defined functions.
-    Module,
+    /// Script-defined functions, shared with function pointers created by the script.
+    Shared<Module>,
+    /// Name of the source (e.g. the path of the script file), if any.
+    Option<ImmutableString>,
 );
 
 impl AST {
     /// Create a new `AST`.
     pub fn new(statements: Vec<Stmt>, lib: Module) -> Self {
-        Self(statements, lib)
+        Self(statements, lib.into(), None)
+    }
+
+    /// Get the name of the source (e.g. the path of the script file), if any.
+    pub fn source(&self) -> Option<&str> {
+        self.2.as_ref().map(|s| s.as_str())
+    }
+
+    /// Get the name of the source, if any.
+    pub(crate) fn source_raw(&self) -> Option<&ImmutableString> {
+        self.2.as_ref()
+    }
+
+    /// Set the name of the source, leaving the script-defined functions untouched.
+    pub(crate) fn set_source_raw(&mut self, source: Option<ImmutableString>) {
+        self.2 = source;
+    }
+
+    /// Set the name of the source (e.g. the path of the script file).
+    ///
+    /// The source is also set for all script-defined functions in the `AST`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let mut ast = engine.compile("40 + 2")?;
+    /// ast.set_source("scripts/answer.rhai");
+    ///
+    /// assert_eq!(ast.source(), Some("scripts/answer.rhai"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_source(&mut self, source: impl Into<ImmutableString>) -> &mut Self {
+        let source = source.into();
+        #[cfg(not(feature = "no_function"))]
+        shared_make_mut(&mut self.1).set_script_fn_source(&source);
+        self.2 = Some(source);
+        self
     }
 
     /// Get the statements.
@@ -109,6 +159,11 @@ impl AST {
         &self.1
     }
 
+    /// Get the shared `Module` containing all script-defined functions.
+    pub(crate) fn shared_lib(&self) -> &Shared<Module> {
+        &self.1
+    }
+
     /// Clone the `AST`'s functions into a new `AST`.
     /// No statements are cloned.
     ///
@@ -127,13 +182,13 @@ impl AST {
     ) -> Self {
         let mut functions: Module = Default::default();
         functions.merge_filtered(&self.1, filter);
-        Self(Default::default(), functions)
+        Self(Default::default(), functions.into(), self.2.clone())
     }
 
     /// Clone the `AST`'s script statements into a new `AST`.
     /// No functions are cloned.
     pub fn clone_statements_only(&self) -> Self {
-        Self(self.0.clone(), Default::default())
+        Self(self.0.clone(), Default::default(), self.2.clone())
     }
 
     /// Merge two `AST` into one.  Both `AST`'s are untouched and a new, merged, version
@@ -147,6 +202,9 @@ impl AST {
     /// All script-defined functions in the second `AST` overwrite similarly-named functions
     /// in the first `AST` with the same number of parameters.
     ///
+    /// The merged `AST` keeps the source of the first `AST`, if any.
+    /// Script-defined functions keep their own sources.
+    ///
     /// # Example
     ///
     /// ```
@@ -245,7 +303,7 @@ impl AST {
         other: &Self,
         filter: impl Fn(FnAccess, &str, usize) -> bool,
     ) -> Self {
-        let Self(statements, functions) = self;
+        let Self(statements, functions, source) = self;
 
         let ast = match (statements.is_empty(), other.0.is_empty()) {
             (false, false) => {
@@ -258,10 +316,14 @@ impl AST {
             (true, true) => vec![],
         };
 
-        let mut functions = functions.clone();
+        let mut functions = functions.as_ref().clone();
         functions.merge_filtered(&other.1, filter);
 
-        Self::new(ast, functions)
+        Self(
+            ast,
+            functions.into(),
+            source.clone().or_else(|| other.2.clone()),
+        )
     }
 
     /// Filter out the functions, retaining only some based on a filter predicate.
@@ -289,7 +351,7 @@ impl AST {
     /// ```
     #[cfg(not(feature = "no_function"))]
     pub fn retain_functions(&mut self, filter: impl Fn(FnAccess, &str, usize) -> bool) {
-        self.1.retain_functions(filter);
+        shared_make_mut(&mut self.1).retain_functions(filter);
     }
 
     /// Clear all function definitions in the `AST`.
@@ -343,7 +405,7 @@ impl fmt::Display for FnAccess {
 }
 
 /// A scripted function definition.
-#[derive(Debug, Clone, Hash)]
+#[derive(Debug, Clone)]
 pub struct ScriptFnDef {
     /// Function name.
     pub name: String,
@@ -355,6 +417,11 @@ pub struct ScriptFnDef {
     pub body: Stmt,
     /// Position of the function definition.
     pub pos: Position,
+    /// Name of the source (e.g. the path of the script file) containing the function, if any.
+    pub source: Option<ImmutableString>,
+    /// Variables and imports of the module containing the function, if any.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) env: Option<Shared<ModuleEnv>>,
 }
 
 impl fmt::Display for ScriptFnDef {
@@ -509,8 +576,16 @@ pub enum Stmt {
     ReturnWithVal(Box<((ReturnType, Position), Option<Expr>)>),
     /// import expr as module
     Import(Box<(Expr, (String, Position))>),
-    /// expr id as name, ...
-    Export(Box<StaticVec<((String, Position), Option<(String, Position)>)>>),
+    /// export id as name, mod::id as name, mod::*, ...
+    Export(
+        Box<
+            StaticVec<(
+                (String, Position),
+                Option<Box<ModuleRef>>,
+                Option<(String, Position)>,
+            )>,
+        >,
+    ),
 }
 
 impl Default for Stmt {
@@ -1483,6 +1558,14 @@ fn parse_primary(
             let index = state.find_var(&s);
             Expr::Variable(Box::new(((s, settings.pos), None, 0, index)))
         }
+        // import(path) - function form of import
+        #[cfg(not(feature = "no_module"))]
+        Token::Import if input.peek().unwrap().0 == Token::LeftParen => Expr::Variable(Box::new((
+            (KEYWORD_IMPORT.into(), settings.pos),
+            None,
+            0,
+            None,
+        ))),
         // Function call is allowed to have reserved keyword
         Token::Reserved(s) if s != KEYWORD_THIS && input.peek().unwrap().0 == Token::LeftParen => {
             Expr::Variable(Box::new(((s, settings.pos), None, 0, None)))
@@ -2447,7 +2530,34 @@ fn parse_import(
     settings.ensure_level_within_max_limit(state.max_expr_depth)?;
 
     // import expr ...
-    let expr = parse_expr(input, state, lib, settings.level_up())?;
+    let expr = match input.peek().unwrap() {
+        // import(expr) ...
+        (Token::LeftParen, _) => {
+            eat_token(input, Token::LeftParen);
+
+            let call = parse_call_expr(
+                input,
+                state,
+                lib,
+                KEYWORD_IMPORT.into(),
+                None,
+                settings.level_up(),
+            )?;
+
+            match (input.peek().unwrap(), call) {
+                // import(expr) as ...
+                ((Token::As, _), Expr::FnCall(mut x)) if x.3.len() == 1 => x.3.pop(),
+                // import(...) - function form of import
+                (_, call) => {
+                    let expr = parse_binary_op(input, state, lib, 1, call, settings.level_up())?;
+                    let expr =
+                        parse_op_assignment_stmt(input, state, lib, expr, settings.level_up())?;
+                    return Ok(Stmt::Expr(Box::new(expr)));
+                }
+            }
+        }
+        _ => parse_expr(input, state, lib, settings.level_up())?,
+    };
 
     // import expr as ...
     match input.next().unwrap() {
@@ -2488,7 +2598,7 @@ fn parse_export(
     let mut exports = StaticVec::new();
 
     loop {
-        let (id, id_pos) = match input.next().unwrap() {
+        let (mut id, mut id_pos) = match input.next().unwrap() {
             (Token::Identifier(s), pos) => (s.clone(), pos),
             (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
                 return Err(PERR::Reserved(s).into_err(pos));
@@ -2497,7 +2607,40 @@ fn parse_export(
             (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
         };
 
-        let rename = if match_token(input, Token::As)? {
+        // Re-export from an imported module - export mod::...::id
+        let mut modules: Option<Box<ModuleRef>> = None;
+
+        while match_token(input, Token::DoubleColon)? {
+            modules
+                .get_or_insert_with(Default::default)
+                .push((id, id_pos));
+
+            match input.next().unwrap() {
+                (Token::Identifier(s), pos) => {
+                    id = s;
+                    id_pos = pos;
+                }
+                // export mod::* - re-export everything in the module
+                (Token::Multiply, pos) => {
+                    id = "*".into();
+                    id_pos = pos;
+                    break;
+                }
+                (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
+                    return Err(PERR::Reserved(s).into_err(pos));
+                }
+                (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                (_, pos) => return Err(PERR::VariableExpected.into_err(pos)),
+            }
+        }
+
+        if let Some(modules) = modules.as_mut() {
+            modules.set_index(state.find_module(&modules.get(0).0));
+        }
+
+        let rename = if modules.is_some() && id == "*" {
+            None
+        } else if match_token(input, Token::As)? {
             match input.next().unwrap() {
                 (Token::Identifier(s), pos) => Some((s.clone(), pos)),
                 (Token::Reserved(s), pos) if is_valid_identifier(s.chars()) => {
@@ -2510,7 +2653,7 @@ fn parse_export(
             None
         };
 
-        exports.push(((id, id_pos), rename));
+        exports.push(((id, id_pos), modules, rename));
 
         match input.peek().unwrap() {
             (Token::Comma, _) => {
@@ -2527,18 +2670,31 @@ fn parse_export(
         }
     }
 
-    // Check for duplicating parameters
+    // Check for duplicated exports - the same variable, or the same exported name
     exports
         .iter()
         .enumerate()
-        .try_for_each(|(i, ((id1, _), _))| {
+        .try_for_each(|(i, ((id1, _), modules1, rename1))| {
+            let name1 = rename1.as_ref().map(|(n, _)| n).unwrap_or(id1);
+
             exports
                 .iter()
                 .skip(i + 1)
-                .find(|((id2, _), _)| id2 == id1)
-                .map_or_else(|| Ok(()), |((id2, pos), _)| Err((id2, *pos)))
+                .find(|((id2, _), modules2, rename2)| {
+                    let name2 = rename2.as_ref().map(|(n, _)| n).unwrap_or(id2);
+
+                    (modules1.is_none() && modules2.is_none() && id2 == id1)
+                        || (name2 == name1 && !(modules2.is_some() && id2 == "*"))
+                })
+                .map_or_else(
+                    || Ok(()),
+                    |((id2, pos2), _, rename2)| match rename2 {
+                        Some((name2, pos)) if name2 == name1 => Err((name2, *pos)),
+                        _ => Err((id2, *pos2)),
+                    },
+                )
         })
-        .map_err(|(id2, pos)| PERR::DuplicatedExport(id2.to_string()).into_err(pos))?;
+        .map_err(|(name, pos)| PERR::DuplicatedExport(name.to_string()).into_err(pos))?;
 
     Ok(Stmt::Export(Box::new(exports)))
 }
@@ -2857,6 +3013,9 @@ fn parse_fn(
         params,
         body,
         pos: settings.pos,
+        source: None,
+        #[cfg(not(feature = "no_module"))]
+        env: None,
     })
 }
 
@@ -2948,6 +3107,9 @@ fn parse_anon_fn(
         params,
         body,
         pos: settings.pos,
+        source: None,
+        #[cfg(not(feature = "no_module"))]
+        env: None,
     };
 
     let expr = Expr::FnPointer(Box::new((fn_name.into(), settings.pos)));
@@ -0,0 +1,135 @@
+//! Support for procedural macros exporting Rust items to Rhai.
+
+use crate::engine::Engine;
+
+/// Trait implemented by custom types that know how to register themselves into an [`Engine`].
+///
+/// Usually implemented via `#[derive(RhaiType)]` and used via [`Engine::register_rhai_type`].
+///
+/// # Example
+///
+/// ```
+/// use rhai::{Engine, EvalAltResult, RegisterFn, INT};
+/// use rhai::plugins::RhaiType;
+///
+/// #[derive(Debug, Clone, PartialEq, RhaiType)]
+/// struct TestStruct {
+///     x: INT,
+///     #[rhai(get, rename = "value")]
+///     y: INT,
+///     #[rhai(skip)]
+///     secret: bool,
+/// }
+///
+/// # fn main() -> Result<(), Box<EvalAltResult>> {
+/// let mut engine = Engine::new();
+///
+/// engine.register_rhai_type::<TestStruct>()
+///       .register_fn("new_ts", || TestStruct { x: 1, y: 42, secret: false });
+///
+/// assert_eq!(engine.eval::<INT>("let a = new_ts(); a.x = 2; a.x + a.value")?, 44);
+/// assert_eq!(engine.eval::<String>("type_of(new_ts())")?, "TestStruct");
+/// assert_eq!(engine.eval::<bool>("new_ts() == new_ts()")?, true);
+/// # Ok(())
+/// # }
+/// ```
+pub trait RhaiType {
+    /// Register this type, together with its getters, setters and other functions, into an [`Engine`].
+    fn register_rhai_type(engine: &mut Engine);
+}
+
+/// Helpers used by code generated via `#[derive(RhaiType)]`.  Not part of the public API.
+///
+/// Functions based on optional traits (e.g. `Display`) are registered only when the type
+/// implements them.  Methods are called on `&&&Probe<T>`, so the implementation on the
+/// most-referenced probe type that applies to `T` is picked, falling back to doing nothing.
+#[doc(hidden)]
+pub mod private {
+    use crate::any::Variant;
+    use crate::engine::{Engine, FN_TO_STRING, KEYWORD_DEBUG, KEYWORD_PRINT};
+    use crate::fn_register::RegisterFn;
+    use crate::parser::ImmutableString;
+
+    use crate::stdlib::{
+        fmt::{Debug, Display},
+        format,
+        marker::PhantomData,
+        string::ToString,
+    };
+
+    /// Probe for the traits implemented by a type.
+    pub struct Probe<T>(PhantomData<T>);
+
+    impl<T> Probe<T> {
+        pub fn new() -> Self {
+            Self(PhantomData)
+        }
+    }
+
+    impl<T> Default for Probe<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    /// `to_string` and `print` via `Display`.
+    pub trait RegisterDisplay {
+        fn register_to_string(&self, engine: &mut Engine);
+    }
+    impl<T: Variant + Clone + Display> RegisterDisplay for &&Probe<T> {
+        fn register_to_string(&self, engine: &mut Engine) {
+            engine.register_fn(FN_TO_STRING, |x: &mut T| -> ImmutableString {
+                x.to_string().into()
+            });
+            engine.register_fn(KEYWORD_PRINT, |x: &mut T| -> ImmutableString {
+                x.to_string().into()
+            });
+        }
+    }
+
+    /// `to_string` and `print` via `Debug`, for types not implementing `Display`.
+    pub trait RegisterDebugString {
+        fn register_to_string(&self, engine: &mut Engine);
+    }
+    impl<T: Variant + Clone + Debug> RegisterDebugString for &Probe<T> {
+        fn register_to_string(&self, engine: &mut Engine) {
+            engine.register_fn(FN_TO_STRING, |x: &mut T| -> ImmutableString {
+                format!("{:?}", x).into()
+            });
+            engine.register_fn(KEYWORD_PRINT, |x: &mut T| -> ImmutableString {
+                format!("{:?}", x).into()
+            });
+        }
+    }
+
+    /// `debug` via `Debug`.
+    pub trait RegisterDebug {
+        fn register_debug(&self, engine: &mut Engine);
+    }
+    impl<T: Variant + Clone + Debug> RegisterDebug for &Probe<T> {
+        fn register_debug(&self, engine: &mut Engine) {
+            engine.register_fn(KEYWORD_DEBUG, |x: &mut T| -> ImmutableString {
+                format!("{:?}", x).into()
+            });
+        }
+    }
+
+    /// `==` and `!=` via `PartialEq`.
+    pub trait RegisterEq {
+        fn register_eq(&self, engine: &mut Engine);
+    }
+    impl<T: Variant + Clone + PartialEq> RegisterEq for &Probe<T> {
+        fn register_eq(&self, engine: &mut Engine) {
+            engine.register_fn("==", |x: &mut T, y: T| *x == y);
+            engine.register_fn("!=", |x: &mut T, y: T| *x != y);
+        }
+    }
+
+    /// Fallback when the type implements none of the optional traits.
+    pub trait RegisterNone {
+        fn register_to_string(&self, _engine: &mut Engine) {}
+        fn register_debug(&self, _engine: &mut Engine) {}
+        fn register_eq(&self, _engine: &mut Engine) {}
+    }
+    impl<T> RegisterNone for Probe<T> {}
+}
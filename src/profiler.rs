@@ -0,0 +1,362 @@
+//! Module implementing the opt-in per-function profiler.
+#![cfg(not(feature = "no_std"))]
+
+use crate::engine::{Engine, State};
+use crate::token::Position;
+
+use crate::stdlib::{
+    collections::HashMap,
+    fmt::Write,
+    format, mem,
+    string::{String, ToString},
+    time::Duration,
+    vec::Vec,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::stdlib::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+
+#[cfg(not(feature = "sync"))]
+use crate::stdlib::cell::RefCell;
+
+#[cfg(feature = "sync")]
+use crate::stdlib::sync::RwLock;
+
+/// Name of the root stack frame in folded stacks.
+pub const PROFILE_ROOT: &str = "<main>";
+
+/// Profiling statistics of a single function.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FnProfile {
+    /// Name of the function.
+    pub name: String,
+    /// Is the function script-defined?
+    pub is_script: bool,
+    /// Number of times the function is called.
+    pub calls: u64,
+    /// Number of operations performed in the function, including the functions it calls.
+    ///
+    /// Recursive calls are counted only once.
+    pub inclusive_operations: u64,
+    /// Number of operations performed in the function, excluding the functions it calls.
+    pub exclusive_operations: u64,
+    /// Time spent in the function, including the functions it calls.
+    ///
+    /// Recursive calls are counted only once.
+    pub inclusive_time: Duration,
+    /// Time spent in the function, excluding the functions it calls.
+    pub exclusive_time: Duration,
+}
+
+/// Profiling statistics of a single line of script.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct LineProfile {
+    /// Position of the beginning of the line.
+    pub position: Position,
+    /// Number of statements evaluated on the line.
+    pub hits: u64,
+    /// Number of operations performed on the line, excluding the functions it calls.
+    pub operations: u64,
+}
+
+/// Metric to use as the sample value when exporting a `Profile`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ProfileMetric {
+    /// Number of operations.
+    Operations,
+    /// Wall time, in microseconds.
+    WallTime,
+}
+
+/// Profiling result collected by an `Engine` with profiling turned on.
+///
+/// Not available under the `no_std` feature.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    /// Statistics of all called functions, sorted by exclusive operations in descending order.
+    pub functions: Vec<FnProfile>,
+    /// Statistics of all evaluated lines, sorted by operations in descending order.
+    pub lines: Vec<LineProfile>,
+    /// Exclusive operations and time, keyed by call stack.
+    stacks: Vec<(String, u64, Duration)>,
+}
+
+impl Profile {
+    /// Get the statistics of a function.
+    ///
+    /// If there are both a script-defined and a native function with the same name,
+    /// the script-defined function is returned.
+    pub fn get_fn(&self, name: &str) -> Option<&FnProfile> {
+        self.functions
+            .iter()
+            .filter(|f| f.name == name)
+            .max_by_key(|f| f.is_script)
+    }
+
+    /// Get the statistics of a line.
+    pub fn get_line(&self, line: usize) -> Option<&LineProfile> {
+        self.lines.iter().find(|l| l.position.line() == Some(line))
+    }
+
+    /// Total number of operations recorded.
+    pub fn total_operations(&self) -> u64 {
+        self.stacks.iter().map(|(_, ops, _)| ops).sum()
+    }
+
+    /// Export the profile in the folded-stack format understood by flamegraph tools,
+    /// i.e. one line per call stack with frames separated by `;` followed by the sample value.
+    ///
+    /// The root frame is `<main>`, which holds everything evaluated outside of functions.
+    /// Wall time is not measured for the root frame itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, ProfileMetric};
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.set_profiling(true);
+    ///
+    /// engine.consume("fn foo() { 42 } foo();")?;
+    ///
+    /// let profile = engine.take_profile().unwrap();
+    /// let folded = profile.to_folded_stacks(ProfileMetric::Operations);
+    ///
+    /// assert!(folded.lines().any(|line| line.starts_with("<main>;foo ")));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_folded_stacks(&self, metric: ProfileMetric) -> String {
+        let mut output = String::new();
+
+        for (stack, ops, time) in &self.stacks {
+            let value = match metric {
+                ProfileMetric::Operations => *ops,
+                ProfileMetric::WallTime => time.as_micros() as u64,
+            };
+
+            if value > 0 {
+                writeln!(output, "{} {}", stack, value).unwrap();
+            }
+        }
+
+        output
+    }
+}
+
+/// A function call in progress.
+#[derive(Debug, Clone)]
+struct Frame {
+    /// Key of the function.
+    key: (String, bool),
+    /// Call stack leading to (and including) this function.
+    stack: String,
+    /// Number of operations when the function is called.
+    start_operations: u64,
+    /// Number of operations performed in called functions.
+    child_operations: u64,
+    /// Time when the function is called.
+    start_time: Instant,
+    /// Time spent in called functions.
+    child_time: Duration,
+}
+
+/// Data being collected by the profiler.
+#[derive(Debug, Clone, Default)]
+struct ProfileData {
+    /// Stack of function calls in progress.
+    frames: Vec<Frame>,
+    /// Line of the statement being evaluated.
+    line: Option<usize>,
+    /// Statistics of functions, keyed by name and whether it is script-defined.
+    functions: HashMap<(String, bool), FnProfile>,
+    /// Statistics of lines.
+    lines: HashMap<usize, LineProfile>,
+    /// Exclusive operations and time, keyed by call stack.
+    stacks: HashMap<String, (u64, Duration)>,
+}
+
+/// Profiler attached to an `Engine`.
+#[derive(Debug, Default)]
+pub(crate) struct Profiler {
+    #[cfg(not(feature = "sync"))]
+    data: RefCell<ProfileData>,
+    #[cfg(feature = "sync")]
+    data: RwLock<ProfileData>,
+}
+
+impl Profiler {
+    /// Run a closure on the data being collected.
+    fn with<T>(&self, f: impl FnOnce(&mut ProfileData) -> T) -> T {
+        #[cfg(not(feature = "sync"))]
+        return f(&mut self.data.borrow_mut());
+        #[cfg(feature = "sync")]
+        return f(&mut self.data.write().unwrap());
+    }
+
+    /// Record a function call.
+    pub fn enter_fn(&self, name: &str, is_script: bool, operations: u64, now: Instant) {
+        self.with(|data| {
+            let stack = match data.frames.last() {
+                Some(frame) => format!("{};{}", frame.stack, name),
+                None => format!("{};{}", PROFILE_ROOT, name),
+            };
+
+            data.frames.push(Frame {
+                key: (name.to_string(), is_script),
+                stack,
+                start_operations: operations,
+                child_operations: 0,
+                start_time: now,
+                child_time: Default::default(),
+            });
+        });
+    }
+
+    /// Record the return from the last function call.
+    pub fn exit_fn(&self, operations: u64, now: Instant) {
+        self.with(|data| {
+            let frame = match data.frames.pop() {
+                Some(frame) => frame,
+                None => return,
+            };
+
+            let inclusive_operations = operations.saturating_sub(frame.start_operations);
+            let exclusive_operations = inclusive_operations.saturating_sub(frame.child_operations);
+            let inclusive_time = now.saturating_duration_since(frame.start_time);
+            let exclusive_time = inclusive_time
+                .checked_sub(frame.child_time)
+                .unwrap_or_default();
+
+            if let Some(parent) = data.frames.last_mut() {
+                parent.child_operations += inclusive_operations;
+                parent.child_time += inclusive_time;
+            }
+
+            // Do not count recursive calls twice
+            let is_recursive = data.frames.iter().any(|f| f.key == frame.key);
+
+            let (name, is_script) = frame.key.clone();
+            let stats = data
+                .functions
+                .entry(frame.key)
+                .or_insert_with(|| FnProfile {
+                    name,
+                    is_script,
+                    calls: 0,
+                    inclusive_operations: 0,
+                    exclusive_operations: 0,
+                    inclusive_time: Default::default(),
+                    exclusive_time: Default::default(),
+                });
+
+            stats.calls += 1;
+            stats.exclusive_operations += exclusive_operations;
+            stats.exclusive_time += exclusive_time;
+
+            if !is_recursive {
+                stats.inclusive_operations += inclusive_operations;
+                stats.inclusive_time += inclusive_time;
+            }
+
+            let entry = data.stacks.entry(frame.stack).or_default();
+            entry.0 += exclusive_operations;
+            entry.1 += exclusive_time;
+        });
+    }
+
+    /// Record the evaluation of a statement, returning the line of the previous statement.
+    pub fn enter_stmt(&self, pos: Position) -> Option<usize> {
+        self.with(|data| {
+            let line = pos.line();
+
+            if let Some(line) = line {
+                data.lines
+                    .entry(line)
+                    .or_insert_with(|| LineProfile {
+                        position: Position::new(line as u16, 0),
+                        hits: 0,
+                        operations: 0,
+                    })
+                    .hits += 1;
+            }
+
+            mem::replace(&mut data.line, line)
+        })
+    }
+
+    /// Record the end of the evaluation of a statement.
+    pub fn exit_stmt(&self, prev_line: Option<usize>) {
+        self.with(|data| data.line = prev_line);
+    }
+
+    /// Record an operation.
+    pub fn inc_operations(&self) {
+        self.with(|data| {
+            if let Some(line) = data.line {
+                if let Some(stats) = data.lines.get_mut(&line) {
+                    stats.operations += 1;
+                }
+            }
+
+            // Operations outside of functions go to the root frame
+            if data.frames.is_empty() {
+                data.stacks.entry(PROFILE_ROOT.into()).or_default().0 += 1;
+            }
+        });
+    }
+
+    /// Take the data collected so far, leaving function calls in progress untouched.
+    pub fn take(&self) -> Profile {
+        self.with(|data| {
+            let mut functions: Vec<_> = data.functions.drain().map(|(_, v)| v).collect();
+            functions.sort_by(|a, b| {
+                b.exclusive_operations
+                    .cmp(&a.exclusive_operations)
+                    .then_with(|| b.exclusive_time.cmp(&a.exclusive_time))
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+
+            let mut lines: Vec<_> = data.lines.drain().map(|(_, v)| v).collect();
+            lines.sort_by(|a, b| {
+                b.operations
+                    .cmp(&a.operations)
+                    .then_with(|| b.hits.cmp(&a.hits))
+                    .then_with(|| a.position.line().cmp(&b.position.line()))
+            });
+
+            let mut stacks: Vec<_> = data
+                .stacks
+                .drain()
+                .map(|(k, (ops, time))| (k, ops, time))
+                .collect();
+            stacks.sort_by(|a, b| a.0.cmp(&b.0));
+
+            Profile {
+                functions,
+                lines,
+                stacks,
+            }
+        })
+    }
+}
+
+impl Engine {
+    /// Record a function call in the profiler, if profiling is turned on.
+    pub(crate) fn profile_enter_fn(&self, state: &State, name: &str, is_script: bool) {
+        if let Some(profiler) = &self.profiler {
+            profiler.enter_fn(name, is_script, state.operations, self.clock.instant());
+        }
+    }
+
+    /// Record the return from a function call in the profiler, if profiling is turned on.
+    pub(crate) fn profile_exit_fn(&self, state: &State) {
+        if let Some(profiler) = &self.profiler {
+            profiler.exit_fn(state.operations, self.clock.instant());
+        }
+    }
+}
@@ -10,6 +10,7 @@ use crate::stdlib::{
     error::Error,
     fmt,
     string::{String, ToString},
+    vec::Vec,
 };
 
 #[cfg(not(feature = "no_std"))]
@@ -34,6 +35,14 @@ pub enum EvalAltResult {
     #[cfg(not(target_arch = "wasm32"))]
     ErrorReadingScriptFile(PathBuf, Position, std::io::Error),
 
+    /// Access to a module script file denied by a sandboxed module resolver.
+    /// Wrapped values are the path of the script file and the reason.
+    ///
+    /// Never appears under the `no_std` feature.
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(target_arch = "wasm32"))]
+    ErrorModuleAccessDenied(PathBuf, String, Position),
+
     /// Call to an unknown function. Wrapped value is the name of the function.
     ErrorFunctionNotFound(String, Position),
     /// An error has occurred inside a called function.
@@ -69,6 +78,9 @@ pub enum EvalAltResult {
     ErrorVariableNotFound(String, Position),
     /// Usage of an unknown module. Wrapped value is the name of the module.
     ErrorModuleNotFound(String, Position),
+    /// A module imports itself, directly or indirectly.
+    /// Wrapped value is the chain of imported modules, starting and ending with the same module.
+    ErrorCircularImport(Vec<String>, Position),
     /// Assignment to an inappropriate LHS (left-hand-side) expression.
     ErrorAssignmentToUnknownLHS(Position),
     /// Assignment to a constant variable.
@@ -108,6 +120,9 @@ impl EvalAltResult {
             #[cfg(not(feature = "no_std"))]
             #[cfg(not(target_arch = "wasm32"))]
             Self::ErrorReadingScriptFile(_, _, _) => "Cannot read from script file",
+            #[cfg(not(feature = "no_std"))]
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::ErrorModuleAccessDenied(_, _, _) => "Access denied to module script file",
 
             Self::ErrorParsing(p, _) => p.desc(),
             Self::ErrorInFunctionCall(_, _, _) => "Error in called function",
@@ -137,6 +152,7 @@ impl EvalAltResult {
             Self::ErrorFor(_) => "For loop expects an array, object map, or range",
             Self::ErrorVariableNotFound(_, _) => "Variable not found",
             Self::ErrorModuleNotFound(_, _) => "module not found",
+            Self::ErrorCircularImport(_, _) => "Circular import",
             Self::ErrorAssignmentToUnknownLHS(_) => {
                 "Assignment to an unsupported left-hand side expression"
             }
@@ -171,6 +187,11 @@ impl fmt::Display for EvalAltResult {
             Self::ErrorReadingScriptFile(path, _, err) => {
                 write!(f, "{} '{}': {}", desc, path.display(), err)?
             }
+            #[cfg(not(feature = "no_std"))]
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::ErrorModuleAccessDenied(path, reason, _) => {
+                write!(f, "{} '{}': {}", desc, path.display(), reason)?
+            }
 
             Self::ErrorParsing(p, _) => write!(f, "Syntax error: {}", p)?,
 
@@ -182,6 +203,8 @@ impl fmt::Display for EvalAltResult {
             | Self::ErrorVariableNotFound(s, _)
             | Self::ErrorModuleNotFound(s, _) => write!(f, "{}: '{}'", desc, s)?,
 
+            Self::ErrorCircularImport(chain, _) => write!(f, "{}: {}", desc, chain.join(" -> "))?,
+
             Self::ErrorDotExpr(s, _) if !s.is_empty() => write!(f, "{} {}", desc, s)?,
 
             Self::ErrorIndexingType(_, _)
@@ -272,6 +295,9 @@ impl EvalAltResult {
             #[cfg(not(feature = "no_std"))]
             #[cfg(not(target_arch = "wasm32"))]
             Self::ErrorReadingScriptFile(_, pos, _) => *pos,
+            #[cfg(not(feature = "no_std"))]
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::ErrorModuleAccessDenied(_, _, pos) => *pos,
 
             Self::ErrorParsing(_, pos)
             | Self::ErrorFunctionNotFound(_, pos)
@@ -289,6 +315,7 @@ impl EvalAltResult {
             | Self::ErrorFor(pos)
             | Self::ErrorVariableNotFound(_, pos)
             | Self::ErrorModuleNotFound(_, pos)
+            | Self::ErrorCircularImport(_, pos)
             | Self::ErrorAssignmentToUnknownLHS(pos)
             | Self::ErrorAssignmentToConstant(_, pos)
             | Self::ErrorMismatchOutputType(_, _, pos)
@@ -312,6 +339,9 @@ impl EvalAltResult {
             #[cfg(not(feature = "no_std"))]
             #[cfg(not(target_arch = "wasm32"))]
             Self::ErrorReadingScriptFile(_, pos, _) => *pos = new_position,
+            #[cfg(not(feature = "no_std"))]
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::ErrorModuleAccessDenied(_, _, pos) => *pos = new_position,
 
             Self::ErrorParsing(_, pos)
             | Self::ErrorFunctionNotFound(_, pos)
@@ -329,6 +359,7 @@ impl EvalAltResult {
             | Self::ErrorFor(pos)
             | Self::ErrorVariableNotFound(_, pos)
             | Self::ErrorModuleNotFound(_, pos)
+            | Self::ErrorCircularImport(_, pos)
             | Self::ErrorAssignmentToUnknownLHS(pos)
             | Self::ErrorAssignmentToConstant(_, pos)
             | Self::ErrorMismatchOutputType(_, _, pos)
@@ -3,7 +3,7 @@
 use crate::engine::Engine;
 use crate::module::ModuleResolver;
 use crate::optimize::OptimizationLevel;
-use crate::packages::PackageLibrary;
+use crate::packages::{PackageLibrary, RandomState};
 use crate::token::{is_valid_identifier, Token};
 
 use crate::stdlib::{boxed::Box, format, string::String};
@@ -37,6 +37,35 @@ impl Engine {
         self.optimization_level
     }
 
+    /// Seed the random number generator used by the `RandomPackage`.
+    ///
+    /// The same seed always produces the same sequence of random numbers, so seeding before
+    /// each evaluation makes script runs reproducible.
+    /// By default, the generator is seeded from the system clock.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, INT};
+    /// use rhai::packages::{Package, RandomPackage};
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.load_package(RandomPackage::new().get());
+    ///
+    /// engine.set_random_seed(42);
+    /// let first = engine.eval::<INT>("rand()")?;
+    ///
+    /// engine.set_random_seed(42);
+    /// assert_eq!(engine.eval::<INT>("rand()")?, first);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_random_seed(&mut self, seed: u64) -> &mut Self {
+        self.random = RandomState::new(seed);
+        self
+    }
+
     /// Set the maximum levels of function calls allowed for a script in order to avoid
     /// infinite recursion and stack overflows.
     #[cfg(not(feature = "unchecked"))]
@@ -169,6 +198,99 @@ impl Engine {
         self
     }
 
+    /// Turn lazy imports on or off.
+    ///
+    /// When turned on, an `import` statement only evaluates the path of the module,
+    /// and the module is resolved when it is first used.
+    /// Errors resolving the module are then reported on first use instead of at the `import` statement.
+    ///
+    /// Not available under the `no_module` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.set_lazy_imports(true);
+    ///
+    /// // The module is never used, so it is never resolved
+    /// engine.consume(r#"import "does_not_exist" as m;"#)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_module"))]
+    pub fn set_lazy_imports(&mut self, enable: bool) -> &mut Self {
+        self.lazy_imports = enable;
+        self
+    }
+
+    /// Are imported modules resolved on first use?
+    ///
+    /// Not available under the `no_module` feature.
+    #[cfg(not(feature = "no_module"))]
+    pub fn lazy_imports(&self) -> bool {
+        self.lazy_imports
+    }
+
+    /// Turn profiling of script evaluation on or off.
+    ///
+    /// When profiling is turned on, the `Engine` records call counts, operations and wall time
+    /// of each function called, plus the number of operations performed on each line of script.
+    /// Use `take_profile` to retrieve the statistics.
+    ///
+    /// Turning profiling off discards all statistics collected so far.
+    ///
+    /// Not available under the `no_std` feature.
+    #[cfg(not(feature = "no_std"))]
+    pub fn set_profiling(&mut self, enable: bool) -> &mut Self {
+        self.profiler = if enable {
+            Some(self.profiler.take().unwrap_or_default())
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Is profiling turned on?
+    ///
+    /// Not available under the `no_std` feature.
+    #[cfg(not(feature = "no_std"))]
+    pub fn is_profiling(&self) -> bool {
+        self.profiler.is_some()
+    }
+
+    /// Turn line and branch coverage of scripts on or off.
+    ///
+    /// When coverage is turned on, the `Engine` records the number of times each statement
+    /// is evaluated, and each branch of `if` statements and `&&`/`||` operators is taken.
+    /// Use `take_coverage` to retrieve the statistics.
+    ///
+    /// Scripts compiled while coverage is turned on are _not_ optimized, so that coverage
+    /// is computed against the original script. Turn coverage on _before_ compiling scripts.
+    ///
+    /// Turning coverage off discards all statistics collected so far.
+    ///
+    /// Not available under the `no_std` feature.
+    #[cfg(not(feature = "no_std"))]
+    pub fn set_coverage(&mut self, enable: bool) -> &mut Self {
+        self.coverage = if enable {
+            Some(self.coverage.take().unwrap_or_default())
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Is coverage turned on?
+    ///
+    /// Not available under the `no_std` feature.
+    #[cfg(not(feature = "no_std"))]
+    pub fn is_coverage_enabled(&self) -> bool {
+        self.coverage.is_some()
+    }
+
     /// Disable a particular keyword or operator in the language.
     ///
     /// # Examples
@@ -1,5 +1,5 @@
 #![cfg(not(feature = "no_index"))]
-use rhai::{Array, Engine, EvalAltResult, RegisterFn, INT};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, RegisterFn, Scope, INT};
 
 #[test]
 fn test_arrays() -> Result<(), Box<EvalAltResult>> {
@@ -108,3 +108,218 @@ fn test_array_with_structs() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+fn test_array_functions() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = [3, 1, 4, 1, 5];
+                x.sort();
+                x.dedup();
+                x.reverse();
+                x[0] * 1000 + x[1] * 100 + x[2] * 10 + x[3] + x.len * 10000
+            "
+        )?,
+        45431
+    );
+    assert!(engine.eval::<bool>("let x = [1, 2, 3]; x.contains(2) && !(x.contains('2'))")?);
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = [1, 2, 3, 4, 5];
+                x.splice(1, 2, [10, 20, 30]);
+                let y = x.extract(1, 3);
+                let z = x.drain(0, 2);
+                x.len * 100 + y[2] + z[1] + x.chunks(2).len * 1000
+            "
+        )?,
+        2440
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_function"))]
+fn test_array_higher_order_functions() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = [1, 2, 3, 4, 5];
+                x.map(|v| v * v).filter(|v| v > 5).reduce(|sum, v| sum + v)
+            "
+        )?,
+        50
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn weight(v, i) { v * i }
+                let x = [1, 2, 3];
+                x.map(Fn("weight")).reduce(|sum, v| sum + v, 100)
+            "#
+        )?,
+        108
+    );
+    assert!(engine.eval::<bool>(
+        r"
+            let x = [1, 2, 3];
+            x.some(|v| v > 2) && !(x.all(|v| v > 2)) && x.find(|v| v > 1) == 2
+                && x.index_of(|v| v > 1) == 1 && x.index_of(|v| v > 9) == -1
+        "
+    )?);
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = [3, 9, 1, 7];
+                x.sort(|a, b| b - a);
+                let odd = x.retain(|v, i| i % 2 == 0);
+                x[0] * 100 + x[1] * 10 + odd.len
+            "
+        )?,
+        932
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = [1, 2, 3, 4, 5, 6];
+                let even = x.drain(|v| v % 2 == 0);
+                x.dedup(|a, b| b - a < 3);
+                x.len * 10 + even.len
+            "
+        )?,
+        23
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_function"))]
+fn test_array_sort_bad_comparisons() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    #[cfg(not(feature = "no_float"))]
+    assert!(engine.eval::<bool>(
+        r"
+            let z = [1.0, 0.0/0.0, 2.0, 0.0/0.0, 0.5];
+            z.sort();
+            z[0] == 0.5 && z[1] == 1.0 && z[2] == 2.0 && z[3].is_nan && z[4].is_nan
+        "
+    )?);
+
+    // Comparators that are not consistent must not panic
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let x = [5, 3, 8, 1, 9, 2, 7, 4, 6, 0];
+                x.sort(|a, b| 1);
+                x.sort(|a, b| (a * 7 + b * 3) % 5 - 2);
+                x.sort();
+                x.reduce(|s, v, i| if v == i { s + 1 } else { s }, 0)
+            "
+        )?,
+        10
+    );
+
+    // Errors stop the sort and leave the array unchanged
+    let mut scope = Scope::new();
+    scope.push("x", vec![Dynamic::from(3 as INT), Dynamic::from(1 as INT)]);
+
+    assert!(matches!(
+        *engine
+            .eval_with_scope::<()>(&mut scope, r#"x.sort(|a, b| { throw "boom"; })"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(f, _, _) if f == "sort"
+    ));
+    assert!(matches!(
+        *engine
+            .eval_with_scope::<()>(&mut scope, r#"x.sort(|a, b| "not a number")"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(f, _, _) if f == "sort"
+    ));
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "x[0] * 10 + x[1]")?,
+        31
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_array_callback_errors() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let mut scope = Scope::new();
+    scope.push(
+        "a",
+        vec![
+            Dynamic::from(1 as INT),
+            Dynamic::from(2 as INT),
+            Dynamic::from(2 as INT),
+        ],
+    );
+
+    // Errors in callbacks leave the array unchanged
+    for script in &[
+        r#"a.retain(|x| if x == 2 { throw "boom" } else { true })"#,
+        r#"a.drain(|x| if x == 2 { throw "boom" } else { true })"#,
+        r#"a.dedup(|x, y| { throw "boom"; })"#,
+    ] {
+        assert!(matches!(
+            *engine
+                .eval_with_scope::<()>(&mut scope, script)
+                .expect_err("should error"),
+            EvalAltResult::ErrorInFunctionCall(_, _, _)
+        ));
+        assert_eq!(
+            engine.eval_with_scope::<INT>(&mut scope, "a.len() * 100 + a[1] * 10 + a[2]")?,
+            322
+        );
+    }
+
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "a.dedup(); a.len()")?,
+        2
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_function"))]
+fn test_array_fold() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>("[1, 2, 3].fold(10, |sum, v| sum + v)")?,
+        16
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"[1, 2, 3].fold("", |s, v| s + v).len"#)?,
+        3
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"[1, 2].fold(#{}, |m, v| { m["k" + v] = v; m }).len()"#)?,
+        2
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"[1, 2].reduce(|m, v| { m["k" + v] = v; m }, #{}).len()"#)?,
+        2
+    );
+    assert_eq!(
+        engine.eval::<INT>("[[1], [2, 3]].fold([], |a, v| a + v).len()")?,
+        3
+    );
+
+    Ok(())
+}
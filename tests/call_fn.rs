@@ -1,6 +1,7 @@
 #![cfg(not(feature = "no_function"))]
 use rhai::{
-    Dynamic, Engine, EvalAltResult, FnPtr, Func, Module, ParseError, ParseErrorType, Scope, INT,
+    Dynamic, Engine, EvalAltResult, FnPtr, Func, Module, NativeCallContext, ParseError,
+    ParseErrorType, Scope, INT,
 };
 use std::any::TypeId;
 
@@ -126,18 +127,12 @@ fn test_fn_ptr_raw() -> Result<(), Box<EvalAltResult>> {
             TypeId::of::<FnPtr>(),
             TypeId::of::<INT>(),
         ],
-        move |engine: &Engine, lib: &Module, args: &mut [&mut Dynamic]| {
+        move |context: &NativeCallContext, args: &mut [&mut Dynamic]| {
             let fp = std::mem::take(args[1]).cast::<FnPtr>();
             let value = args[2].clone();
             let this_ptr = args.get_mut(0).unwrap();
 
-            engine.call_fn_dynamic(
-                &mut Scope::new(),
-                lib,
-                fp.fn_name(),
-                Some(this_ptr),
-                [value],
-            )?;
+            fp.call_with_context(context, Some(this_ptr), [value])?;
 
             Ok(())
         },
@@ -166,9 +161,14 @@ fn test_fn_ptr_curry_call() -> Result<(), Box<EvalAltResult>> {
     module.set_raw_fn(
         "call_with_arg",
         &[TypeId::of::<FnPtr>(), TypeId::of::<INT>()],
-        |engine: &Engine, module: &Module, args: &mut [&mut Dynamic]| {
+        |context: &NativeCallContext, args: &mut [&mut Dynamic]| {
             let fn_ptr = std::mem::take(args[0]).cast::<FnPtr>();
-            fn_ptr.call_dynamic(engine, module, None, [std::mem::take(args[1])])
+            fn_ptr.call_dynamic(
+                context.engine(),
+                context.lib(),
+                None,
+                [std::mem::take(args[1])],
+            )
         },
     );
 
@@ -190,3 +190,62 @@ fn test_fn_ptr_curry_call() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_native_call_context() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine
+        .register_raw_fn_1::<FnPtr, INT>("call_twice", |context, args| {
+            let fn_ptr = std::mem::take(args[0]).cast::<FnPtr>();
+            let x = fn_ptr.call_with_context(context, None, [Dynamic::from(1 as INT)])?;
+            let y = fn_ptr.call_with_context(context, None, [x])?;
+            Ok(y.cast::<INT>())
+        })
+        .register_raw_fn_1::<INT, INT>("call_double", |context, args| {
+            let mut value = std::mem::take(args[0]);
+            let result = context.call_fn_dynamic_raw("double", false, &mut [&mut value])?;
+            Ok(result.cast::<INT>())
+        })
+        .register_raw_fn_0::<INT>("line", |context, _| {
+            Ok(context.position().line().unwrap() as INT)
+        })
+        .register_raw_fn_0::<INT>("call_no_object", |context, _| {
+            let result = context.call_fn_dynamic_raw("double", true, &mut [])?;
+            Ok(result.cast::<INT>())
+        });
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn add(x) { x + 20 }
+                call_twice(Fn("add"))
+            "#
+        )?,
+        41
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn double(x) { x * 2 }
+                call_double(21)
+            "#
+        )?,
+        42
+    );
+
+    assert_eq!(engine.eval::<INT>("\n\n  line()")?, 3);
+
+    assert!(matches!(
+        *engine.eval::<INT>("call_double(21)").expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(ref f, pos) if f.starts_with("double") && pos.line() == Some(1)
+    ));
+
+    assert!(matches!(
+        *engine.eval::<INT>("fn double() { this * 2 } call_no_object()").expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(ref f, _) if f == "double"
+    ));
+
+    Ok(())
+}
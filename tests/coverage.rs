@@ -0,0 +1,112 @@
+#![cfg(not(feature = "no_std"))]
+use rhai::{Engine, EvalAltResult, Position, BRANCH_NOT_TAKEN, BRANCH_TAKEN, INT};
+
+#[test]
+fn test_coverage_off() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert!(!engine.is_coverage_enabled());
+    assert_eq!(engine.eval::<INT>("40 + 2")?, 42);
+    assert!(engine.take_coverage().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_coverage_branches() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.set_coverage(true);
+
+    let mut ast = engine.compile(
+        r#"
+            let x = 0;
+            if true {
+                x = 1;
+            } else {
+                x = 2;
+            }
+            if x > 0 && x < 10 || false {
+                x += 1;
+            }
+            x
+        "#,
+    )?;
+    ast.set_source("branches.rhai");
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 2);
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 2);
+
+    let coverage = engine.take_coverage().unwrap();
+    let cov = coverage.get("branches.rhai").unwrap();
+
+    // Dead code removed by the optimizer must still be reported
+    assert_eq!(cov.lines[&4], 2);
+    assert_eq!(cov.lines[&6], 0);
+    assert_eq!(cov.branches[&(Position::new(3, 21), BRANCH_TAKEN)], 2);
+    assert_eq!(cov.branches[&(Position::new(3, 21), BRANCH_NOT_TAKEN)], 0);
+
+    // &&
+    assert_eq!(cov.branches[&(Position::new(8, 22), BRANCH_TAKEN)], 2);
+    assert_eq!(cov.branches[&(Position::new(8, 22), BRANCH_NOT_TAKEN)], 0);
+    // ||
+    assert_eq!(cov.branches[&(Position::new(8, 32), BRANCH_TAKEN)], 0);
+    assert_eq!(cov.branches[&(Position::new(8, 32), BRANCH_NOT_TAKEN)], 2);
+
+    assert_eq!(cov.branches.len(), 8);
+    assert_eq!(cov.branches_hit(), 4);
+
+    // Coverage is reset after being taken
+    assert_eq!(engine.take_coverage().unwrap().iter().count(), 0);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_coverage_functions() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.set_coverage(true);
+
+    let mut lib = engine.compile(
+        r#"
+            fn used(x) {
+                x * 2
+            }
+            fn unused(x) {
+                x * 3
+            }
+        "#,
+    )?;
+    lib.set_source("lib.rhai");
+
+    let mut main = engine.compile("let x = 21;\nused(x)")?;
+    main.set_source("main.rhai");
+
+    let ast = main.merge(&lib);
+
+    assert_eq!(ast.source(), Some("main.rhai"));
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+    assert_eq!(
+        engine.call_fn::<_, INT>(&mut Default::default(), &ast, "used", (1 as INT,))?,
+        2
+    );
+
+    let coverage = engine.take_coverage().unwrap();
+
+    let main = coverage.get("main.rhai").unwrap();
+    assert_eq!(main.lines.len(), 2);
+    assert_eq!(main.lines_hit(), 2);
+
+    // Function bodies are attributed to their own source
+    let lib = coverage.get("lib.rhai").unwrap();
+    assert_eq!(lib.lines[&3], 2);
+    assert_eq!(lib.lines[&6], 0);
+
+    let lcov = coverage.to_lcov();
+    assert!(lcov.starts_with("TN:\nSF:lib.rhai\n"));
+    assert!(lcov.contains("DA:3,2\nDA:5,0\nDA:6,0\nLF:4\nLH:2\nend_of_record\nTN:\nSF:main.rhai\n"));
+
+    Ok(())
+}
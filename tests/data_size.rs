@@ -49,6 +49,14 @@ fn test_max_string_size() -> Result<(), Box<EvalAltResult>> {
         EvalAltResult::ErrorDataTooLarge(_, 10, 100, _)
     ));
 
+    #[cfg(not(feature = "no_object"))]
+    assert!(matches!(
+        *engine
+            .eval::<String>(r#""hello".repeat(3)"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, 10, 15, _)
+    ));
+
     engine.set_max_string_size(0);
 
     assert_eq!(
@@ -108,6 +116,20 @@ fn test_max_array_size() -> Result<(), Box<EvalAltResult>> {
         EvalAltResult::ErrorDataTooLarge(_, 10, 100, _)
     ));
 
+    #[cfg(not(feature = "no_object"))]
+    assert!(matches!(
+        *engine
+            .eval::<Array>(
+                r"
+                    let x = [1,2,3,4,5,6];
+                    x.splice(1, 1, [7,8,9,10,11,12]);
+                    x
+                "
+            )
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, 10, 11, _)
+    ));
+
     assert!(matches!(
         *engine
             .eval::<Array>(
@@ -110,3 +110,61 @@ fn test_fn_ptr_curry() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_fn_ptr_carries_lib() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{Dynamic, FnPtr, Scope};
+
+    let mut engine = Engine::new();
+
+    // Call from Rust without the original AST
+    let f = engine.eval::<FnPtr>(
+        r#"
+            fn double(x) { x * 2 }
+            curry(|x, y| double(x + y), 1)
+        "#,
+    )?;
+
+    assert!(f.lib().is_some());
+    assert_eq!(
+        f.call_with_engine(&engine, None, [Dynamic::from(20 as INT)])?
+            .cast::<INT>(),
+        42
+    );
+
+    let g = engine.eval::<FnPtr>(r#"Fn("len")"#)?;
+
+    assert!(g.lib().is_none());
+    assert_eq!(
+        g.call_with_engine(&engine, None, [Dynamic::from("hello".to_string())])?
+            .cast::<INT>(),
+        5
+    );
+
+    // Pass into a separately-compiled script
+    let mut scope = Scope::new();
+    scope.push("f", f);
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "f.call(20) + call(f, 0)")?,
+        44
+    );
+
+    // The function pointer's own library takes precedence
+    engine.register_fn("double", |x: INT| x * 3);
+
+    assert_eq!(
+        engine.eval_with_scope::<INT>(
+            &mut scope,
+            r#"
+                fn double(x) { x * 4 }
+                call(f, 20) + double(1)
+            "#
+        )?,
+        46
+    );
+
+    Ok(())
+}
@@ -0,0 +1,210 @@
+use rhai::{Dynamic, Engine, EvalAltResult, ParseErrorType, Scope, INT};
+
+#[cfg(not(feature = "no_index"))]
+use rhai::Array;
+
+#[cfg(not(feature = "no_object"))]
+use rhai::Map;
+
+#[test]
+fn test_json_scalars() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.parse_json_value("42")?.cast::<INT>(), 42);
+    assert_eq!(engine.parse_json_value(" -7 ")?.cast::<INT>(), -7);
+    assert!(engine.parse_json_value("true")?.cast::<bool>());
+    assert_eq!(engine.parse_json_value("null")?.cast::<()>(), ());
+    assert_eq!(
+        engine
+            .parse_json_value(r#""a\"b\\c\nA😀""#)?
+            .cast::<String>(),
+        "a\"b\\c\nA\u{1F600}"
+    );
+
+    #[cfg(not(feature = "no_float"))]
+    {
+        assert_eq!(engine.parse_json_value("1.5")?.cast::<rhai::FLOAT>(), 1.5);
+        assert_eq!(
+            engine.parse_json_value("2e3")?.cast::<rhai::FLOAT>(),
+            2000.0
+        );
+        assert_eq!(Dynamic::from(1.0 as rhai::FLOAT).to_json()?, "1.0");
+        assert_eq!(Dynamic::from(rhai::FLOAT::INFINITY).to_json()?, "null");
+    }
+
+    assert_eq!(Dynamic::from(42 as INT).to_json()?, "42");
+    assert_eq!(Dynamic::from(()).to_json()?, "null");
+    assert_eq!(Dynamic::from('x').to_json()?, r#""x""#);
+    assert_eq!(
+        Dynamic::from("\"\\\n\t\u{1}".to_string()).to_json()?,
+        r#""\"\\\n\t\u0001""#
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+fn test_json_round_trip() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let json = r#"[1,{"a":null,"b":[true,false],"c":"hello"},[]]"#;
+    let value = engine.parse_json_value(json)?;
+    assert_eq!(value.to_json()?, json);
+
+    let array = value.cast::<Array>();
+    assert_eq!(array.len(), 3);
+    let map = array[1].clone().cast::<Map>();
+    assert_eq!(map.get("a").cloned().unwrap().cast::<()>(), ());
+    assert_eq!(map["c"].clone().cast::<String>(), "hello");
+
+    assert_eq!(
+        engine.eval::<String>(r#"to_json(#{ b: [1, ()], a: "x" })"#)?,
+        r#"{"a":"x","b":[1,null]}"#
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"let x = parse_json("[1, 2, {\"y\": 39}]"); x[0] + x[2].y"#)?,
+        40
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"let x = #{ a: [1, "\n"] }; to_json(parse_json(to_json(x)))"#)?,
+        r#"{"a":[1,"\n"]}"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_json_errors() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert!(matches!(
+        *engine.parse_json_value("tru").expect_err("should error"),
+        EvalAltResult::ErrorParsing(ParseErrorType::BadInput(_), _)
+    ));
+    assert!(matches!(
+        *engine.parse_json_value("42 43").expect_err("should error"),
+        EvalAltResult::ErrorParsing(ParseErrorType::BadInput(_), pos) if pos.position() == Some(4)
+    ));
+    assert!(matches!(
+        *engine
+            .parse_json_value(r#""abc"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorParsing(ParseErrorType::UnexpectedEOF, _)
+    ));
+
+    for text in &[
+        "01", "-01", "-", "1.", ".5", "1.e5", "1e", "1e+", "0x10", "1-2",
+    ] {
+        assert!(
+            matches!(
+                *engine.parse_json_value(text).expect_err("should error"),
+                EvalAltResult::ErrorParsing(ParseErrorType::BadInput(_), _)
+            ),
+            "{}",
+            text
+        );
+    }
+    assert_eq!(engine.parse_json_value("-0")?.cast::<INT>(), 0);
+
+    #[cfg(not(feature = "no_index"))]
+    assert!(matches!(
+        *engine.parse_json_value("[1,\n 2,]").expect_err("should error"),
+        EvalAltResult::ErrorParsing(ParseErrorType::BadInput(_), pos) if pos.line() == Some(2)
+    ));
+
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"to_json(Fn("foo"))"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(_, _)
+    ));
+    assert!(matches!(
+        *Dynamic::from(Engine::new_raw)
+            .to_json()
+            .expect_err("should error"),
+        EvalAltResult::ErrorRuntime(_, _)
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "unchecked"))]
+#[cfg(not(feature = "no_index"))]
+fn test_json_limits() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_max_array_size(3);
+    engine.set_max_string_size(5);
+
+    assert!(matches!(
+        *engine
+            .parse_json_value("[1,2,3,4]")
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, 3, 4, _)
+    ));
+    assert!(matches!(
+        *engine
+            .parse_json_value(r#""hello!""#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, 5, 6, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<String>(r#"to_json("abcd")"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, 5, 6, _)
+    ));
+
+    engine.set_max_expr_depths(10, 10);
+
+    assert!(matches!(
+        *engine
+            .parse_json_value("[[[[[[[[[[[1]]]]]]]]]]]")
+            .expect_err("should error"),
+        EvalAltResult::ErrorParsing(ParseErrorType::ExprTooDeep, _)
+    ));
+    assert_eq!(
+        engine.parse_json_value("[[[[1]]]]")?.cast::<Array>().len(),
+        1
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+fn test_json_depth_without_limit() -> Result<(), Box<EvalAltResult>> {
+    #[allow(unused_mut)]
+    let mut engine = Engine::new();
+
+    #[cfg(not(feature = "unchecked"))]
+    engine.set_max_expr_depths(0, 0);
+
+    let json = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+
+    assert!(matches!(
+        *engine.parse_json_value(&json).expect_err("should error"),
+        EvalAltResult::ErrorParsing(ParseErrorType::ExprTooDeep, _)
+    ));
+
+    let mut value = Dynamic::from(Array::new());
+    for _ in 0..1000 {
+        value = Dynamic::from(vec![value]);
+    }
+    let mut scope = Scope::new();
+    scope.push("value", value);
+
+    assert!(matches!(
+        *engine
+            .eval_with_scope::<String>(&mut scope, "to_json(value)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorRuntime(_, _)
+    ));
+
+    let json = format!("{}{}", "[".repeat(100), "]".repeat(100));
+    assert_eq!(engine.parse_json_value(&json)?.cast::<Array>().len(), 1);
+
+    Ok(())
+}
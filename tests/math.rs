@@ -99,3 +99,171 @@ fn test_math() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_math_int_functions() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("min(3, -7)")?, -7);
+    assert_eq!(engine.eval::<INT>("max(3, -7)")?, 3);
+    assert_eq!(engine.eval::<INT>("clamp(42, 0, 10)")?, 10);
+    assert_eq!(engine.eval::<INT>("clamp(-5, 0, 10)")?, 0);
+    assert_eq!(engine.eval::<INT>("let x = 5; x.clamp(0, 10)")?, 5);
+    assert_eq!(engine.eval::<INT>("signum(-42)")?, -1);
+    assert_eq!(engine.eval::<INT>("signum(0)")?, 0);
+    assert_eq!(engine.eval::<INT>("sqrt(99)")?, 9);
+    assert_eq!(engine.eval::<INT>("sqrt(100)")?, 10);
+    assert_eq!(engine.eval::<INT>("sqrt(1)")?, 1);
+    assert_eq!(engine.eval::<INT>("sqrt(0)")?, 0);
+    assert_eq!(engine.eval::<INT>("gcd(12, -18)")?, 6);
+    assert_eq!(engine.eval::<INT>("gcd(0, 0)")?, 0);
+    assert_eq!(engine.eval::<INT>("lcm(4, 6)")?, 12);
+    assert_eq!(engine.eval::<INT>("lcm(0, 6)")?, 0);
+    assert_eq!(engine.eval::<INT>("pow(2, 10)")?, 1024);
+
+    #[cfg(not(feature = "only_i32"))]
+    assert_eq!(
+        engine.eval::<INT>("sqrt(9223372036854775807)")?,
+        3_037_000_499
+    );
+
+    #[cfg(feature = "only_i32")]
+    assert_eq!(engine.eval::<INT>("sqrt(2147483647)")?, 46340);
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>("clamp(1, 10, 0)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorRuntime(_, _)
+    ));
+    assert!(matches!(
+        *engine.eval::<INT>("sqrt(-1)").expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(_, _)
+    ));
+
+    #[cfg(not(feature = "unchecked"))]
+    {
+        assert!(matches!(
+            *engine
+                .eval::<INT>("pow(2, 64)")
+                .expect_err("expects overflow"),
+            EvalAltResult::ErrorArithmetic(_, _)
+        ));
+        assert!(matches!(
+            *engine
+                .eval::<INT>("pow(2, -1)")
+                .expect_err("expects negative index"),
+            EvalAltResult::ErrorArithmetic(_, _)
+        ));
+
+        #[cfg(not(feature = "only_i32"))]
+        assert!(matches!(
+            *engine
+                .eval::<INT>("lcm(9223372036854775807, 2)")
+                .expect_err("expects overflow"),
+            EvalAltResult::ErrorArithmetic(_, _)
+        ));
+
+        #[cfg(feature = "only_i32")]
+        assert!(matches!(
+            *engine
+                .eval::<INT>("lcm(2147483647, 2)")
+                .expect_err("expects overflow"),
+            EvalAltResult::ErrorArithmetic(_, _)
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_math_bits() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("count_ones(0b1011)")?, 3);
+    assert_eq!(engine.eval::<INT>("trailing_zeros(0b1000)")?, 3);
+    assert_eq!(engine.eval::<INT>("rotate_left(0b1011, 4)")?, 0b1011_0000);
+    assert_eq!(engine.eval::<INT>("rotate_right(0b1011_0000, 4)")?, 0b1011);
+    assert_eq!(
+        engine.eval::<INT>("rotate_left(0b1011, -4)")?,
+        engine.eval::<INT>("rotate_right(0b1011, 4)")?
+    );
+    assert_eq!(engine.eval::<INT>("rotate_left(-1, 3)")?, -1);
+    assert!(engine.eval::<bool>("get_bit(0b100, 2)")?);
+    assert!(!engine.eval::<bool>("let x = 0b100; x.get_bit(1)")?);
+    assert_eq!(
+        engine.eval::<INT>("let x = 0b100; x.set_bit(0, true); x.set_bit(2, false); x")?,
+        1
+    );
+    assert_eq!(engine.eval::<INT>("get_bits(0xabcd, 4, 8)")?, 0xbc);
+    assert_eq!(engine.eval::<INT>("get_bits(0xabcd, 4, 0)")?, 0);
+    assert_eq!(engine.eval::<INT>("get_bits(-1, 0, 1)")?, 1);
+
+    #[cfg(not(feature = "only_i32"))]
+    {
+        assert_eq!(engine.eval::<INT>("leading_zeros(1)")?, 63);
+        assert_eq!(engine.eval::<INT>("count_zeros(0)")?, 64);
+        assert_eq!(engine.eval::<INT>("get_bits(-1, 0, 64)")?, -1);
+        assert!(engine.eval::<bool>("get_bit(-1, 63)")?);
+    }
+
+    #[cfg(feature = "only_i32")]
+    {
+        assert_eq!(engine.eval::<INT>("leading_zeros(1)")?, 31);
+        assert_eq!(engine.eval::<INT>("count_zeros(0)")?, 32);
+        assert_eq!(engine.eval::<INT>("get_bits(-1, 0, 32)")?, -1);
+        assert!(engine.eval::<bool>("get_bit(-1, 31)")?);
+    }
+
+    assert!(matches!(
+        *engine
+            .eval::<bool>("get_bit(1, 64)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(_, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<bool>("get_bit(1, -1)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(_, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<INT>("get_bits(1, 60, 10)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(_, _)
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_float"))]
+#[test]
+fn test_math_float_functions() -> Result<(), Box<EvalAltResult>> {
+    use rhai::FLOAT;
+
+    const EPSILON: FLOAT = 0.000_000_000_1;
+
+    let engine = Engine::new();
+
+    assert!((engine.eval::<FLOAT>("PI()")? - std::f64::consts::PI).abs() < EPSILON);
+    assert!((engine.eval::<FLOAT>("E()")? - std::f64::consts::E).abs() < EPSILON);
+    assert!((engine.eval::<FLOAT>("min(1.5, -2.5)")? + 2.5).abs() < EPSILON);
+    assert!((engine.eval::<FLOAT>("max(1.5, -2.5)")? - 1.5).abs() < EPSILON);
+    assert!((engine.eval::<FLOAT>("clamp(1.5, 0.0, 1.0)")? - 1.0).abs() < EPSILON);
+    assert!((engine.eval::<FLOAT>("signum(-0.5)")? + 1.0).abs() < EPSILON);
+    assert!((engine.eval::<FLOAT>("pow(2.0, 0.5)")? - 2.0_f64.sqrt()).abs() < EPSILON);
+    assert!((engine.eval::<FLOAT>("pow(2.0, 3)")? - 8.0).abs() < EPSILON);
+    assert!((engine.eval::<FLOAT>("hypot(3.0, 4.0)")? - 5.0).abs() < EPSILON);
+    assert!((engine.eval::<FLOAT>("atan2(1.0, 1.0)")? - 45.0).abs() < EPSILON);
+    assert!((engine.eval::<FLOAT>("atan2(1.0, -1.0)")? - 135.0).abs() < EPSILON);
+
+    assert!(matches!(
+        *engine
+            .eval::<FLOAT>("clamp(0.5, 1.0, 0.0)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorRuntime(_, _)
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use rhai::{Engine, Module, RegisterFn, RegisterResultFn, INT};
+
+#[derive(Debug, Clone)]
+struct TestStruct {
+    x: INT,
+}
+
+#[test]
+fn test_metadata_engine() {
+    let mut engine = Engine::new_raw();
+
+    engine.register_type_with_name::<TestStruct>("TestStruct");
+    engine.register_fn("new_ts", || TestStruct { x: 1 });
+    engine.register_fn("update", |ts: &mut TestStruct, value: INT| ts.x += value);
+    engine.register_result_fn("checked", |x: INT| Ok(x.into()));
+    engine.describe_fn("update", &["ts", "value"], "Increment the field.");
+
+    let metadata = engine.gen_fn_metadata(false);
+
+    assert_eq!(metadata.len(), 3);
+    assert_eq!(
+        metadata[0].to_string(),
+        format!("checked(_: {}) -> Dynamic", std::any::type_name::<INT>())
+    );
+    assert_eq!(metadata[1].to_string(), "new_ts() -> TestStruct");
+    assert_eq!(
+        metadata[2].to_string(),
+        format!(
+            "update(ts: TestStruct, value: {}) -> ()",
+            std::any::type_name::<INT>()
+        )
+    );
+    assert_eq!(metadata[2].doc.as_deref(), Some("Increment the field."));
+    assert!(metadata.iter().all(|f| !f.is_script));
+}
+
+#[test]
+fn test_metadata_packages() {
+    let engine = Engine::new();
+
+    assert!(engine.gen_fn_metadata(false).is_empty());
+
+    let metadata = engine.gen_fn_metadata(true);
+
+    assert!(metadata
+        .iter()
+        .any(|f| f.name == "+" && f.param_types.len() == 2));
+    assert!(metadata
+        .iter()
+        .any(|f| f.name == "len" && f.param_types == ["string"]));
+}
+
+#[test]
+fn test_metadata_module() {
+    let mut sub_module = Module::new();
+    let hash = sub_module.set_fn_2("add", |x: INT, y: INT| Ok(x + y));
+    sub_module
+        .set_fn_param_names(hash, &["x", "y"])
+        .set_fn_doc(hash, "Add \"two\" numbers.");
+
+    let mut module = Module::new();
+    module.set_sub_module("calc", sub_module);
+
+    let engine = Engine::new();
+    let metadata = engine.gen_module_fn_metadata(&module);
+
+    assert_eq!(metadata.len(), 1);
+    assert_eq!(metadata[0].namespace, ["calc"]);
+    assert_eq!(
+        metadata[0].to_json(),
+        format!(
+            r#"{{"namespace":["calc"],"name":"add","params":[{{"name":"x","type":"{0}"}},{{"name":"y","type":"{0}"}}],"return_type":"{0}","is_script":false,"doc":"Add \"two\" numbers."}}"#,
+            std::any::type_name::<INT>()
+        )
+    );
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_module"))]
+fn test_metadata_script() -> Result<(), Box<rhai::EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile(
+        r#"
+            fn foo(x, y) { x + y }
+            private fn bar() { 42 }
+        "#,
+    )?;
+
+    let module = Module::eval_ast_as_new(Default::default(), &ast, &engine)?;
+    let metadata = engine.gen_module_fn_metadata(&module);
+
+    assert_eq!(metadata.len(), 1);
+    assert!(metadata[0].is_script);
+    assert_eq!(
+        metadata[0].to_string(),
+        "foo(x: Dynamic, y: Dynamic) -> Dynamic"
+    );
+
+    Ok(())
+}
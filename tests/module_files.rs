@@ -0,0 +1,548 @@
+#![cfg(not(feature = "no_module"))]
+#![cfg(not(feature = "no_std"))]
+#![cfg(not(target_arch = "wasm32"))]
+use rhai::module_resolvers::FileModuleResolver;
+use rhai::{Engine, EvalAltResult, INT};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+#[cfg(not(feature = "no_index"))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(not(feature = "sync"))]
+use std::rc::Rc as Shared;
+
+#[cfg(feature = "sync")]
+use std::sync::Arc as Shared;
+
+/// Create a fresh directory tree of script files for a test.
+fn make_scripts(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("rhai-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+
+    for (path, script) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, script).unwrap();
+    }
+
+    root
+}
+
+/// Overwrite a script file, making sure that its modification time changes.
+fn touch_script(path: &Path, script: &str) {
+    fs::write(path, script).unwrap();
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(10))
+        .unwrap();
+}
+
+#[test]
+fn test_module_files_relative() -> Result<(), Box<EvalAltResult>> {
+    let root = make_scripts(
+        "relative",
+        &[
+            (
+                "a/b.rhai",
+                r#"import "../common/util" as util; util::x + 1"#,
+            ),
+            ("a/c.rhai", r#"import "./d" as d; let y = d::z; export y;"#),
+            ("a/d.rhai", "let z = 40; export z;"),
+            (
+                "common/util.rhai",
+                r#"import "../a/c" as c; let x = c::y + 1; export x;"#,
+            ),
+        ],
+    );
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(FileModuleResolver::new_with_path("/nonexistent")));
+
+    assert_eq!(engine.eval_file::<INT>(root.join("a/b.rhai"))?, 42);
+
+    fs::remove_dir_all(root).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_module_files_search_paths() -> Result<(), Box<EvalAltResult>> {
+    let root = make_scripts(
+        "search",
+        &[
+            ("lib1/first.rhai", "let x = 1; export x;"),
+            ("lib2/first.rhai", "let x = 2; export x;"),
+            ("lib2/second.rhai", "let x = 40; export x;"),
+        ],
+    );
+
+    let mut resolver = FileModuleResolver::new_with_path(root.join("lib1"));
+    resolver.add_path(root.join("lib2"));
+    assert_eq!(resolver.paths().len(), 2);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(resolver));
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "first" as a; import "second" as b; a::x + b::x + 1"#)?,
+        42
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"import "third" as c; c::x"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorReadingScriptFile(_, _, _)
+    ));
+
+    fs::remove_dir_all(root).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_module_files_circular() {
+    let root = make_scripts(
+        "circular",
+        &[
+            ("a.rhai", r#"import "b" as b; let x = 1; export x;"#),
+            ("b.rhai", r#"import "./c" as c; let x = 2; export x;"#),
+            ("c.rhai", r#"import "a" as a; let x = 3; export x;"#),
+        ],
+    );
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(FileModuleResolver::new_with_path(&root)));
+
+    let err = engine
+        .eval::<INT>(r#"import "a" as a; a::x"#)
+        .expect_err("should error");
+
+    match *err {
+        EvalAltResult::ErrorCircularImport(ref chain, _) => {
+            let names: Vec<_> = chain
+                .iter()
+                .map(|p| PathBuf::from(p).file_name().unwrap().to_owned())
+                .collect();
+            assert_eq!(names, ["a.rhai", "b.rhai", "c.rhai", "a.rhai"]);
+        }
+        ref err => panic!("wrong error: {}", err),
+    }
+
+    assert!(err.to_string().starts_with("Circular import: "));
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_module_files_invalidate() -> Result<(), Box<EvalAltResult>> {
+    let root = make_scripts(
+        "invalidate",
+        &[
+            ("main.rhai", r#"import "lib" as lib; lib::x"#),
+            (
+                "lib.rhai",
+                r#"import "inner" as inner; let x = inner::y; export x;"#,
+            ),
+            ("inner.rhai", "let y = 1; export y;"),
+            ("other.rhai", "let z = 0; export z;"),
+        ],
+    );
+
+    let resolver = Shared::new(FileModuleResolver::new_with_path(&root));
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(resolver.clone()));
+
+    let ast = engine.compile_file(root.join("main.rhai"))?;
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 1);
+
+    // Cached script files are not reloaded
+    touch_script(&root.join("inner.rhai"), "let y = 2; export y;");
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 1);
+
+    // Changed script files are detected
+    let changed = resolver.invalidate_changed();
+    assert_eq!(changed.len(), 1);
+    assert!(changed[0].ends_with("inner.rhai"));
+    assert!(resolver.depends_on(&ast, &changed));
+    assert!(!resolver.depends_on(&ast, &[root.join("other.rhai")]));
+    assert!(resolver.invalidate_changed().is_empty());
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 2);
+
+    // Explicit invalidation
+    touch_script(&root.join("lib.rhai"), "let x = 40; export x;");
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 2);
+    assert!(resolver.invalidate(root.join("lib.rhai")));
+    assert!(!resolver.invalidate(root.join("lib.rhai")));
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 40);
+
+    // 'lib.rhai' no longer imports 'inner.rhai'
+    assert!(!resolver.depends_on(&ast, &[root.join("inner.rhai")]));
+
+    touch_script(&root.join("lib.rhai"), "let x = 42; export x;");
+    resolver.clear_cache();
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+
+    fs::remove_dir_all(root).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_module_files_auto_reload() -> Result<(), Box<EvalAltResult>> {
+    let root = make_scripts("reload", &[("lib.rhai", "let x = 1; export x;")]);
+
+    let mut resolver = FileModuleResolver::new_with_path(&root);
+    resolver.set_auto_reload(true);
+    assert!(resolver.is_auto_reload());
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(resolver));
+
+    let script = r#"import "lib" as lib; lib::x"#;
+
+    assert_eq!(engine.eval::<INT>(script)?, 1);
+    touch_script(&root.join("lib.rhai"), "let x = 42; export x;");
+    assert_eq!(engine.eval::<INT>(script)?, 42);
+
+    fs::remove_dir_all(root).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+fn test_module_files_cache_modules() -> Result<(), Box<EvalAltResult>> {
+    let root = make_scripts(
+        "cache_modules",
+        &[
+            (
+                "lib.rhai",
+                r#"print("loading"); let x = [1, 2, 3]; export x;"#,
+            ),
+            (
+                "other.rhai",
+                r#"import "lib" as lib; let y = lib::x.len(); export y;"#,
+            ),
+        ],
+    );
+
+    let loaded = std::sync::Arc::new(AtomicUsize::new(0));
+    let counter = loaded.clone();
+
+    let mut resolver = FileModuleResolver::new_with_path(&root);
+    resolver.set_cache_modules(true);
+    assert!(resolver.is_caching_modules());
+
+    let mut engine = Engine::new();
+    engine.on_print(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    engine.set_module_resolver(Some(resolver));
+
+    let script = r#"import "lib" as lib; import "other" as other; lib::x.len() + other::y"#;
+
+    assert_eq!(engine.eval::<INT>(script)?, 6);
+    assert_eq!(engine.eval::<INT>(script)?, 6);
+    assert_eq!(loaded.load(Ordering::SeqCst), 1);
+
+    // Modifying a module variable does not affect other importers
+    assert_eq!(
+        engine.eval::<INT>(r#"import "lib" as lib; lib::x.push(4); lib::x.len()"#)?,
+        4
+    );
+    assert_eq!(engine.eval::<INT>(script)?, 6);
+    assert_eq!(loaded.load(Ordering::SeqCst), 1);
+
+    // Modules are evaluated on each import by default
+    engine.set_module_resolver(Some(FileModuleResolver::new_with_path(&root)));
+    assert_eq!(engine.eval::<INT>(script)?, 6);
+    assert_eq!(loaded.load(Ordering::SeqCst), 3);
+
+    fs::remove_dir_all(root).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_module_files_cache_modules_invalidate() -> Result<(), Box<EvalAltResult>> {
+    let root = make_scripts(
+        "cache_modules_invalidate",
+        &[
+            (
+                "lib.rhai",
+                r#"import "inner" as inner; let x = inner::y; export x;"#,
+            ),
+            ("inner.rhai", "let y = 1; export y;"),
+        ],
+    );
+
+    let mut resolver = FileModuleResolver::new_with_path(&root);
+    resolver.set_cache_modules(true);
+    let resolver = Shared::new(resolver);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(resolver.clone()));
+
+    let script = r#"import "lib" as lib; lib::x"#;
+
+    assert_eq!(engine.eval::<INT>(script)?, 1);
+
+    // Modules importing a changed script file are evaluated again
+    touch_script(&root.join("inner.rhai"), "let y = 2; export y;");
+    assert_eq!(engine.eval::<INT>(script)?, 1);
+    assert_eq!(resolver.invalidate_changed().len(), 1);
+    assert_eq!(engine.eval::<INT>(script)?, 2);
+
+    touch_script(&root.join("inner.rhai"), "let y = 3; export y;");
+    assert!(resolver.invalidate(root.join("inner.rhai")));
+    assert_eq!(engine.eval::<INT>(script)?, 3);
+
+    fs::remove_dir_all(root).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_module_files_cache_modules_auto_reload() -> Result<(), Box<EvalAltResult>> {
+    let root = make_scripts(
+        "cache_modules_reload",
+        &[
+            (
+                "lib.rhai",
+                r#"import "inner" as inner; let x = inner::y; export x;"#,
+            ),
+            ("inner.rhai", "let y = 1; export y;"),
+        ],
+    );
+
+    let mut resolver = FileModuleResolver::new_with_path(&root);
+    resolver.set_cache_modules(true).set_auto_reload(true);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(resolver));
+
+    let script = r#"import "lib" as lib; lib::x"#;
+
+    assert_eq!(engine.eval::<INT>(script)?, 1);
+    touch_script(&root.join("inner.rhai"), "let y = 42; export y;");
+    assert_eq!(engine.eval::<INT>(script)?, 42);
+
+    fs::remove_dir_all(root).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_module_files_sandboxed() -> Result<(), Box<EvalAltResult>> {
+    let root = make_scripts(
+        "sandboxed",
+        &[
+            (
+                "scripts/lib.rhai",
+                r#"import "./sub/inner" as inner; let x = inner::y; export x;"#,
+            ),
+            (
+                "scripts/sub/inner.rhai",
+                r#"import "../other" as other; let y = other::z + 1; export y;"#,
+            ),
+            ("scripts/other.rhai", "let z = 41; export z;"),
+            ("secret.rhai", "let x = 0; export x;"),
+        ],
+    );
+
+    let mut resolver = FileModuleResolver::new_with_path(root.join("scripts"));
+    resolver.set_sandboxed(true);
+    assert!(resolver.is_sandboxed());
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(resolver));
+
+    assert_eq!(engine.eval::<INT>(r#"import "lib" as lib; lib::x"#)?, 42);
+
+    let err = engine
+        .eval::<INT>(r#"import "../secret" as s; s::x"#)
+        .expect_err("should error");
+    assert!(matches!(
+        *err,
+        EvalAltResult::ErrorModuleAccessDenied(_, _, _)
+    ));
+    assert!(err
+        .to_string()
+        .starts_with("Access denied to module script file"));
+
+    let absolute = root.join("secret").to_string_lossy().replace('\\', "\\\\");
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>(&format!(r#"import "{}" as s; s::x"#, absolute))
+            .expect_err("should error"),
+        EvalAltResult::ErrorModuleAccessDenied(_, _, _)
+    ));
+
+    // Without sandboxing, files outside of the search paths can be imported
+    engine.set_module_resolver(Some(FileModuleResolver::new_with_path(
+        root.join("scripts"),
+    )));
+    assert_eq!(engine.eval::<INT>(r#"import "../secret" as s; s::x"#)?, 0);
+
+    fs::remove_dir_all(root).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_module_files_sandboxed_symlinks() -> Result<(), Box<EvalAltResult>> {
+    use std::os::unix::fs::symlink;
+
+    let root = make_scripts(
+        "sandboxed_symlinks",
+        &[
+            ("scripts/lib.rhai", "let x = 42; export x;"),
+            ("secret.rhai", "let x = 0; export x;"),
+        ],
+    );
+
+    symlink(
+        root.join("scripts/lib.rhai"),
+        root.join("scripts/link.rhai"),
+    )
+    .unwrap();
+    symlink(root.join("secret.rhai"), root.join("scripts/escape.rhai")).unwrap();
+
+    let mut resolver = FileModuleResolver::new_with_path(root.join("scripts"));
+    resolver.set_sandboxed(true);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(resolver));
+
+    // Symbolic links must stay inside the search paths
+    assert_eq!(engine.eval::<INT>(r#"import "link" as lib; lib::x"#)?, 42);
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"import "escape" as s; s::x"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorModuleAccessDenied(_, _, _)
+    ));
+
+    let mut resolver = FileModuleResolver::new_with_path(root.join("scripts"));
+    resolver.set_sandboxed(true).set_follow_symlinks(false);
+    assert!(!resolver.is_following_symlinks());
+    engine.set_module_resolver(Some(resolver));
+
+    assert_eq!(engine.eval::<INT>(r#"import "lib" as lib; lib::x"#)?, 42);
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"import "link" as lib; lib::x"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorModuleAccessDenied(_, _, _)
+    ));
+
+    fs::remove_dir_all(root).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_module_files_max_file_size() -> Result<(), Box<EvalAltResult>> {
+    let root = make_scripts(
+        "max_file_size",
+        &[
+            ("small.rhai", "let x = 42; export x;"),
+            (
+                "large.rhai",
+                &format!("let x = 42; export x; // {}", "x".repeat(100)),
+            ),
+        ],
+    );
+
+    let mut resolver = FileModuleResolver::new_with_path(&root);
+    resolver.set_max_file_size(50);
+    assert_eq!(resolver.max_file_size(), 50);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(resolver));
+
+    assert_eq!(engine.eval::<INT>(r#"import "small" as m; m::x"#)?, 42);
+
+    let err = engine
+        .eval::<INT>(r#"import "large" as m; m::x"#)
+        .expect_err("should error");
+
+    match *err {
+        EvalAltResult::ErrorModuleAccessDenied(ref path, ref reason, _) => {
+            assert!(path.ends_with("large.rhai"));
+            assert_eq!(reason, "file too large");
+        }
+        ref err => panic!("wrong error: {}", err),
+    }
+
+    fs::remove_dir_all(root).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_module_files_max_file_size_pipe() -> Result<(), Box<EvalAltResult>> {
+    let root = make_scripts("max_file_size_pipe", &[]);
+    fs::create_dir_all(&root).unwrap();
+    let pipe = root.join("pipe.rhai");
+
+    // A named pipe has no size, so its size is only known by reading it
+    if !std::process::Command::new("mkfifo")
+        .arg(&pipe)
+        .status()
+        .is_ok_and(|s| s.success())
+    {
+        return Ok(());
+    }
+
+    let writer = std::thread::spawn({
+        let pipe = pipe.clone();
+        move || {
+            use std::io::Write;
+            let script = format!("let x = 42; export x; // {}", "x".repeat(100_000));
+            let _ = fs::OpenOptions::new()
+                .write(true)
+                .open(pipe)
+                .and_then(|mut f| f.write_all(script.as_bytes()));
+        }
+    });
+
+    let mut resolver = FileModuleResolver::new_with_path(&root);
+    resolver.set_max_file_size(50);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(resolver));
+
+    let err = engine
+        .eval::<INT>(r#"import "pipe" as m; m::x"#)
+        .expect_err("should error");
+
+    assert!(matches!(
+        *err,
+        EvalAltResult::ErrorModuleAccessDenied(_, ref reason, _) if reason == "file too large"
+    ));
+
+    writer.join().unwrap();
+    fs::remove_dir_all(root).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_module_files_missing() {
+    let root = make_scripts("missing", &[]);
+    fs::create_dir_all(&root).unwrap();
+
+    let mut resolver = FileModuleResolver::new_with_path(&root);
+    resolver.set_max_file_size(50);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(resolver));
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"import "missing" as m; m::x"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorReadingScriptFile(_, _, _)
+    ));
+
+    fs::remove_dir_all(root).unwrap();
+}
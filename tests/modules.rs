@@ -170,6 +170,32 @@ fn test_module_resolver() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_module_fn_4_mut() -> Result<(), Box<EvalAltResult>> {
+    let mut module = Module::new();
+
+    // The last parameter has a different type from the others
+    module.set_fn_4_mut(
+        "add_if".to_string(),
+        |x: &mut INT, y: INT, z: INT, flag: bool| {
+            if flag {
+                *x += y + z;
+            }
+            Ok(())
+        },
+    );
+
+    let mut engine = Engine::new();
+    engine.load_package(module.into());
+
+    assert_eq!(
+        engine.eval::<INT>("let x = 1; x.add_if(20, 21, true); x.add_if(1, 1, false); x")?,
+        42
+    );
+
+    Ok(())
+}
+
 #[test]
 #[cfg(not(feature = "no_function"))]
 fn test_module_from_ast() -> Result<(), Box<EvalAltResult>> {
@@ -251,6 +277,69 @@ fn test_module_from_ast() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_module_fn_env() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let mut resolver1 = StaticModuleResolver::new();
+    let mut sub_module = Module::new();
+    sub_module.set_var("answer", 2 as INT);
+    resolver1.insert("inner", sub_module);
+    engine.set_module_resolver(Some(resolver1));
+
+    let ast = engine.compile(
+        r#"
+            import "inner" as inner;
+
+            const x = 40;
+            let hidden = 1;
+
+            fn calc() { x + inner::answer }
+            fn shadow(x) { x }
+            fn change() { x = 1; }
+            fn peek() { hidden }
+
+            export x;
+        "#,
+    )?;
+
+    let module = Module::eval_ast_as_new(Scope::new(), &ast, &engine)?;
+
+    let mut resolver2 = StaticModuleResolver::new();
+    resolver2.insert("testing", module);
+    engine.set_module_resolver(Some(resolver2));
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "testing" as ttt; ttt::calc()"#)?,
+        42
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"import "testing" as ttt; ttt::shadow(1)"#)?,
+        1
+    );
+
+    // Module variables are constants
+    assert!(matches!(
+        *engine
+            .consume(r#"import "testing" as ttt; ttt::change()"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(fn_name, err, _)
+            if fn_name == "change" && matches!(*err, EvalAltResult::ErrorAssignmentToConstant(_, _))
+    ));
+
+    // Only exported variables are visible
+    assert!(matches!(
+        *engine
+            .consume(r#"import "testing" as ttt; ttt::peek()"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(fn_name, err, _)
+            if fn_name == "peek" && matches!(*err, EvalAltResult::ErrorVariableNotFound(_, _))
+    ));
+
+    Ok(())
+}
+
 #[test]
 fn test_module_export() -> Result<(), Box<EvalAltResult>> {
     let engine = Engine::new();
@@ -268,3 +357,266 @@ fn test_module_export() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(not(feature = "no_std"))]
+fn test_module_memory_resolver() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::{MemoryModuleResolver, ModuleResolversCollection};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let mut resolver = MemoryModuleResolver::new();
+    resolver
+        .insert_script(
+            "app/main",
+            r#"import "../lib/util" as util; let x = util::y + 1; export x;"#,
+        )
+        .insert_script_with_source(
+            "lib/util",
+            r#"print("loading"); import "./consts" as c; let y = c::z; export y;"#,
+            "db://util",
+        )
+        .insert_script("lib/consts", "let z = 41; export z;")
+        .insert_script("broken", "let x = ;")
+        .insert_script("cycle/a", r#"import "./b" as b;"#)
+        .insert_script("cycle/b", r#"import "/cycle/a" as a;"#);
+
+    assert!(resolver.contains_path("/lib/./consts"));
+    assert_eq!(resolver.paths().count(), 6);
+
+    let mut static_resolver = StaticModuleResolver::new();
+    let mut module = Module::new();
+    module.set_var("answer", 42 as INT);
+    static_resolver.insert("static", module);
+
+    let mut collection = ModuleResolversCollection::new();
+    collection.push(resolver);
+    collection.push(static_resolver);
+
+    let loaded = Arc::new(AtomicUsize::new(0));
+    let counter = loaded.clone();
+
+    let mut engine = Engine::new();
+    engine.on_print(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    engine.set_module_resolver(Some(collection));
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "app/main" as m; import "lib/util" as u; m::x + u::y"#)?,
+        83
+    );
+    assert_eq!(engine.eval::<INT>(r#"import "app/main" as m; m::x"#)?, 42);
+    assert_eq!(loaded.load(Ordering::SeqCst), 1);
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "static" as s; s::answer"#)?,
+        42
+    );
+
+    // Scripts are compiled only when imported
+    assert!(engine.consume(r#"import "broken" as b;"#).is_err());
+
+    assert!(matches!(
+        *engine
+            .consume(r#"import "../app/main" as m;"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorModuleNotFound(_, _)
+    ));
+
+    assert!(matches!(
+        *engine.consume(r#"import "cycle/a" as a;"#).expect_err("should error"),
+        EvalAltResult::ErrorCircularImport(chain, _)
+            if chain == ["cycle/a", "cycle/b", "cycle/a"]
+    ));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_std"))]
+fn test_module_dynamic_import() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::MemoryModuleResolver;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let mut resolver = MemoryModuleResolver::new();
+    resolver
+        .insert_script(
+            "lib/util",
+            r#"print("loading"); fn double(x) { x * 2 } let x = 21; export x;"#,
+        )
+        .insert_script("lib/other", "let x = 1; export x;")
+        .insert_script("lib/lazy", r#"print("loading"); let x = 21; export x;"#)
+        .insert_script(
+            "lib/optional",
+            r#"import "broken" as b; import "lib/lazy" as u; fn get() { u::x } let y = 1; export y;"#,
+        )
+        .insert_script("broken", "let x = ;");
+
+    let loaded = Arc::new(AtomicUsize::new(0));
+    let counter = loaded.clone();
+
+    let mut engine = Engine::new();
+    engine.on_print(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    engine.set_module_resolver(Some(resolver));
+
+    // Computed import paths
+    assert_eq!(
+        engine.eval::<INT>(r#"let base = "lib"; import base + "/util" as u; u::double(u::x)"#)?,
+        42
+    );
+    assert!(matches!(
+        *engine
+            .consume(r#"import 42 as u;"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorImportExpr(_)
+    ));
+
+    // Function form
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let m = import("lib/other");
+                m = if m::x > 0 { import("lib/util") } else { m };
+                let modules = #{ util: m };
+                let u = modules.util;
+                u::double(u::x) + m::x
+            "#
+        )?,
+        63
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"type_of(import("lib/util"))"#)?,
+        "module"
+    );
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"let m = 42; m::x"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorModuleNotFound(_, _)
+    ));
+
+    // Lazy imports
+    assert!(!engine.lazy_imports());
+    assert!(engine.consume(r#"import "broken" as b;"#).is_err());
+
+    loaded.store(0, Ordering::SeqCst);
+    engine.set_lazy_imports(true);
+
+    engine.consume(r#"import "broken" as b; import "lib/lazy" as u;"#)?;
+    assert_eq!(loaded.load(Ordering::SeqCst), 0);
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let base = "lib";
+                import base + "/lazy" as u;
+                base = "wrong";
+                u::x + u::x * 2
+            "#
+        )?,
+        63
+    );
+    assert_eq!(loaded.load(Ordering::SeqCst), 1);
+
+    assert!(engine.eval::<INT>(r#"import "broken" as b; b::x"#).is_err());
+
+    // Lazy imports in a module are not resolved when the module is loaded
+    loaded.store(0, Ordering::SeqCst);
+    assert_eq!(
+        engine.eval::<INT>(r#"import "lib/optional" as o; o::y"#)?,
+        1
+    );
+    assert_eq!(loaded.load(Ordering::SeqCst), 0);
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<INT>(r#"import "lib/optional" as o; o::get() * 2"#)?,
+        42
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(feature = "no_function"))]
+fn test_module_reexport() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::MemoryModuleResolver;
+
+    let mut resolver = MemoryModuleResolver::new();
+    resolver
+        .insert_script(
+            "util",
+            r#"
+                import "consts" as consts;
+                let scale = 2;
+                fn helper(x) { x * scale }
+                fn helper(x, y) { x * y }
+                private fn hidden() { 0 }
+                export scale;
+            "#,
+        )
+        .insert_script("consts", "let answer = 42; export answer;")
+        .insert_script("extra", "let scale = 100; export scale; fn extra() { 1 }")
+        .insert_script(
+            "facade",
+            r#"
+                import "util" as util;
+                import "extra" as extra;
+                export util::helper as h, util::consts as c, util::consts::answer;
+                export extra::*;
+                fn extra() { 2 }
+            "#,
+        )
+        .insert_script("missing", r#"import "util" as util; export util::nothing;"#)
+        .insert_script("all", r#"import "util" as util; export util::*;"#);
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(resolver));
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "facade" as f; f::h(21) + f::h(2, 3)"#)?,
+        48
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"import "facade" as f; f::c::answer + f::answer"#)?,
+        84
+    );
+
+    // Items defined in the module take precedence
+    assert_eq!(
+        engine.eval::<INT>(r#"import "facade" as f; f::scale + f::extra()"#)?,
+        102
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "all" as a; a::helper(a::scale) + a::consts::answer"#)?,
+        46
+    );
+    assert!(engine
+        .eval::<INT>(r#"import "all" as a; a::hidden()"#)
+        .is_err());
+    assert!(engine.consume(r#"import "missing" as m;"#).is_err());
+
+    assert!(matches!(
+        engine
+            .compile(r#"import "util" as util; let x = 1; export x, util::helper as x;"#)
+            .expect_err("should error"),
+        ParseError(x, _) if *x == ParseErrorType::DuplicatedExport("x".into())
+    ));
+    assert!(matches!(
+        engine
+            .compile(r#"import "util" as util; let x = 1; export x as y, util::y;"#)
+            .expect_err("should error"),
+        ParseError(x, _) if *x == ParseErrorType::DuplicatedExport("y".into())
+    ));
+    assert!(engine
+        .compile(r#"import "util" as util; export util::*, util::helper as h;"#)
+        .is_ok());
+
+    Ok(())
+}
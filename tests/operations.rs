@@ -22,6 +22,17 @@ fn test_max_operations() -> Result<(), Box<EvalAltResult>> {
         EvalAltResult::ErrorTooManyOperations(_)
     ));
 
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_object"))]
+    assert!(matches!(
+        *engine
+            .eval::<()>("let x = []; x.pad(500, 0); x.map(|v| v + 1);")
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(_, err, _)
+            if matches!(*err, EvalAltResult::ErrorInFunctionCall(_, ref err, _)
+                if matches!(**err, EvalAltResult::ErrorTooManyOperations(_)))
+    ));
+
     engine.set_max_operations(0);
 
     engine.eval::<()>("for x in range(0, 10000) {}")?;
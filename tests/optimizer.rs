@@ -1,5 +1,6 @@
 #![cfg(not(feature = "no_optimize"))]
 
+use rhai::packages::{Package, RandomPackage};
 use rhai::{Engine, EvalAltResult, OptimizationLevel, INT};
 
 #[test]
@@ -48,3 +49,72 @@ fn test_optimizer_parse() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_optimizer_math_functions() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::Full);
+
+    let ast = engine.compile("gcd(12, 18) + pow(2, 3)")?;
+    assert!(format!("{:?}", ast).starts_with("AST([Expr(IntegerConstant((14,"));
+
+    let ast = engine.compile("get_bits(0xabcd, 4, 8)")?;
+    assert!(format!("{:?}", ast).starts_with("AST([Expr(IntegerConstant((188,"));
+
+    #[cfg(not(feature = "no_float"))]
+    {
+        let ast = engine.compile("PI()")?;
+        assert!(format!("{:?}", ast).starts_with("AST([Expr(FloatConstant("));
+    }
+
+    // Errors are left for run-time
+    let ast = engine.compile("sqrt(-1)")?;
+    assert!(format!("{:?}", ast).starts_with("AST([Expr(FnCall("));
+
+    engine.set_optimization_level(OptimizationLevel::Simple);
+
+    let ast = engine.compile("gcd(12, 18)")?;
+    assert!(format!("{:?}", ast).starts_with("AST([Expr(FnCall("));
+
+    Ok(())
+}
+
+#[test]
+fn test_optimizer_impure_functions() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.load_package(RandomPackage::new().get());
+    engine.set_optimization_level(OptimizationLevel::Full);
+
+    let ast = engine.compile("rand()")?;
+    assert!(format!("{:?}", ast).starts_with("AST([Expr(FnCall("));
+
+    let ast = engine.compile("rand_int(1, 1000000)")?;
+    assert!(format!("{:?}", ast).starts_with("AST([Expr(FnCall("));
+
+    engine.set_random_seed(1);
+    let first = engine.eval_ast::<INT>(&ast)?;
+    engine.set_random_seed(2);
+    assert_ne!(engine.eval_ast::<INT>(&ast)?, first);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_function"))]
+fn test_optimizer_fn_ptr_arguments() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::Full);
+
+    assert_eq!(
+        engine.eval::<INT>("[1, 2, 3].map(|x| x * 2).reduce(|sum, x| sum + x)")?,
+        12
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"fn double(x) { x * 2 } [1, 2, 3].map(Fn("double"))[2]"#)?,
+        6
+    );
+    assert_eq!(engine.eval::<INT>(r#"[Fn("double"), 1].len()"#)?, 2);
+
+    Ok(())
+}
@@ -0,0 +1,291 @@
+#![cfg(feature = "plugins")]
+use rhai::packages::Package;
+use rhai::plugins::*;
+use rhai::{def_package, Engine, EvalAltResult, INT};
+
+#[export_module]
+mod special_array_package {
+    use rhai::{Array, EvalAltResult, ImmutableString, Position, INT};
+
+    /// Number of elements in the array.
+    #[rhai_fn(get = "foo")]
+    pub fn foo(array: &mut Array) -> INT {
+        array.len() as INT
+    }
+
+    pub fn hash(_text: &str, x: INT) -> INT {
+        x * 2
+    }
+
+    #[rhai_fn(name = "test", name = "hi")]
+    pub fn len(array: &mut Array, mul: INT) -> INT {
+        (array.len() as INT) * mul
+    }
+
+    #[rhai_fn(name = "+")]
+    pub fn funky_add(x: INT, y: INT) -> INT {
+        x / 2 + y * 2
+    }
+
+    pub fn greet(name: &str) -> ImmutableString {
+        format!("hello, {}!", name).into()
+    }
+
+    pub fn checked_div(x: INT, y: INT) -> Result<INT, Box<EvalAltResult>> {
+        if y == 0 {
+            Err(Box::new(EvalAltResult::ErrorArithmetic(
+                "division by zero".into(),
+                Position::default(),
+            )))
+        } else {
+            Ok(x / y)
+        }
+    }
+
+    #[rhai_fn(skip)]
+    pub fn hidden() -> INT {
+        42
+    }
+}
+
+def_package!(rhai:SpecialArrayPackage:"Special array functions", lib, {
+    combine_with_exported_module!(lib, special_array_package);
+});
+
+#[test]
+fn test_plugins_package() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.load_package(SpecialArrayPackage::new().get());
+
+    assert_eq!(engine.eval::<INT>("let a = [1, 2, 3]; a.foo")?, 3);
+    assert_eq!(engine.eval::<INT>("let a = [1, 2, 3]; a.test(2)")?, 6);
+    assert_eq!(engine.eval::<INT>("let a = [1, 2, 3]; hi(a, 2)")?, 6);
+    assert_eq!(engine.eval::<INT>(r#"hash("hello", 42)"#)?, 84);
+    assert_eq!(engine.eval::<INT>("2 + 14")?, 29);
+    assert_eq!(engine.eval::<String>(r#"greet("world")"#)?, "hello, world!");
+    assert_eq!(engine.eval::<INT>("checked_div(42, 2)")?, 21);
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>("checked_div(42, 0)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(_, _)
+    ));
+    assert!(matches!(
+        *engine.eval::<INT>("hidden()").expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(_, _)
+    ));
+    assert_eq!(special_array_package::hidden(), 42);
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct Point {
+    x: INT,
+    y: INT,
+}
+
+#[export_module]
+mod point_api {
+    use super::Point;
+    use rhai::INT;
+
+    /// The origin of the coordinates system.
+    pub const ORIGIN_X: INT = 0;
+
+    pub fn new_point(x: INT, y: INT) -> Point {
+        Point { x, y }
+    }
+
+    #[rhai_fn(get = "x")]
+    pub fn get_x(p: &mut Point) -> INT {
+        p.x
+    }
+
+    #[rhai_fn(set = "x")]
+    pub fn set_x(p: &mut Point, value: INT) {
+        p.x = value;
+    }
+
+    #[rhai_fn(index_get)]
+    pub fn get_index(p: &mut Point, index: INT) -> INT {
+        if index == 0 {
+            p.x
+        } else {
+            p.y
+        }
+    }
+
+    #[rhai_fn(index_set)]
+    pub fn set_index(p: &mut Point, index: INT, value: INT) {
+        if index == 0 {
+            p.x = value;
+        } else {
+            p.y = value;
+        }
+    }
+
+    /// Move a point by an offset.
+    pub fn shift(p: &mut Point, dx: INT, dy: INT) {
+        p.x += dx;
+        p.y += dy;
+    }
+
+    pub mod math {
+        use rhai::INT;
+
+        pub fn double(x: INT) -> INT {
+            x * 2
+        }
+    }
+}
+
+#[test]
+fn test_plugins_module() -> Result<(), Box<EvalAltResult>> {
+    let module = exported_module!(point_api);
+
+    assert!(module.contains_var("ORIGIN_X"));
+    assert!(module.contains_sub_module("math"));
+
+    let mut engine = Engine::new();
+    engine.register_type::<Point>();
+    engine.load_package(module.into());
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r"
+                let p = new_point(1, 2);
+                p.x = 40;
+                p[1] = 3;
+                p.shift(1, 0);
+                p.x + p[1] - p[0]
+            "
+        )?,
+        3
+    );
+
+    let metadata = engine.gen_module_fn_metadata(&exported_module!(point_api));
+    let shift = metadata
+        .iter()
+        .find(|f| f.name == "shift")
+        .expect("shift should exist");
+
+    assert_eq!(
+        shift.param_names,
+        vec![Some("p".into()), Some("dx".into()), Some("dy".into())]
+    );
+    assert_eq!(shift.doc.as_ref().unwrap(), "Move a point by an offset.");
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_module"))]
+#[test]
+fn test_plugins_sub_module() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::StaticModuleResolver;
+
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("point", exported_module!(point_api));
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(Some(resolver));
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                import "point" as point;
+                point::math::double(21) + point::ORIGIN_X
+            "#
+        )?,
+        42
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_object"))]
+mod derived {
+    use rhai::plugins::RhaiType;
+    use rhai::{Engine, EvalAltResult, RegisterFn, INT};
+    use std::fmt;
+    use std::sync::{Arc, RwLock};
+
+    #[derive(Debug, Clone, PartialEq, RhaiType)]
+    #[rhai(name = "Point")]
+    struct MyPoint {
+        x: INT,
+        #[rhai(get, rename = "why")]
+        y: INT,
+        #[rhai(set)]
+        z: INT,
+        #[rhai(skip)]
+        _cache: Vec<u8>,
+    }
+
+    impl fmt::Display for MyPoint {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "({}, {}, {})", self.x, self.y, self.z)
+        }
+    }
+
+    #[derive(Clone, RhaiType)]
+    #[allow(dead_code)]
+    struct Wrapper(#[rhai(rename = "value")] INT, #[rhai(skip)] bool);
+
+    #[test]
+    fn test_plugins_derive_type() -> Result<(), Box<EvalAltResult>> {
+        let logbook = Arc::new(RwLock::new(Vec::<String>::new()));
+        let log1 = logbook.clone();
+        let log2 = logbook.clone();
+
+        let mut engine = Engine::new();
+
+        engine
+            .on_print(move |s| log1.write().unwrap().push(s.to_string()))
+            .on_debug(move |s| log2.write().unwrap().push(s.to_string()))
+            .register_rhai_type::<MyPoint>()
+            .register_rhai_type::<Wrapper>()
+            .register_fn("new_point", |x: INT, y: INT| MyPoint {
+                x,
+                y,
+                z: 0,
+                _cache: Vec::new(),
+            })
+            .register_fn("new_wrapper", |x: INT| Wrapper(x, true));
+
+        assert_eq!(
+            engine.eval::<INT>("let p = new_point(1, 2); p.x = 40; p.z = 3; p.x + p.why")?,
+            42
+        );
+        assert_eq!(engine.eval::<String>("type_of(new_point(1, 2))")?, "Point");
+        assert_eq!(
+            engine.eval::<String>("let p = new_point(1, 2); p.z = 3; p.to_string()")?,
+            "(1, 2, 3)"
+        );
+
+        engine.eval::<()>("let p = new_point(1, 2); print(p); debug(p)")?;
+        assert_eq!(
+            *logbook.read().unwrap(),
+            vec!["(1, 2, 0)", "MyPoint { x: 1, y: 2, z: 0, _cache: [] }"]
+        );
+
+        assert!(engine.eval::<bool>("new_point(1, 2) == new_point(1, 2)")?);
+        assert!(engine.eval::<bool>("new_point(1, 2) != new_point(1, 3)")?);
+
+        assert!(engine.eval::<INT>("new_point(1, 2).z").is_err());
+        assert!(engine
+            .eval::<INT>("let p = new_point(1, 2); p.why = 1; p.why")
+            .is_err());
+
+        assert_eq!(
+            engine.eval::<INT>("let w = new_wrapper(1); w.value = 42; w.value")?,
+            42
+        );
+
+        // Without `PartialEq`, `==` falls back to the default of `false`
+        assert!(!engine.eval::<bool>("let w = new_wrapper(1); w == w")?);
+
+        Ok(())
+    }
+}
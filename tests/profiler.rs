@@ -0,0 +1,129 @@
+#![cfg(not(feature = "no_std"))]
+use rhai::{Engine, EvalAltResult, ProfileMetric, RegisterFn, INT};
+
+#[test]
+fn test_profiler_off() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert!(!engine.is_profiling());
+    assert_eq!(engine.eval::<INT>("40 + 2")?, 42);
+    assert!(engine.take_profile().is_none());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_profiler_functions() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_fn("inc", |x: INT| x + 1);
+    engine.set_profiling(true);
+
+    let ast = engine.compile(
+        r#"
+            fn leaf(x) {
+                inc(x)
+            }
+            fn branch(x) {
+                let y = leaf(x);
+                leaf(y)
+            }
+            let total = 0;
+            for n in range(0, 5) {
+                total += branch(n);
+            }
+            total
+        "#,
+    )?;
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 20);
+
+    let profile = engine.take_profile().unwrap();
+
+    let branch = profile.get_fn("branch").unwrap();
+    let leaf = profile.get_fn("leaf").unwrap();
+    let inc = profile.get_fn("inc").unwrap();
+
+    assert!(branch.is_script);
+    assert!(!inc.is_script);
+    assert_eq!(branch.calls, 5);
+    assert_eq!(leaf.calls, 10);
+    assert_eq!(inc.calls, 10);
+    assert_eq!(
+        branch.inclusive_operations,
+        branch.exclusive_operations + leaf.inclusive_operations
+    );
+    assert!(branch.inclusive_time >= leaf.inclusive_time);
+
+    assert_eq!(profile.get_line(3).unwrap().hits, 10);
+    assert_eq!(profile.get_line(7).unwrap().hits, 5);
+    assert!(profile.get_line(7).unwrap().operations > 0);
+
+    // Statistics are reset after being taken
+    assert!(engine.take_profile().unwrap().functions.is_empty());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_profiler_recursion() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.set_profiling(true);
+
+    let ast = engine.compile(
+        r#"
+            fn fib(n) {
+                if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+            }
+        "#,
+    )?;
+
+    assert_eq!(
+        engine.call_fn::<_, INT>(&mut Default::default(), &ast, "fib", (10 as INT,))?,
+        55
+    );
+
+    let profile = engine.take_profile().unwrap();
+    let fib = profile.get_fn("fib").unwrap();
+
+    assert_eq!(fib.calls, 177);
+    assert_eq!(fib.inclusive_operations, fib.exclusive_operations);
+    assert!(profile.total_operations() >= fib.inclusive_operations);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_profiler_folded_stacks() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.set_profiling(true);
+
+    engine.consume(
+        r#"
+            fn foo() { bar() + bar() }
+            fn bar() { let x = 1; x + 1 }
+            foo();
+        "#,
+    )?;
+
+    let folded = engine
+        .take_profile()
+        .unwrap()
+        .to_folded_stacks(ProfileMetric::Operations);
+
+    let stacks: Vec<_> = folded
+        .lines()
+        .map(|line| line.rsplitn(2, ' ').nth(1).unwrap())
+        .collect();
+
+    assert!(stacks.contains(&"<main>"));
+    assert!(stacks.contains(&"<main>;foo"));
+    assert!(stacks.contains(&"<main>;foo;bar"));
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+#![cfg(not(feature = "no_index"))]
+use rhai::packages::{Package, RandomPackage};
+use rhai::{Array, Engine, EvalAltResult, INT};
+
+fn make_engine(seed: u64) -> Engine {
+    let mut engine = Engine::new();
+    engine.load_package(RandomPackage::new().get());
+    engine.set_random_seed(seed);
+    engine
+}
+
+#[test]
+fn test_random_seed() -> Result<(), Box<EvalAltResult>> {
+    let script = "[rand(), rand_int(1, 6), rand(), rand_int(-100, 100)]";
+
+    let mut engine = make_engine(42);
+    let first = engine.eval::<Array>(script)?;
+
+    engine.set_random_seed(42);
+    let second = engine.eval::<Array>(script)?;
+
+    assert_eq!(format!("{:?}", first), format!("{:?}", second));
+
+    let other = make_engine(42).eval::<Array>(script)?;
+    assert_eq!(format!("{:?}", first), format!("{:?}", other));
+
+    let other = make_engine(43).eval::<Array>(script)?;
+    assert_ne!(format!("{:?}", first), format!("{:?}", other));
+
+    Ok(())
+}
+
+#[test]
+fn test_random_ranges() -> Result<(), Box<EvalAltResult>> {
+    let engine = make_engine(1);
+
+    assert!(engine.eval::<bool>(
+        r"
+            let seen = [false, false, false, false, false, false];
+            for i in range(0, 1000) {
+                let x = rand_int(1, 6);
+                if x < 1 || x > 6 { return false; }
+                seen[x - 1] = true;
+            }
+            seen.filter(|x| !x).len() == 0
+        "
+    )?);
+
+    assert_eq!(engine.eval::<INT>("rand_int(7, 7)")?, 7);
+
+    engine.eval::<INT>(&format!("rand_int({}, {})", INT::MIN, INT::MAX))?;
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>("rand_int(2, 1)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorRuntime(_, _)
+    ));
+
+    #[cfg(not(feature = "no_float"))]
+    assert!(engine.eval::<bool>(
+        r"
+            for i in range(0, 1000) {
+                let x = rand_float();
+                if x < 0.0 || x >= 1.0 { return false; }
+            }
+            true
+        "
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn test_random_arrays() -> Result<(), Box<EvalAltResult>> {
+    let engine = make_engine(7);
+
+    let shuffled = engine.eval::<Array>("let x = [1, 2, 3, 4, 5, 6, 7, 8]; shuffle(x); x")?;
+    let mut values: Vec<_> = shuffled.into_iter().map(|v| v.cast::<INT>()).collect();
+    values.sort();
+    assert_eq!(values, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+
+    assert!(engine.eval::<bool>(
+        r"
+            let x = [1, 2, 3, 4, 5, 6, 7, 8];
+            let s = sample(x, 3);
+            s.len() == 3 && s[0] != s[1] && s[0] != s[2] && s[1] != s[2] &&
+                s[0] in x && s[1] in x && s[2] in x
+        "
+    )?);
+    assert_eq!(engine.eval::<INT>("sample([1, 2, 3], 10).len()")?, 3);
+    assert_eq!(engine.eval::<INT>("sample([1, 2, 3], -1).len()")?, 0);
+
+    assert!(engine.eval::<bool>("let x = [1, 2, 3]; choice(x) in x")?);
+    assert!(engine.eval::<bool>("choice([]) == ()")?);
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "regex")]
+use rhai::packages::{Package, RegexPackage};
+use rhai::{Engine, EvalAltResult};
+
+#[cfg(not(feature = "no_index"))]
+use rhai::Array;
+
+#[cfg(not(feature = "no_object"))]
+use rhai::Map;
+
+#[test]
+fn test_regex() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.load_package(RegexPackage::new().get());
+
+    assert!(engine.eval::<bool>(r#"regex("^[a-z]+[0-9]$").is_match("abc1")"#)?);
+    assert!(!engine.eval::<bool>(r#"is_match(regex("^[a-z]+$"), "abc1")"#)?);
+    assert_eq!(
+        engine.eval::<String>(r#"regex("[0-9]+").find("abc123def45")"#)?,
+        "123"
+    );
+    assert_eq!(engine.eval::<()>(r#"regex("[0-9]+").find("abc")"#)?, ());
+    assert_eq!(
+        engine.eval::<String>(r#"regex("(?P<d>[0-9])").replace_all("a1b2", "<$d>")"#)?,
+        "a<1>b<2>"
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"let re = regex("x"); print(re); re.to_string()"#)?,
+        "x"
+    );
+    assert_eq!(engine.eval::<String>(r#"type_of(regex("x"))"#)?, "Regex");
+
+    assert!(matches!(
+        *engine.eval::<()>(r#"regex("(")"#).expect_err("should error"),
+        EvalAltResult::ErrorRuntime(_, _)
+    ));
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        let found = engine.eval::<Array>(r#"regex("[0-9]+").find_all("a1b22c333")"#)?;
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[2].clone().cast::<String>(), "333");
+
+        let pieces = engine.eval::<Array>(r#"regex("[,;] *").split("a, b;c")"#)?;
+        assert_eq!(pieces.len(), 3);
+        assert_eq!(pieces[1].clone().cast::<String>(), "b");
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        let caps = engine.eval::<Map>(
+            r#"regex("(?P<key>[a-z]+)=(?P<value>[0-9]+)(x)?").captures("abc=42")"#,
+        )?;
+        assert_eq!(caps["key"].clone().cast::<String>(), "abc");
+        assert_eq!(caps["1"].clone().cast::<String>(), "abc");
+        assert_eq!(caps["value"].clone().cast::<String>(), "42");
+        assert_eq!(caps["0"].clone().cast::<String>(), "abc=42");
+        assert!(caps["3"].is::<()>());
+
+        assert_eq!(engine.eval::<()>(r#"regex("[0-9]").captures("abc")"#)?, ());
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "unchecked"))]
+fn test_regex_limits() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.load_package(RegexPackage::new().get());
+    engine.set_max_string_size(20);
+
+    assert!(matches!(
+        *engine
+            .eval::<String>(r#"regex("o").replace_all("foo foo foo", "0000")"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(_, 20, _, _)
+    ));
+
+    engine.set_max_string_size(0);
+    engine.set_max_operations(20);
+
+    #[cfg(not(feature = "no_index"))]
+    assert!(matches!(
+        *engine
+            .eval::<Array>(r#"regex(".").find_all("abcdefghijklmnopqrstuvwxyz")"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorTooManyOperations(_)
+    ));
+
+    Ok(())
+}
@@ -186,3 +186,148 @@ fn test_string_fn() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_string_more() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<String>(r#"let x = "Hello"; x.to_upper() + x.to_lower()"#)?,
+        "HELLOhello"
+    );
+    assert!(engine.eval::<bool>(
+        r#"let x = "hello"; x.starts_with("he") && x.ends_with('o') && !(x.ends_with("x"))"#
+    )?);
+    assert_eq!(
+        engine.eval::<String>(r#"let x = "  hi  "; x.trim_start(); x + "|""#)?,
+        "hi  |"
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"let x = "  hi  "; x.trim_end(); x + "|""#)?,
+        "  hi|"
+    );
+    assert_eq!(engine.eval::<String>(r#""ab".repeat(3)"#)?, "ababab");
+    assert_eq!(
+        engine.eval::<INT>(r#""❤ hello ❤ hello".last_index_of("hello")"#)?,
+        10
+    );
+    assert_eq!(engine.eval::<INT>(r#""hello".last_index_of('x')"#)?, -1);
+
+    assert_eq!(engine.eval::<INT>(r#"parse_int("42")"#)?, 42);
+    assert_eq!(engine.eval::<INT>(r#"parse_int(" ff ", 16)"#)?, 255);
+    assert!(matches!(
+        *engine.eval::<INT>(r#"parse_int("4x2")"#).expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(_, _)
+    ));
+    assert!(matches!(
+        *engine.eval::<INT>(r#"parse_int("42", 1)"#).expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(_, _)
+    ));
+
+    #[cfg(not(feature = "no_float"))]
+    assert_eq!(engine.eval::<rhai::FLOAT>(r#"parse_float("1.5")"#)?, 1.5);
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert_eq!(
+            engine.eval::<String>(r#""a,b,,c".split(",").join("|")"#)?,
+            "a|b||c"
+        );
+        assert_eq!(
+            engine.eval::<String>(r#""a b  c".split(' ', 2).join('|')"#)?,
+            "a|b  c"
+        );
+        assert_eq!(
+            engine.eval::<String>(r#""a-b-c".splitn(2, "-").join("|")"#)?,
+            "a|b-c"
+        );
+        assert_eq!(
+            engine.eval::<String>(r#""  a  b ".split().join("|")"#)?,
+            "a|b"
+        );
+        assert_eq!(
+            engine.eval::<String>(r#""one\ntwo\r\nthree".lines().join(",")"#)?,
+            "one,two,three"
+        );
+        assert_eq!(
+            engine.eval::<String>(r#"[1, "x", 'y', true].join(", ")"#)?,
+            "1, x, y, true"
+        );
+        assert_eq!(
+            engine.eval::<INT>(r#"let n = 0; for ch in "abc".chars() { n += 1; } n"#)?,
+            3
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_string_format() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    assert_eq!(engine.eval::<String>(r#"format("{{{}}}", "x")"#)?, "{x}");
+    assert_eq!(
+        engine.eval::<String>(r#"format("[{:>5}]", 42)"#)?,
+        "[   42]"
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert_eq!(
+            engine.eval::<String>(r#"format("{:>5}|{:<4}|{:^5}|", [42, "ab", 'c'])"#)?,
+            "   42|ab  |  c  |"
+        );
+        assert_eq!(
+            engine.eval::<String>(
+                r#"format("{:x} {:#X} {:08b} {:+} {:+05}", [255, 255, 5, 3, -3])"#
+            )?,
+            "ff 0xFF 00000101 +3 -0003"
+        );
+        assert_eq!(
+            engine.eval::<String>(r#"format("{1}-{0}-{}", ["a", "b"])"#)?,
+            "b-a-a"
+        );
+
+        #[cfg(not(feature = "no_float"))]
+        assert_eq!(
+            engine.eval::<String>(r#"format("{:>8.2}|{:.1e}", [3.14159, 1234.5])"#)?,
+            "    3.14|1.2e3"
+        );
+
+        assert!(matches!(
+            *engine
+                .eval::<String>(r#"format("{} {}", [1])"#)
+                .expect_err("should error"),
+            EvalAltResult::ErrorRuntime(_, _)
+        ));
+        assert!(matches!(
+            *engine
+                .eval::<String>(r#"format("{:x}", ["hello"])"#)
+                .expect_err("should error"),
+            EvalAltResult::ErrorRuntime(_, _)
+        ));
+
+        #[derive(Clone)]
+        struct TestStruct(INT);
+
+        engine
+            .register_type_with_name::<TestStruct>("TestStruct")
+            .register_fn("new_ts", || TestStruct(42))
+            .register_fn("to_string", |x: &mut TestStruct| format!("TS({})", x.0));
+
+        assert_eq!(
+            engine.eval::<String>(r#"format("<{:>7}>", [new_ts()])"#)?,
+            "< TS(42)>"
+        );
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval::<String>(r#"format("{name} is {age:03}", #{name: "Bob", age: 7})"#)?,
+        "Bob is 007"
+    );
+
+    Ok(())
+}
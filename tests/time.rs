@@ -1,7 +1,9 @@
 #![cfg(not(feature = "no_std"))]
 #![cfg(not(target_arch = "wasm32"))]
 
-use rhai::{Engine, EvalAltResult, INT};
+use rhai::{DateTime, Duration, Engine, EvalAltResult, INT};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
 
 #[cfg(not(feature = "no_float"))]
 use rhai::FLOAT;
@@ -38,3 +40,184 @@ fn test_timestamp() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_datetime_api() {
+    let dt = DateTime::from_ymd_hms(2020, 2, 29, 13, 45, 30).unwrap();
+    assert_eq!(dt.to_string(), "2020-02-29T13:45:30Z");
+    assert_eq!(dt.weekday(), 6);
+    assert_eq!(dt.day_of_year(), 60);
+
+    assert!(DateTime::from_ymd(2019, 2, 29).is_none());
+    assert!(DateTime::from_ymd(10000, 1, 1).is_none());
+
+    let dt = DateTime::from_unix_timestamp(1_600_000_000).unwrap();
+    assert_eq!(dt.to_string(), "2020-09-13T12:26:40Z");
+    assert_eq!(DateTime::from_unix_timestamp(0).unwrap().weekday(), 4);
+
+    let dt = DateTime::parse("1969-12-31T23:59:59.5-01:30").unwrap();
+    assert_eq!(dt.unix_timestamp(), 5399);
+    assert_eq!(dt.offset(), -90);
+    assert_eq!((dt.hour(), dt.nanosecond()), (23, 500_000_000));
+    assert_eq!(dt.to_string(), "1969-12-31T23:59:59.5-01:30");
+    assert_eq!(dt.to_utc().to_string(), "1970-01-01T01:29:59.5Z");
+    assert_eq!(dt, dt.to_utc());
+
+    assert_eq!(
+        DateTime::parse("2020-01-31").unwrap(),
+        DateTime::from_ymd(2020, 1, 31).unwrap()
+    );
+    assert!(DateTime::parse("2020-01-31T25:00").is_none());
+    assert!(DateTime::parse("2020-1-31").is_none());
+
+    let jan31 = DateTime::from_ymd(2021, 1, 31).unwrap();
+    assert_eq!(
+        jan31.checked_add_months(1).unwrap().to_string(),
+        "2021-02-28T00:00:00Z"
+    );
+    assert_eq!(
+        jan31.checked_add_months(-13).unwrap().to_string(),
+        "2019-12-31T00:00:00Z"
+    );
+
+    let d = Duration::from_secs(93_784)
+        .checked_add(Duration::from_millis(500))
+        .unwrap();
+    assert_eq!(d.to_string(), "P1DT2H3M4.5S");
+    assert_eq!(d.checked_neg().unwrap().to_string(), "-P1DT2H3M4.5S");
+    assert_eq!(Duration::default().to_string(), "PT0S");
+}
+
+#[test]
+fn test_datetime() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<String>(r#"type_of(datetime(2020, 1, 1))"#)?,
+        "DateTime"
+    );
+    assert_eq!(engine.eval::<String>(r#"type_of(days(1))"#)?, "Duration");
+
+    assert_eq!(
+        engine
+            .eval::<String>(r#"(datetime(2020, 12, 31, 23, 59, 59) + seconds(1)).to_string()"#)?,
+        "2021-01-01T00:00:00Z"
+    );
+    assert_eq!(
+        engine.eval::<String>(
+            r#"let d = parse_datetime("2020-03-01T12:00+08:00"); (d - days(1)).to_string()"#
+        )?,
+        "2020-02-29T12:00:00+08:00"
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"(datetime(2021, 1, 1) - datetime(2020, 1, 1)).total_days"#)?,
+        366
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let d = datetime_from_unix(1600000000).to_offset(330);
+                d.year * 10000 + d.month * 100 + d.day
+            "#
+        )?,
+        20200913
+    );
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let d = parse_datetime("2020-09-13T17:56:40+05:30");
+                d.hour + ":" + d.minute + " " + d.offset + " " + d.weekday + " " + d.day_of_year
+            "#
+        )?,
+        "17:56 330 7 257"
+    );
+    assert!(engine.eval::<bool>(
+        r#"
+            let d1 = parse_datetime("2020-09-13T17:56:40+05:30");
+            let d2 = datetime_from_unix(1600000000);
+            d1 == d2 && d1 <= d2 && d1 < d2 + milliseconds(1)
+        "#
+    )?);
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let d = hours(1) * 3 - minutes(10);
+                d += seconds(5);
+                d.to_string()
+            "#
+        )?,
+        "PT2H50M5S"
+    );
+    assert_eq!(
+        engine
+            .eval::<String>(r#"datetime(2020, 2, 29).add_years(1).add_months(-1).to_string()"#)?,
+        "2021-01-28T00:00:00Z"
+    );
+    assert!(engine.eval::<bool>(r#"now() > datetime(2020, 1, 1)"#)?);
+
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"datetime(2021, 2, 29)"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorRuntime(_, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"parse_datetime("yesterday")"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorRuntime(_, _)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<()>(r#"datetime(9999, 12, 31) + days(1)"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(_, _)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_clock() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.set_clock(|| DateTime::parse("2020-02-29T12:00:00Z").unwrap());
+
+    assert_eq!(
+        engine.eval::<String>("now().to_string()")?,
+        "2020-02-29T12:00:00Z"
+    );
+    assert!(engine.eval::<bool>("let t = timestamp(); t == timestamp()")?);
+
+    // Stepped clock - advances one second on every reading
+    let seconds = Arc::new(AtomicI64::new(0));
+    let clock = seconds.clone();
+    engine.set_clock(move || {
+        let secs = clock.fetch_add(1, Ordering::SeqCst);
+        DateTime::from_unix_timestamp(1_600_000_000 + secs as INT).unwrap()
+    });
+
+    assert_eq!(
+        engine.eval::<String>("now().to_string()")?,
+        "2020-09-13T12:26:41Z"
+    );
+
+    #[cfg(not(feature = "no_float"))]
+    assert_eq!(
+        engine.eval::<FLOAT>("let t = timestamp(); elapsed(t)")?,
+        1.0
+    );
+    #[cfg(feature = "no_float")]
+    assert_eq!(engine.eval::<INT>("let t = timestamp(); elapsed(t)")?, 1);
+
+    engine.set_profiling(true);
+    engine.consume("fn foo() { 42 } foo();")?;
+
+    let profile = engine.take_profile().unwrap();
+    assert_eq!(
+        profile.get_fn("foo").unwrap().inclusive_time,
+        std::time::Duration::from_secs(1)
+    );
+
+    Ok(())
+}